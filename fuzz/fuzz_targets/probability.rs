@@ -0,0 +1,19 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use hero_guild_bevy::fuzzing;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    difficulty_level: u8,
+    hero_levels: Vec<u32>,
+    percent: i32,
+}
+
+fuzz_target!(|input: Input| {
+    let difficulty_level = 1 + u32::from(input.difficulty_level) % 20; // 1-20
+    let party_size = input.hero_levels.len().min(10); // 0-10 heroes
+    fuzzing::check_probability_of_quest_success(difficulty_level, &input.hero_levels[..party_size]);
+    fuzzing::check_percent_distribution(input.percent);
+});
@@ -0,0 +1,15437 @@
+use bevy::ecs::query::QueryData;
+use bevy::prelude::*;
+#[cfg(any(test, feature = "stress"))]
+use bevy::state::app::StatesPlugin;
+use rand::{Rng, RngCore, SeedableRng, distr::Distribution, seq::IndexedRandom};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+mod percent;
+use percent::Percent;
+
+#[cfg(test)]
+use test_helpers::{
+    install_mock_rng, last_notification, link_hero_to_quest, make_test_app, quest_complete_event,
+    send_quest_complete, send_turns, spawn_hero, spawn_quest,
+};
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+struct Turn(u32);
+
+#[derive(Event)]
+struct TurnDeltaEvent(u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SeasonKind {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl SeasonKind {
+    // Each season lasts 100 turns, and the year starts in Spring.
+    fn for_turn(turn: u32) -> SeasonKind {
+        match turn / 100 % 4 {
+            0 => SeasonKind::Spring,
+            1 => SeasonKind::Summer,
+            2 => SeasonKind::Autumn,
+            _ => SeasonKind::Winter,
+        }
+    }
+
+    // Added to a quest's difficulty when working out its chance of success.
+    fn difficulty_modifier(&self) -> u32 {
+        match self {
+            SeasonKind::Winter => 1,
+            _ => 0,
+        }
+    }
+
+    fn success_modifier(&self) -> Percent {
+        match self {
+            SeasonKind::Autumn => Percent(-10),
+            _ => Percent(0),
+        }
+    }
+
+    fn gold_multiplier(&self) -> f32 {
+        match self {
+            SeasonKind::Summer => 1.05,
+            SeasonKind::Winter => 1.30,
+            _ => 1.0,
+        }
+    }
+}
+
+// The current season, derived from the Turn.
+#[derive(Resource)]
+struct Season {
+    current: SeasonKind,
+    turn_of_last_change: u32,
+}
+
+impl Default for Season {
+    fn default() -> Self {
+        Season {
+            current: SeasonKind::Spring,
+            turn_of_last_change: 0,
+        }
+    }
+}
+
+#[derive(Event)]
+struct SeasonChangeEvent(SeasonKind);
+
+// Ordered from fairest to foulest, so each condition can only change into its neighbours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WeatherCondition {
+    Clear,
+    Rainy,
+    Stormy,
+    Blizzard,
+}
+
+impl WeatherCondition {
+    fn success_modifier(&self) -> Percent {
+        match self {
+            WeatherCondition::Clear => Percent(0),
+            WeatherCondition::Rainy => Percent(-5),
+            WeatherCondition::Stormy => Percent(-15),
+            WeatherCondition::Blizzard => Percent(-25),
+        }
+    }
+
+    fn fairer(&self) -> Option<WeatherCondition> {
+        match self {
+            WeatherCondition::Clear => None,
+            WeatherCondition::Rainy => Some(WeatherCondition::Clear),
+            WeatherCondition::Stormy => Some(WeatherCondition::Rainy),
+            WeatherCondition::Blizzard => Some(WeatherCondition::Stormy),
+        }
+    }
+
+    fn fouler(&self) -> Option<WeatherCondition> {
+        match self {
+            WeatherCondition::Clear => Some(WeatherCondition::Rainy),
+            WeatherCondition::Rainy => Some(WeatherCondition::Stormy),
+            WeatherCondition::Stormy => Some(WeatherCondition::Blizzard),
+            WeatherCondition::Blizzard => None,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct CurrentWeather(WeatherCondition);
+
+impl Default for CurrentWeather {
+    fn default() -> Self {
+        CurrentWeather(WeatherCondition::Clear)
+    }
+}
+
+#[derive(Event)]
+struct WeatherChangeEvent(WeatherCondition);
+
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    #[default]
+    Setup, // The starting guild is being created
+    Running,
+    Paused,
+    Won,
+    Lost,
+}
+
+// Systems that advance the game, which only run while the game is Running.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+struct GameplaySystems;
+
+#[derive(Event)]
+struct PauseEvent;
+
+#[derive(Event)]
+struct ResumeEvent;
+
+#[derive(Event)]
+struct GameOverEvent {
+    reason: GameOverReason,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GameOverReason {
+    Victory,       // The guild reached the highest rank, winning the game
+    Bankruptcy,    // The guild couldn't pay its heroes for too long, losing the game
+    AllHeroesDead, // The guild has no heroes left, not even recruits, losing the game
+}
+
+#[derive(Resource, Default)]
+struct Notificiations(Vec<Notification>);
+
+struct Notification {
+    message: String,
+    is_unread: bool,
+}
+
+#[derive(Event)]
+struct NotificationEvent(String);
+
+// The game's RNG. Boxed so tests can swap in an RNG with pre-programmed outcomes.
+#[derive(Resource)]
+struct RandomSource(Box<dyn RngCore + Send + Sync>);
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+struct Guild {
+    gold: u64,
+}
+
+// Payroll the guild couldn't cover, which must be paid off before the guild can keep any more gold.
+#[derive(Resource, Default, Serialize, Deserialize)]
+struct GuildDebt {
+    amount: u64,
+    turns_in_debt: u32, // Consecutive turns the guild has owed anything
+}
+
+impl GuildDebt {
+    // Pay down as much of the debt as the incoming gold allows, returning whatever gold is left over.
+    fn pay_down(&mut self, gold: u64) -> u64 {
+        let payment = gold.min(self.amount);
+        self.amount -= payment;
+        if self.amount == 0 {
+            self.turns_in_debt = 0;
+        }
+        gold - payment
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize)]
+struct Supplies(u32); // Food and other supplies the guild's heroes eat through each turn
+
+impl Default for Supplies {
+    fn default() -> Self {
+        Supplies(100)
+    }
+}
+
+#[derive(Resource)]
+struct ConsumptionConfig {
+    supplies_per_hero_per_turn: u32,
+    gold_per_supply: u32, // Price of each supply bought
+}
+
+impl Default for ConsumptionConfig {
+    fn default() -> Self {
+        ConsumptionConfig {
+            supplies_per_hero_per_turn: 1,
+            gold_per_supply: 2,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct GuildConfig {
+    base_gold_cap: u64, // Most gold the guild's vault can hold without a Treasury
+    gold_cap_per_treasury: u64, // Extra gold the vault can hold for each operational Treasury
+    baseline_effectiveness: i32, // Effectiveness percentage if hero level matches difficulty level
+    effectiveness_per_level: i32, // Effectiveness gained for each level a hero is above the difficulty level
+    rng_seed: u64,                // Seed for the game's RNG
+    starting_gold: u64,           // Gold in the vault when a new game starts
+    gold_per_level_per_turn: u32, // Each hero is paid this much per level, every turn
+    max_rank: u32,                // The guild can't advance past this rank
+    bankruptcy_threshold: u32, // The guild goes bankrupt once it's been in debt for more turns than this
+    base_hero_capacity: usize, // Most heroes the guild can hold without a Barracks
+}
+
+impl Default for GuildConfig {
+    fn default() -> Self {
+        GuildConfig {
+            base_gold_cap: 1000,
+            gold_cap_per_treasury: 5000,
+            baseline_effectiveness: 70,
+            effectiveness_per_level: 20,
+            rng_seed: 42,
+            starting_gold: 0,
+            gold_per_level_per_turn: 10,
+            max_rank: 10,
+            bankruptcy_threshold: 5,
+            base_hero_capacity: 5,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct FallenHeroes(Vec<FallenHeroRecord>);
+
+// Which heroes are the parents of which legacy heroes, looked up in either direction.
+#[derive(Resource, Default)]
+struct FamilyTree {
+    parent_to_children: HashMap<Entity, Vec<Entity>>,
+    child_to_parents: HashMap<Entity, Vec<Entity>>,
+}
+
+struct FallenHeroRecord {
+    name: String,
+    level: u32,
+    class: HeroClass,
+    quest_count: QuestCount,
+    biography: Biography, // Only the last few entries of the hero's biography
+}
+
+#[derive(Resource, Default)]
+struct RetiredHeroes(Vec<RetiredHeroRecord>);
+
+struct RetiredHeroRecord {
+    name: String,
+    final_level: u32,
+}
+
+// How much reputation from retired heroes has already been added to GuildReputation.
+#[derive(Resource, Default)]
+struct RetirementBonus(i32);
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+struct GuildReputation(i32);
+
+#[derive(Resource)]
+struct GuildMorale(i32); // 0-100, where 50 is the guild's usual spirits. Raised by successes and lowered by failures.
+
+impl Default for GuildMorale {
+    fn default() -> Self {
+        GuildMorale(50)
+    }
+}
+
+impl GuildMorale {
+    // How much quest gold rewards are scaled by the guild's spirits.
+    fn gold_multiplier(&self) -> f32 {
+        (self.0 as f32 / 50.0).clamp(0.5, 1.5)
+    }
+}
+
+#[derive(Resource)]
+struct GuildStats {
+    average_hero_level: u32,
+    total_successful_quests: u32,
+    total_quests_completed: u32, // Every quest the guild has finished, whether or not it succeeded
+    total_quests_failed: u32,
+    total_gold_earned: u64,
+    total_exp_awarded: u64, // Summed across every hero on each quest
+    total_heroes_hired: u32,
+    total_heroes_lost: u32, // Heroes who have died or retired
+}
+
+impl Default for GuildStats {
+    fn default() -> Self {
+        GuildStats {
+            average_hero_level: 1,
+            total_successful_quests: 0,
+            total_quests_completed: 0,
+            total_quests_failed: 0,
+            total_gold_earned: 0,
+            total_exp_awarded: 0,
+            total_heroes_hired: 0,
+            total_heroes_lost: 0,
+        }
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize)]
+struct GuildRank(u32);
+
+impl Default for GuildRank {
+    fn default() -> Self {
+        GuildRank(1)
+    }
+}
+
+// The guild hall's level. Each level past the first unlocks something new.
+#[derive(Resource)]
+struct GuildHallLevel(u32);
+
+impl Default for GuildHallLevel {
+    fn default() -> Self {
+        GuildHallLevel(1)
+    }
+}
+
+impl GuildHallLevel {
+    const MAX_LEVEL: u32 = 5; // The guild hall has nothing left to unlock past this level
+
+    fn quest_board_bonus(&self) -> usize {
+        if self.0 >= 2 { 1 } else { 0 }
+    }
+
+    fn gold_reward_multiplier(&self) -> f32 {
+        if self.0 >= 3 { 1.1 } else { 1.0 }
+    }
+
+    fn hero_capacity_bonus(&self) -> usize {
+        if self.0 >= 4 { 5 } else { 0 }
+    }
+
+    fn boss_quests_enabled(&self) -> bool {
+        self.0 >= 5
+    }
+
+    // What reaching this level unlocks, for telling the player.
+    fn unlock_description(&self) -> &'static str {
+        match self.0 {
+            2 => "the quest board holds one more quest",
+            3 => "quests pay 10% more gold",
+            4 => "the guild can house 5 more heroes",
+            5 => "boss quests can be posted",
+            _ => "nothing new",
+        }
+    }
+}
+
+#[derive(Resource)]
+struct GuildRankThresholds(Vec<u32>); // Index N - 1 holds the successful quests needed to advance past rank N
+
+impl Default for GuildRankThresholds {
+    fn default() -> Self {
+        GuildRankThresholds(vec![5, 15, 30, 50, 75, 105, 140, 180, 225])
+    }
+}
+
+// Another guild competing for the same quests.
+struct RivalGuild {
+    name: String,
+    claim_probability_per_turn: f32, // Chance each turn of claiming each available quest, when as strong as the guild's rank
+    strength: u32,
+}
+
+#[derive(Resource)]
+struct RivalGuilds(Vec<RivalGuild>);
+
+impl Default for RivalGuilds {
+    fn default() -> Self {
+        let rival = |name: &str, claim_probability_per_turn, strength| RivalGuild {
+            name: name.to_string(),
+            claim_probability_per_turn,
+            strength,
+        };
+        RivalGuilds(vec![
+            rival("The Iron Wolves", 0.02, 1),
+            rival("The Gilded Hand", 0.01, 2),
+        ])
+    }
+}
+
+// How long the guild has gone without advancing its rank, giving the rival guilds time to grow.
+#[derive(Resource, Default)]
+struct RivalGrowth {
+    last_rank: u32,
+    turns_without_advance: u32,
+}
+
+// What happens to the guild when a random event strikes.
+#[derive(Clone, Copy)]
+enum RandomEventEffect {
+    GoldWindfall(u32),
+    BanditRaid { gold_loss: u32 }, // Also closes the guild's trade routes
+    HeroInspiration,               // Every hero's morale rises
+    GuildFire,                     // Every hero's morale falls
+}
+
+struct RandomEventDefinition {
+    name: String,
+    weight: u32, // Relative chance of this event, when a random event strikes
+    effect: RandomEventEffect,
+}
+
+#[derive(Resource)]
+struct RandomEventConfig {
+    event_probability_per_turn: f32, // Chance each turn of any random event striking
+    events: Vec<RandomEventDefinition>,
+}
+
+impl Default for RandomEventConfig {
+    fn default() -> Self {
+        let event = |name: &str, weight, effect| RandomEventDefinition {
+            name: name.to_string(),
+            weight,
+            effect,
+        };
+        RandomEventConfig {
+            event_probability_per_turn: 0.02,
+            events: vec![
+                event("A Generous Patron", 3, RandomEventEffect::GoldWindfall(100)),
+                event(
+                    "Bandit Raid",
+                    2,
+                    RandomEventEffect::BanditRaid { gold_loss: 50 },
+                ),
+                event("A Bard's Tale", 2, RandomEventEffect::HeroInspiration),
+                event("Fire in the Guild Hall", 1, RandomEventEffect::GuildFire),
+            ],
+        }
+    }
+}
+
+// Story beats the guild reaches as it completes more quests.
+#[derive(Resource)]
+struct ChapterConfig {
+    milestones: Vec<Milestone>,
+    completed_milestones: HashSet<u32>, // quests_required of each milestone already reached, so none fire twice
+}
+
+struct Milestone {
+    quests_required: u32, // Successful quests to reach this milestone
+    title: String,
+    narrative: String,
+    gold_reward: u32,
+}
+
+impl Default for ChapterConfig {
+    fn default() -> Self {
+        let milestone = |quests_required, title: &str, narrative: &str, gold_reward| Milestone {
+            quests_required,
+            title: title.to_string(),
+            narrative: narrative.to_string(),
+            gold_reward,
+        };
+        ChapterConfig {
+            milestones: vec![
+                milestone(
+                    1,
+                    "A Humble Beginning",
+                    "Word spreads that a new guild has finished its first job.",
+                    50,
+                ),
+                milestone(
+                    10,
+                    "Making a Name",
+                    "Townsfolk have started asking for the guild by name.",
+                    200,
+                ),
+                milestone(
+                    25,
+                    "Tales in the Tavern",
+                    "Bards sing of the guild's deeds in every tavern in the realm.",
+                    500,
+                ),
+                milestone(
+                    50,
+                    "Heroes of the Realm",
+                    "The crown itself sends its thanks, and a purse of gold.",
+                    1000,
+                ),
+                milestone(
+                    100,
+                    "Living Legends",
+                    "The guild's hall has become a place of pilgrimage.",
+                    2500,
+                ),
+            ],
+            completed_milestones: HashSet::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum AchievementId {
+    FirstQuestCompleted,
+    FirstHeroDeath,
+    FirstLevelFive,
+    GoldHoard,
+    FiveHeroesHired,
+    TenQuestsSucceeded,
+    FirstRetirement,
+    FirstBuilding,
+    FirstTradeRoute,
+    RankFive,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum AchievementReward {
+    Gold(u64),
+    Reputation(i32),
+    MoraleBoost(i32), // Raises the morale of every hero in the guild
+}
+
+struct AchievementDefinition {
+    id: AchievementId,
+    description: String,
+    condition: fn(&World) -> bool,
+    reward: AchievementReward,
+}
+
+// Whether each achievement has been completed. Achievements that haven't been checked yet are missing.
+#[derive(Resource, Default)]
+struct Achievements(HashMap<AchievementId, bool>);
+
+// Walks a new player through their first quest. While enabled, the tutorial's own quest and hero
+// replace the randomly generated ones.
+#[derive(Resource, Default)]
+struct TutorialMode {
+    enabled: bool,
+    step: u32, // 0: not started, 1: quest posted, 2: heroes sent out, 3: quest complete and tutorial over
+}
+
+#[derive(Resource)]
+struct QuestGenerationConfig {
+    cadence_turns: u32, // Turns between each batch of new quests
+    min_per_batch: u32, // Fewest quests generated in a batch
+    max_per_batch: u32, // Most quests generated in a batch
+    difficulty_offset_range: RangeInclusive<i32>, // Offset from the average hero level for new quest difficulty
+    item_reward_chance: Percent, // Chance that each new quest offers an item reward
+    boss_quest_chance: Percent, // Chance that each new quest is a boss quest, once the guild hall has unlocked them
+}
+
+#[derive(Resource)]
+struct RelationshipConfig {
+    decay_interval_turns: u32, // Turns between each step of relationship decay
+    decay_rate: i32,           // How far each relationship moves toward neutral per step
+    min_value: i32,            // Lowest possible opinion of another hero
+    max_value: i32,            // Highest possible opinion of another hero
+}
+
+impl Default for RelationshipConfig {
+    fn default() -> Self {
+        RelationshipConfig {
+            decay_interval_turns: 20,
+            decay_rate: 1,
+            min_value: -10,
+            max_value: 10,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct FatigueConfig {
+    rest_multiplier: f32, // Turns of rest needed per turn spent on a quest
+    min_rest_turns: u32,  // Heroes always rest at least this long after a quest
+}
+
+impl Default for FatigueConfig {
+    fn default() -> Self {
+        FatigueConfig {
+            rest_multiplier: 0.5,
+            min_rest_turns: 1,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct TavernConfig {
+    rest_turns: u32,               // Turns a hero spends resting at the Tavern
+    rest_recovery_multiplier: i32, // Resting heroes recover morale this many times as fast
+    rested_turns: u32,             // Turns a hero stays well-rested after resting
+}
+
+impl Default for TavernConfig {
+    fn default() -> Self {
+        TavernConfig {
+            rest_turns: 5,
+            rest_recovery_multiplier: 3,
+            rested_turns: 10,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct MoraleConfig {
+    recovery_rate: i32,   // How far morale moves back toward 50 each turn
+    success_boost: i32,   // Morale gained by each hero on a successful quest
+    failure_penalty: i32, // Morale lost by each hero on a failed quest
+}
+
+impl Default for MoraleConfig {
+    fn default() -> Self {
+        MoraleConfig {
+            recovery_rate: 1,
+            success_boost: 5,
+            failure_penalty: 10,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct QuestBoardConfig {
+    max_available_quests: usize, // No new quests are generated while the board has this many available
+}
+
+impl Default for QuestBoardConfig {
+    fn default() -> Self {
+        QuestBoardConfig {
+            max_available_quests: 10,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct QuestAutoAssign {
+    enabled: bool, // Whether idle heroes are sent on quests automatically
+    min_success_probability: Percent, // Parties must beat this success probability to be sent
+}
+
+impl Default for QuestAutoAssign {
+    fn default() -> Self {
+        QuestAutoAssign {
+            enabled: false,
+            min_success_probability: Percent(50),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct RecruitmentConfig {
+    cadence_turns: u32,        // Turns between each new recruit
+    turns_to_expiry: u32,      // Turns a recruit waits to be hired before leaving
+    hiring_fee_per_level: u32, // Gold cost to hire a recruit, per level
+}
+
+impl Default for RecruitmentConfig {
+    fn default() -> Self {
+        RecruitmentConfig {
+            cadence_turns: 15,
+            turns_to_expiry: 10,
+            hiring_fee_per_level: 50,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct MarketConfig {
+    refresh_interval_turns: u32, // Turns between each restock of the market
+    min_listings: u32,           // Fewest items listed after a restock
+    max_listings: u32,           // Most items listed after a restock
+}
+
+impl Default for MarketConfig {
+    fn default() -> Self {
+        MarketConfig {
+            refresh_interval_turns: 10,
+            min_listings: 3,
+            max_listings: 5,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct Market {
+    listings: Vec<MarketListing>,
+}
+
+// Scales every market price. Drifts a little each turn, so prices rise and fall over time.
+#[derive(Resource)]
+struct MarketPriceModifier(f32);
+
+impl Default for MarketPriceModifier {
+    fn default() -> Self {
+        MarketPriceModifier(1.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct MarketListing {
+    item: Item,
+    buy_price: u32,
+}
+
+#[derive(Resource)]
+struct HeroConfig {
+    max_level: u32,       // Heroes at this level retire instead of levelling up again
+    elder_threshold: u32, // Heroes older than this many turns are elders
+    turns_per_elder_penalty: u32, // Elders lose a level for each this many turns past it
+}
+
+#[derive(Resource)]
+struct LevelConfig {
+    base_exp: u32,     // Exp a level 1 hero needs to reach level 2
+    scale_factor: f32, // How steeply the exp needed grows with level
+}
+
+impl Default for LevelConfig {
+    fn default() -> Self {
+        LevelConfig {
+            base_exp: 100,
+            scale_factor: 1.5,
+        }
+    }
+}
+
+impl Default for HeroConfig {
+    fn default() -> Self {
+        HeroConfig {
+            max_level: 10,
+            elder_threshold: 500,
+            turns_per_elder_penalty: 100,
+        }
+    }
+}
+
+struct BuildingCost {
+    gold_cost: u64,          // Gold paid up front to start construction
+    construction_turns: u32, // Turns until the building is operational
+}
+
+#[derive(Resource)]
+struct GuildBuildingConfig {
+    costs: HashMap<BuildingType, BuildingCost>,
+}
+
+// A bonus that applies while both buildings of a pair are operational.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum SynergyBonus {
+    MoraleRecovery(Percent), // Heroes' morale recovers this much faster
+    ExpGain(Percent),        // Heroes gain this much more exp from quests
+}
+
+#[derive(Resource)]
+struct BuildingSynergyConfig {
+    synergies: Vec<(BuildingType, BuildingType, SynergyBonus)>,
+}
+
+impl Default for BuildingSynergyConfig {
+    fn default() -> Self {
+        BuildingSynergyConfig {
+            synergies: vec![
+                (
+                    BuildingType::Tavern,
+                    BuildingType::Barracks,
+                    SynergyBonus::MoraleRecovery(Percent(50)),
+                ),
+                (
+                    BuildingType::Library,
+                    BuildingType::TrainingGround,
+                    SynergyBonus::ExpGain(Percent(20)),
+                ),
+            ],
+        }
+    }
+}
+
+// The synergy bonuses of every pair of buildings that are both operational.
+#[derive(Resource, Default)]
+struct ActiveSynergies(Vec<SynergyBonus>);
+
+impl ActiveSynergies {
+    fn morale_recovery_bonus(&self) -> Percent {
+        self.0
+            .iter()
+            .filter_map(|bonus| match bonus {
+                SynergyBonus::MoraleRecovery(percent) => Some(*percent),
+                _ => None,
+            })
+            .sum()
+    }
+
+    fn exp_gain_bonus(&self) -> Percent {
+        self.0
+            .iter()
+            .filter_map(|bonus| match bonus {
+                SynergyBonus::ExpGain(percent) => Some(*percent),
+                _ => None,
+            })
+            .sum()
+    }
+}
+
+impl Default for GuildBuildingConfig {
+    fn default() -> Self {
+        let building_cost = |gold_cost, construction_turns| BuildingCost {
+            gold_cost,
+            construction_turns,
+        };
+        GuildBuildingConfig {
+            costs: HashMap::from([
+                (BuildingType::Barracks, building_cost(300, 10)),
+                (BuildingType::Library, building_cost(400, 15)),
+                (BuildingType::Tavern, building_cost(200, 8)),
+                (BuildingType::Blacksmith, building_cost(500, 15)),
+                (BuildingType::Treasury, building_cost(600, 20)),
+                (BuildingType::TrainingGround, building_cost(450, 12)),
+            ]),
+        }
+    }
+}
+
+impl Default for QuestGenerationConfig {
+    fn default() -> Self {
+        QuestGenerationConfig {
+            cadence_turns: 10,
+            min_per_batch: 1,
+            max_per_batch: 3,
+            difficulty_offset_range: -1..=1,
+            item_reward_chance: Percent(25),
+            boss_quest_chance: Percent(10),
+        }
+    }
+}
+
+#[derive(Component)]
+struct Hero;
+
+// Hero status markers
+#[derive(Component)]
+struct HeroStatusAvailable; // A recruit who can be hired, but hasn't joined the guild yet.
+
+#[derive(Component)]
+struct HeroStatusIdle; // A hero who isn't on a quest. Injured or Fatigued heroes are still idle, but can't be sent out.
+
+#[derive(Component)]
+struct HeroStatusOnQuest; // A hero who is away on a quest, as a child of the quest entity.
+
+#[derive(Component)]
+struct HeroStatusResting; // A hero resting at the Tavern until their TurnTimer runs out.
+
+#[derive(Component, Clone, Default, Serialize, Deserialize)]
+struct Person {
+    personality: Personality,
+    #[serde(with = "entity_relationships")]
+    relationships: HashMap<Entity, i32>,
+}
+
+// Saves relationships keyed by the other hero's entity bits, since Entity can't be serialized directly.
+mod entity_relationships {
+    use bevy::prelude::Entity;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::{BTreeMap, HashMap};
+
+    pub fn serialize<S: Serializer>(
+        relationships: &HashMap<Entity, i32>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        relationships
+            .iter()
+            .map(|(entity, value)| (entity.to_bits(), *value))
+            .collect::<BTreeMap<u64, i32>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<Entity, i32>, D::Error> {
+        let relationships = BTreeMap::<u64, i32>::deserialize(deserializer)?;
+        Ok(relationships
+            .into_iter()
+            .map(|(bits, value)| (Entity::from_bits(bits), value))
+            .collect())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+enum Personality {
+    #[default]
+    Friendly, // +1 opinion of party members after questing together, regardless of outcome
+    ResultOriented, // +1 opinion of party members if successful, -1 if not
+    Mirror,         // Moves toward the other person's opinion of them
+    Judgmental,     // -2 opinion of party members if they get injured, +1 otherwise.
+    Learner,        // +1 opinion of anyone stronger, -1 of anyone weaker
+    Teacher,        // +1 opinion of anyone weaker, -1 of anyone stronger
+    Brave, // +1 opinion of party members after questing together, and more effective on quests above their level
+    Cowardly, // -1 opinion of party members if the quest fails, and refuses quests well above their level
+    // Born to a bonded couple: changes opinion of party members by both parents' reactions to the quest's outcome
+    Legacy {
+        on_success: i32,
+        on_failure: i32,
+    },
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+enum HeroClass {
+    Warrior,
+    Tank,
+    Support,
+    Rogue,
+    Mage,
+}
+
+// Every hero class, for picking one at random or going through them all.
+const ALL_CLASSES: [HeroClass; 5] = [
+    HeroClass::Warrior,
+    HeroClass::Tank,
+    HeroClass::Support,
+    HeroClass::Rogue,
+    HeroClass::Mage,
+];
+
+impl fmt::Display for HeroClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            HeroClass::Warrior => "Warrior",
+            HeroClass::Tank => "Tank",
+            HeroClass::Support => "Support",
+            HeroClass::Rogue => "Rogue",
+            HeroClass::Mage => "Mage",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// The path a hero chooses on reaching level 5. Each class has its own specializations.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+enum Specialization {
+    Berserker,  // Warrior
+    Guardian,   // Warrior
+    Juggernaut, // Tank
+    Sentinel,   // Tank
+    Healer,     // Support
+    Bard,       // Support
+}
+
+impl Specialization {
+    // Rogues and Mages don't have any specializations yet.
+    fn options_for(class: &HeroClass) -> Vec<Specialization> {
+        match class {
+            HeroClass::Warrior => vec![Specialization::Berserker, Specialization::Guardian],
+            HeroClass::Tank => vec![Specialization::Juggernaut, Specialization::Sentinel],
+            HeroClass::Support => vec![Specialization::Healer, Specialization::Bard],
+            HeroClass::Rogue | HeroClass::Mage => vec![],
+        }
+    }
+
+    fn effectiveness_bonus(&self) -> i32 {
+        match self {
+            Specialization::Berserker => 15,
+            Specialization::Guardian => 5,
+            Specialization::Juggernaut => 10,
+            Specialization::Sentinel => 10,
+            Specialization::Healer => 10,
+            Specialization::Bard => 5,
+        }
+    }
+}
+
+#[derive(Component, Clone, Serialize, Deserialize)]
+struct LevelState {
+    level: u32,
+    exp: u32,
+    exp_to_next: u32,
+}
+
+// A fresh level 1 hero, needing the default LevelConfig's base exp to level up.
+impl Default for LevelState {
+    fn default() -> Self {
+        LevelState {
+            level: 1,
+            exp: 0,
+            exp_to_next: 100,
+        }
+    }
+}
+
+impl LevelState {
+    // The level a hero performs at on a quest. Elders lose levels with age, and an equipped item made for the hero's class is worth half a level, scaled by its rarity.
+    fn effective_level(
+        &self,
+        class: &HeroClass,
+        inventory: &Inventory,
+        age: &Age,
+        hero_config: &HeroConfig,
+    ) -> f32 {
+        let aged_level = self
+            .level
+            .saturating_sub(elder_level_penalty(age, hero_config)) as f32;
+        aged_level + equipment_level_bonus(class, inventory.equipped_item())
+    }
+}
+
+// An equipped item made for the hero's class is worth half a level, scaled by its rarity.
+fn equipment_level_bonus(class: &HeroClass, equipped: Option<&Item>) -> f32 {
+    let equipment_bonus = 0.5;
+    match equipped {
+        Some(item) if item.class == *class => equipment_bonus * item.rarity.bonus_multiplier(),
+        _ => 0.0,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Item {
+    class: HeroClass,
+    rarity: ItemRarity,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum ItemRarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl ItemRarity {
+    // Scales the bonus an equipped item gives, from +5% effectiveness for Common up to +30% for Legendary.
+    fn bonus_multiplier(&self) -> f32 {
+        match self {
+            ItemRarity::Common => 0.5,
+            ItemRarity::Uncommon => 1.0,
+            ItemRarity::Rare => 1.5,
+            ItemRarity::Epic => 2.0,
+            ItemRarity::Legendary => 3.0,
+        }
+    }
+
+    // Gold the market charges for an item of this rarity, before the MarketPriceModifier is applied.
+    fn base_price(&self) -> u32 {
+        match self {
+            ItemRarity::Common => 50,
+            ItemRarity::Uncommon => 100,
+            ItemRarity::Rare => 200,
+            ItemRarity::Epic => 400,
+            ItemRarity::Legendary => 1000,
+        }
+    }
+}
+
+impl fmt::Display for ItemRarity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ItemRarity::Common => "Common",
+            ItemRarity::Uncommon => "Uncommon",
+            ItemRarity::Rare => "Rare",
+            ItemRarity::Epic => "Epic",
+            ItemRarity::Legendary => "Legendary",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Pick a rarity for a new item. Most items are Common, and Legendary items are very rare.
+fn random_item_rarity(rng: &mut impl Rng) -> ItemRarity {
+    [
+        (ItemRarity::Common, 70.0),
+        (ItemRarity::Uncommon, 20.0),
+        (ItemRarity::Rare, 8.0),
+        (ItemRarity::Epic, 2.0),
+        (ItemRarity::Legendary, 0.5),
+    ]
+    .choose_weighted(rng, |(_, weight)| *weight)
+    .unwrap()
+    .0
+}
+
+#[derive(Component)]
+struct Injured; // Injured heroes can't be sent on quests until they recover.
+
+#[derive(Component)]
+struct Fatigued(TurnTimer); // Fatigued heroes must rest until the timer runs out before going on another quest.
+
+#[derive(Component)]
+struct HeroRested(TurnTimer); // Well-rested heroes aren't fatigued by their next quest, as long as it starts before the timer runs out.
+
+#[derive(Component)]
+struct HeroCooldown(TurnTimer); // Heroes just back from a quest can't be assigned to another until the timer runs out.
+
+#[derive(Component, Clone, Serialize, Deserialize)]
+struct Morale(i32); // 0-100, where 50 is a hero's usual state of mind.
+
+impl Default for Morale {
+    fn default() -> Self {
+        Morale(50)
+    }
+}
+
+#[derive(Component, Clone, Copy, Default, Serialize, Deserialize)]
+struct QuestCount {
+    completed: u32, // Quests this hero has succeeded at
+    failed: u32,    // Quests this hero has failed
+}
+
+#[derive(Component, Clone, Default, Serialize, Deserialize)]
+struct Age {
+    turns_alive: u32,
+}
+
+#[derive(Component, Clone, Default, Serialize, Deserialize)]
+struct Inventory {
+    items: Vec<Item>,
+    equipped: Option<usize>, // Index into items of the item the hero is using, if any
+}
+
+impl Inventory {
+    fn equipped_item(&self) -> Option<&Item> {
+        self.equipped.and_then(|index| self.items.get(index))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+enum SkillType {
+    Scouting,
+    Diplomacy,
+    Healing,
+    Combat,
+    Arcana,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Skill {
+    skill_type: SkillType,
+    level: u32,
+    uses: u32, // Quests that have used this skill since it last levelled up
+}
+
+// Every hero starts with every skill at level 0, and improves the ones their quests call for.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+struct Skills(Vec<Skill>);
+
+impl Default for Skills {
+    fn default() -> Self {
+        Skills(
+            [
+                SkillType::Scouting,
+                SkillType::Diplomacy,
+                SkillType::Healing,
+                SkillType::Combat,
+                SkillType::Arcana,
+            ]
+            .into_iter()
+            .map(|skill_type| Skill {
+                skill_type,
+                level: 0,
+                uses: 0,
+            })
+            .collect(),
+        )
+    }
+}
+
+impl Skills {
+    fn get_mut(&mut self, skill_type: SkillType) -> Option<&mut Skill> {
+        self.0
+            .iter_mut()
+            .find(|skill| skill.skill_type == skill_type)
+    }
+
+    // +5% effectiveness for each level of the skill the quest calls for.
+    fn effectiveness_bonus(&self, quest_type: QuestType) -> i32 {
+        let bonus_per_level = 5;
+        self.0
+            .iter()
+            .find(|skill| skill.skill_type == quest_type.skill())
+            .map_or(0, |skill| skill.level as i32 * bonus_per_level)
+    }
+}
+
+#[derive(Component)]
+struct Quest;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+enum BuildingType {
+    Barracks,
+    Library,
+    Tavern,
+    Blacksmith,
+    Treasury,
+    TrainingGround,
+}
+
+impl fmt::Display for BuildingType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            BuildingType::Barracks => "Barracks",
+            BuildingType::Library => "Library",
+            BuildingType::Tavern => "Tavern",
+            BuildingType::Blacksmith => "Blacksmith",
+            BuildingType::Treasury => "Treasury",
+            BuildingType::TrainingGround => "Training Ground",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Component)]
+struct GuildBuilding {
+    building_type: BuildingType,
+}
+
+#[derive(Component)]
+struct Operational; // A building whose construction is finished.
+
+#[derive(Component)]
+struct GuildHallUpgrade; // Marks the entity whose TurnTimer counts down the guild hall's next upgrade.
+
+#[derive(Component)]
+struct BossQuest; // A harder quest with bigger rewards, posted once the guild hall unlocks them.
+
+#[derive(Component)]
+struct RecruitGenerator; // Marks the entity whose repeating TurnTimer drives recruit generation.
+
+#[derive(Component)]
+struct QuestGenerator; // Marks the entity whose repeating TurnTimer drives quest generation.
+
+#[derive(Component)]
+struct RelationshipDecayTimer; // Marks the entity whose repeating TurnTimer drives relationship decay.
+
+#[derive(Component)]
+struct GuildReportTimer; // Marks the entity whose repeating TurnTimer drives the guild report.
+
+#[derive(Component)]
+struct MarketRefreshTimer; // Marks the entity whose repeating TurnTimer drives market restocks.
+
+// Quest status markers
+#[derive(Component)]
+struct QuestStatusAvailable;
+#[derive(Component)]
+struct QuestStatusInProgress;
+
+#[derive(Component)]
+struct TradeRouteActive {
+    gold_per_turn: u32, // Gold paid to the guild every turn while the route is open
+}
+
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+struct QuestDescription {
+    name: String,
+    difficulty_level: u32,
+    turns_to_complete: u32,
+    exp_reward: u32,
+    gold_reward: u32,
+    item_reward: Option<Item>,
+    turns_to_expiry: u32,
+    quest_type: QuestType,
+    min_party_size: u32,      // Fewest heroes that can be sent on the quest
+    max_party_size: u32,      // Most heroes that can be sent on the quest
+    faction: Option<Faction>, // Faction the quest is for, if any
+    alignment_affinity: Option<Alignment>, // Alignment the quest suits, if any
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+enum QuestType {
+    Dungeon,       // Favours Warriors
+    Escort,        // Favours Tanks
+    Investigation, // Favours Supports
+    Bounty,        // No class has an edge
+    TradeRoute,    // Pays out every turn once established, until bandits raid it
+}
+
+impl QuestType {
+    // The skill a hero needs most on this kind of quest.
+    fn skill(&self) -> SkillType {
+        match self {
+            QuestType::Dungeon => SkillType::Arcana, // Wards, traps and cursed relics
+            QuestType::Escort => SkillType::Healing, // Keeping the client alive
+            QuestType::Investigation => SkillType::Scouting,
+            QuestType::Bounty => SkillType::Combat,
+            QuestType::TradeRoute => SkillType::Diplomacy,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+enum Faction {
+    Order,
+    Chaos,
+    Nature,
+    Arcane,
+}
+
+impl Faction {
+    fn opposes(&self, other: &Faction) -> bool {
+        matches!(
+            (self, other),
+            (Faction::Order, Faction::Chaos)
+                | (Faction::Chaos, Faction::Order)
+                | (Faction::Nature, Faction::Arcane)
+                | (Faction::Arcane, Faction::Nature)
+        )
+    }
+}
+
+#[derive(Component)]
+struct HeroFaction(Faction);
+
+#[derive(Component)]
+struct Companion(Entity); // The hero this hero is permanently bonded to. Both heroes of a pair have one.
+
+#[derive(Component)]
+struct MourningDebuff; // A hero whose companion died, and who is permanently less effective for it.
+
+#[derive(Component)]
+struct Bonded(Entity); // The hero this hero has fallen for. Both heroes of a couple have one.
+
+#[derive(Component)]
+struct BondedSince {
+    turn: u32, // The turn the couple bonded
+}
+
+#[derive(Component)]
+struct LegacyParent; // A hero whose bond has already produced a legacy hero.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum BiographyEventType {
+    QuestCompleted,
+    QuestFailed,
+    LeveledUp,
+    Injured,
+    Retired,
+    LegacyBorn,
+}
+
+#[derive(Clone, Debug)]
+struct BiographyEntry {
+    turn: u32,
+    event_type: BiographyEventType,
+    description: String,
+}
+
+// The notable events of a hero's life, oldest first.
+#[derive(Component, Clone, Debug, Default)]
+struct Biography {
+    entries: Vec<BiographyEntry>,
+}
+
+impl Biography {
+    fn record(&mut self, turn: u32, event_type: BiographyEventType, description: String) {
+        self.entries.push(BiographyEntry {
+            turn,
+            event_type,
+            description,
+        });
+    }
+}
+
+// One line per entry, such as "Turn 12: Reached level 3".
+fn biography_summary(bio: &Biography) -> String {
+    bio.entries
+        .iter()
+        .map(|entry| format!("Turn {}: {}", entry.turn, entry.description))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// A memorial listing every fallen hero, with the end of their biography.
+fn display_fallen_heroes(fallen: &FallenHeroes) -> String {
+    if fallen.0.is_empty() {
+        return "No heroes have fallen".to_string();
+    }
+    fallen
+        .0
+        .iter()
+        .map(|record| {
+            let mut memorial = format!(
+                "{}, level {} {} ({} quests completed, {} failed)",
+                record.name,
+                record.level,
+                record.class,
+                record.quest_count.completed,
+                record.quest_count.failed
+            );
+            for line in biography_summary(&record.biography).lines() {
+                memorial.push_str("\n  ");
+                memorial.push_str(line);
+            }
+            memorial
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn display_fallen_heroes_lists_each_record() {
+    assert_eq!(
+        display_fallen_heroes(&FallenHeroes::default()),
+        "No heroes have fallen"
+    );
+
+    let mut biography = Biography::default();
+    biography.record(
+        4,
+        BiographyEventType::QuestCompleted,
+        "Completed The Dragon's Lair".to_string(),
+    );
+    biography.record(
+        9,
+        BiographyEventType::QuestFailed,
+        "Failed The Sunken Crypt".to_string(),
+    );
+    let fallen = FallenHeroes(vec![
+        FallenHeroRecord {
+            name: "Aldric the Steadfast".to_string(),
+            level: 3,
+            class: HeroClass::Warrior,
+            quest_count: QuestCount {
+                completed: 1,
+                failed: 1,
+            },
+            biography,
+        },
+        FallenHeroRecord {
+            name: "Brenna".to_string(),
+            level: 1,
+            class: HeroClass::Mage,
+            quest_count: QuestCount::default(),
+            biography: Biography::default(),
+        },
+    ]);
+    assert_eq!(
+        display_fallen_heroes(&fallen),
+        "Aldric the Steadfast, level 3 Warrior (1 quests completed, 1 failed)\n  \
+         Turn 4: Completed The Dragon's Lair\n  \
+         Turn 9: Failed The Sunken Crypt\n\
+         Brenna, level 1 Mage (0 quests completed, 0 failed)"
+    );
+}
+
+#[test]
+fn biography_summary_lists_every_entry() {
+    let mut biography = Biography::default();
+    assert_eq!(biography_summary(&biography), "");
+
+    biography.record(
+        0,
+        BiographyEventType::LegacyBorn,
+        "Born to Aldric and Brenna".to_string(),
+    );
+    biography.record(
+        4,
+        BiographyEventType::QuestCompleted,
+        "Completed The Dragon's Lair".to_string(),
+    );
+    biography.record(
+        5,
+        BiographyEventType::LeveledUp,
+        "Reached level 2".to_string(),
+    );
+    biography.record(
+        9,
+        BiographyEventType::QuestFailed,
+        "Failed The Sunken Crypt".to_string(),
+    );
+    biography.record(
+        9,
+        BiographyEventType::Injured,
+        "Injured on The Sunken Crypt".to_string(),
+    );
+    biography.record(
+        30,
+        BiographyEventType::Retired,
+        "Retired at level 2".to_string(),
+    );
+    assert_eq!(
+        biography_summary(&biography),
+        "Turn 0: Born to Aldric and Brenna\n\
+         Turn 4: Completed The Dragon's Lair\n\
+         Turn 5: Reached level 2\n\
+         Turn 9: Failed The Sunken Crypt\n\
+         Turn 9: Injured on The Sunken Crypt\n\
+         Turn 30: Retired at level 2"
+    );
+}
+
+#[derive(Component)]
+struct PermanentMoralePenalty {
+    amount: i32, // How far below 50 this hero's morale settles from now on
+}
+
+#[derive(Component)]
+struct Rival(Entity); // The hero this hero is trying to outdo. The rival doesn't have to feel the same way.
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+enum Alignment {
+    Law,
+    #[default]
+    Neutral,
+    Chaos,
+}
+
+impl Alignment {
+    fn opposes(&self, other: &Alignment) -> bool {
+        matches!(
+            (self, other),
+            (Alignment::Law, Alignment::Chaos) | (Alignment::Chaos, Alignment::Law)
+        )
+    }
+}
+
+impl QuestDescription {
+    fn difficulty_category(&self) -> QuestDifficulty {
+        match self.difficulty_level {
+            0..=2 => QuestDifficulty::Trivial,
+            3..=4 => QuestDifficulty::Easy,
+            5..=6 => QuestDifficulty::Medium,
+            7..=9 => QuestDifficulty::Hard,
+            _ => QuestDifficulty::Legendary,
+        }
+    }
+}
+
+// Builds a QuestDescription, filling in sensible defaults for anything not set.
+struct QuestDescriptionBuilder {
+    description: QuestDescription,
+}
+
+impl QuestDescriptionBuilder {
+    fn new() -> Self {
+        QuestDescriptionBuilder {
+            description: QuestDescription {
+                name: "Unknown Quest".to_string(),
+                difficulty_level: 1,
+                turns_to_complete: 5,
+                exp_reward: 50,
+                gold_reward: 100,
+                item_reward: None,
+                turns_to_expiry: 10,
+                quest_type: QuestType::Bounty,
+                min_party_size: 1,
+                max_party_size: 3,
+                faction: None,
+                alignment_affinity: None,
+            },
+        }
+    }
+
+    fn difficulty(mut self, difficulty_level: u32) -> Self {
+        self.description.difficulty_level = difficulty_level;
+        self
+    }
+
+    fn turns(mut self, turns_to_complete: u32) -> Self {
+        self.description.turns_to_complete = turns_to_complete;
+        self
+    }
+
+    fn exp(mut self, exp_reward: u32) -> Self {
+        self.description.exp_reward = exp_reward;
+        self
+    }
+
+    fn gold(mut self, gold_reward: u32) -> Self {
+        self.description.gold_reward = gold_reward;
+        self
+    }
+
+    // Quest generation rolls item rewards after building, so only tests set them up front.
+    #[cfg(test)]
+    fn item(mut self, item: Item) -> Self {
+        self.description.item_reward = Some(item);
+        self
+    }
+
+    fn expiry(mut self, turns_to_expiry: u32) -> Self {
+        self.description.turns_to_expiry = turns_to_expiry;
+        self
+    }
+
+    fn name(mut self, name: String) -> Self {
+        self.description.name = name;
+        self
+    }
+
+    fn quest_type(mut self, quest_type: QuestType) -> Self {
+        self.description.quest_type = quest_type;
+        self
+    }
+
+    fn build(self) -> QuestDescription {
+        self.description
+    }
+}
+
+#[test]
+fn quest_description_builder_applies_defaults() {
+    let description = QuestDescriptionBuilder::new().build();
+    assert_eq!(description.name, "Unknown Quest");
+    assert_eq!(description.difficulty_level, 1);
+    assert_eq!(description.turns_to_complete, 5);
+    assert_eq!(description.exp_reward, 50);
+    assert_eq!(description.gold_reward, 100);
+    assert_eq!(description.item_reward, None);
+    assert_eq!(description.turns_to_expiry, 10);
+    assert_eq!(description.quest_type, QuestType::Bounty);
+    assert_eq!(description.min_party_size, 1);
+    assert_eq!(description.max_party_size, 3);
+}
+
+#[test]
+fn quest_description_builder_overrides_defaults() {
+    let item = Item {
+        class: HeroClass::Tank,
+        rarity: ItemRarity::Epic,
+    };
+    let description = QuestDescriptionBuilder::new()
+        .difficulty(7)
+        .turns(3)
+        .exp(400)
+        .gold(900)
+        .item(item)
+        .expiry(4)
+        .name("The Dragon's Lair".to_string())
+        .quest_type(QuestType::Dungeon)
+        .build();
+    assert_eq!(description.name, "The Dragon's Lair");
+    assert_eq!(description.difficulty_level, 7);
+    assert_eq!(description.turns_to_complete, 3);
+    assert_eq!(description.exp_reward, 400);
+    assert_eq!(description.gold_reward, 900);
+    assert_eq!(description.item_reward, Some(item));
+    assert_eq!(description.turns_to_expiry, 4);
+    assert_eq!(description.quest_type, QuestType::Dungeon);
+
+    // Only the fields that were set change
+    let description = QuestDescriptionBuilder::new().gold(250).build();
+    assert_eq!(description.gold_reward, 250);
+    assert_eq!(description.exp_reward, 50);
+    assert_eq!(description.difficulty_level, 1);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum QuestDifficulty {
+    Trivial,
+    Easy,
+    Medium,
+    Hard,
+    Legendary,
+}
+
+impl QuestDifficulty {
+    // Range of gold paid per difficulty level for quests in this tier. Harder tiers pay better per level.
+    fn gold_per_level_range(&self) -> RangeInclusive<u32> {
+        match self {
+            QuestDifficulty::Trivial => 80..=100,
+            QuestDifficulty::Easy => 90..=110,
+            QuestDifficulty::Medium => 100..=130,
+            QuestDifficulty::Hard => 120..=160,
+            QuestDifficulty::Legendary => 150..=200,
+        }
+    }
+}
+
+impl fmt::Display for QuestDifficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            QuestDifficulty::Trivial => "Trivial",
+            QuestDifficulty::Easy => "Easy",
+            QuestDifficulty::Medium => "Medium",
+            QuestDifficulty::Hard => "Hard",
+            QuestDifficulty::Legendary => "Legendary",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[test]
+fn difficulty_category_maps_levels_to_tiers() {
+    let description = |difficulty_level| QuestDescription {
+        name: "The Dragon's Lair".to_string(),
+        difficulty_level,
+        turns_to_complete: 5,
+        exp_reward: 50,
+        gold_reward: 100,
+        item_reward: None,
+        turns_to_expiry: 10,
+        quest_type: QuestType::Bounty,
+        min_party_size: 1,
+        max_party_size: 3,
+        faction: None,
+        alignment_affinity: None,
+    };
+    let expected = [
+        (1, QuestDifficulty::Trivial),
+        (2, QuestDifficulty::Trivial),
+        (3, QuestDifficulty::Easy),
+        (4, QuestDifficulty::Easy),
+        (5, QuestDifficulty::Medium),
+        (6, QuestDifficulty::Medium),
+        (7, QuestDifficulty::Hard),
+        (9, QuestDifficulty::Hard),
+        (10, QuestDifficulty::Legendary),
+        (15, QuestDifficulty::Legendary),
+    ];
+    for (difficulty_level, category) in expected {
+        assert_eq!(
+            description(difficulty_level).difficulty_category(),
+            category
+        );
+    }
+}
+
+#[test]
+fn quest_difficulty_displays_tier_name() {
+    assert_eq!(QuestDifficulty::Trivial.to_string(), "Trivial");
+    assert_eq!(QuestDifficulty::Easy.to_string(), "Easy");
+    assert_eq!(QuestDifficulty::Medium.to_string(), "Medium");
+    assert_eq!(QuestDifficulty::Hard.to_string(), "Hard");
+    assert_eq!(QuestDifficulty::Legendary.to_string(), "Legendary");
+}
+
+#[derive(Component)]
+struct QuestChain {
+    follow_up: QuestDescription, // Quest to post as a sequel once this one succeeds
+}
+
+#[derive(Component)]
+struct TurnTimer {
+    initial_value: u32, // Number of turns this timer will take (or has taken) to complete.
+    turns_remaining: u32, // Starts equal to initial_value and counts down to 0.
+    timer_mode: TimerMode,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum TimerMode {
+    Once,      // Stays at 0 once complete
+    Repeating, // Restarts as soon as it completes, for systems that run on a cadence
+}
+
+impl TurnTimer {
+    // A timer that completes once, after the given number of turns.
+    fn new(turns: u32) -> Self {
+        TurnTimer {
+            initial_value: turns,
+            turns_remaining: turns,
+            timer_mode: TimerMode::Once,
+        }
+    }
+
+    // A timer that completes every time the given number of turns passes.
+    fn repeating(turns: u32) -> Self {
+        TurnTimer {
+            timer_mode: TimerMode::Repeating,
+            ..TurnTimer::new(turns)
+        }
+    }
+
+    fn reset(&mut self) {
+        self.turns_remaining = self.initial_value;
+    }
+}
+
+#[derive(Event)]
+struct TurnTimerCompleteEvent(Entity); // An event indiciating a TurnTimer attached to an entity has completed.
+
+#[derive(Component)]
+struct TimerPaused; // A TurnTimer with this marker doesn't count down until it's removed.
+
+#[derive(Event)]
+struct PauseTimerEvent(Entity);
+
+#[derive(Event)]
+struct ResumeTimerEvent(Entity);
+
+#[derive(Component)]
+struct HeroName(String);
+
+// The image shown for a hero, once there's a UI to show it in.
+#[derive(Component, Clone, Debug)]
+struct HeroPortrait {
+    asset_path: String,
+}
+
+impl HeroPortrait {
+    // Every portrait a hero of this class might have.
+    fn paths_for(class: &HeroClass) -> &'static [&'static str] {
+        match class {
+            HeroClass::Warrior => &[
+                "heroes/warrior_01.png",
+                "heroes/warrior_02.png",
+                "heroes/warrior_03.png",
+            ],
+            HeroClass::Tank => &[
+                "heroes/tank_01.png",
+                "heroes/tank_02.png",
+                "heroes/tank_03.png",
+            ],
+            HeroClass::Support => &[
+                "heroes/support_01.png",
+                "heroes/support_02.png",
+                "heroes/support_03.png",
+            ],
+            HeroClass::Rogue => &["heroes/rogue_01.png", "heroes/rogue_02.png"],
+            HeroClass::Mage => &["heroes/mage_01.png", "heroes/mage_02.png"],
+        }
+    }
+
+    fn first_for(class: &HeroClass) -> HeroPortrait {
+        HeroPortrait {
+            asset_path: HeroPortrait::paths_for(class)[0].to_string(),
+        }
+    }
+
+    fn random_for(class: &HeroClass, rng: &mut impl Rng) -> HeroPortrait {
+        HeroPortrait {
+            asset_path: HeroPortrait::paths_for(class)
+                .choose(rng)
+                .unwrap()
+                .to_string(),
+        }
+    }
+}
+
+// Handles to every hero portrait, so they stay loaded for the whole game.
+#[derive(Resource)]
+#[allow(dead_code)] // Only held, never read
+struct HeroPortraitHandles(Vec<Handle<Image>>);
+
+const QUEST_NAMES: [&str; 16] = [
+    "The Dragon's Lair",
+    "The Sunken Crypt",
+    "Bandits on the King's Road",
+    "The Whispering Woods",
+    "A Merchant's Safe Passage",
+    "The Missing Heir",
+    "Rats in the Cellar",
+    "The Haunted Mill",
+    "The Ogre of Blackfen",
+    "The Stolen Relic",
+    "Wolves at the Gate",
+    "The Cult of the Pale Moon",
+    "The Drowned Lighthouse",
+    "A Pilgrim's Escort",
+    "The Goblin Warrens",
+    "The Burning Tower",
+];
+
+const HERO_FIRST_NAMES: [&str; 16] = [
+    "Aldric", "Brenna", "Cedric", "Dagny", "Edmund", "Freya", "Gareth", "Helga", "Ivor", "Jorunn",
+    "Kael", "Liesel", "Magnus", "Nessa", "Osric", "Rowena",
+];
+
+const HERO_EPITHETS: [&str; 16] = [
+    "the Steadfast",
+    "the Bold",
+    "the Wise",
+    "the Swift",
+    "the Unbroken",
+    "the Lucky",
+    "the Grim",
+    "the Kind",
+    "the Wanderer",
+    "the Quiet",
+    "the Fearless",
+    "the Cunning",
+    "the Patient",
+    "the Loud",
+    "the Scarred",
+    "the Young",
+];
+
+#[derive(Bundle)]
+struct HeroBundle {
+    marker: Hero,
+    name: HeroName,
+    age: Age,
+    morale: Morale,
+    quest_count: QuestCount,
+    level: LevelState,
+    class: HeroClass,
+    person: Person,
+    inventory: Inventory,
+    biography: Biography,
+    skills: Skills,
+    alignment: Alignment,
+    portrait: HeroPortrait,
+    status: HeroStatusIdle,
+}
+
+// A level 1 Warrior with no name, items or relationships.
+impl Default for HeroBundle {
+    fn default() -> Self {
+        HeroBundle {
+            marker: Hero,
+            name: HeroName(String::new()),
+            age: Age::default(),
+            morale: Morale::default(),
+            quest_count: QuestCount::default(),
+            level: LevelState::default(),
+            class: HeroClass::Warrior,
+            person: Person::default(),
+            inventory: Inventory::default(),
+            biography: Biography::default(),
+            skills: Skills::default(),
+            alignment: Alignment::default(),
+            portrait: HeroPortrait::first_for(&HeroClass::Warrior),
+            status: HeroStatusIdle,
+        }
+    }
+}
+
+impl HeroBundle {
+    // A level 1 Rogue, who keeps a close eye on how their party performs.
+    fn rogue_archetype(name: String) -> HeroBundle {
+        HeroBundle {
+            name: HeroName(name),
+            class: HeroClass::Rogue,
+            person: Person {
+                personality: Personality::Judgmental,
+                ..default()
+            },
+            portrait: HeroPortrait::first_for(&HeroClass::Rogue),
+            ..default()
+        }
+    }
+}
+
+#[test]
+fn rogue_archetype_is_a_judgmental_rogue() {
+    let bundle = HeroBundle::rogue_archetype("Vex the Quiet".to_string());
+    assert_eq!(bundle.name.0, "Vex the Quiet");
+    assert_eq!(bundle.class, HeroClass::Rogue);
+    assert!(matches!(bundle.person.personality, Personality::Judgmental));
+    assert_eq!(bundle.level.level, 1);
+    assert_eq!(format!("{}", HeroClass::Rogue), "Rogue");
+    assert_eq!(format!("{}", HeroClass::Support), "Support");
+}
+
+#[derive(Bundle)]
+struct QuestBundle {
+    marker: Quest,
+    description: QuestDescription,
+    progress: TurnTimer,
+    status: QuestStatusAvailable,
+}
+
+#[derive(Event)]
+struct StartQuestEvent {
+    quest: Entity,
+    heroes: Vec<Entity>,
+}
+
+#[derive(Event)]
+struct QuestCompleteEvent {
+    quest_description: QuestDescription,
+    heroes: Vec<Entity>,                 // Heroes that completed the quest
+    success_probability: Percent,        // Probability of success for the quest
+    is_successful: bool,                 // Whether the quest was successful or not
+    injured_heroes: Vec<Entity>, // Heroes injured on the quest, rolled before the event is sent
+    exp_reward: u32,             // Experience reward for the heroes
+    gold_reward: u32,            // Gold reward for the guild
+    follow_up: Option<QuestDescription>, // Next quest in the chain, copied from the quest's QuestChain before it was despawned
+}
+
+#[derive(Event)]
+struct HireHeroEvent(Entity);
+
+#[derive(Event)]
+struct DismissHeroEvent(Entity);
+
+#[derive(Event)]
+struct RestHeroEvent(Entity);
+
+#[derive(Event)]
+struct HeroDeathEvent(Entity);
+
+#[derive(Event)]
+struct LegacyHeroSpawnEvent {
+    parent_a: Entity,
+    parent_b: Entity,
+}
+
+#[derive(Event)]
+struct CreateRivalryEvent {
+    from: Entity,
+    toward: Entity,
+}
+
+#[derive(Event)]
+struct BondCompanionsEvent {
+    hero_a: Entity,
+    hero_b: Entity,
+}
+
+#[derive(Event)]
+struct BuildBuildingEvent(BuildingType);
+
+#[derive(Event)]
+struct UpgradeGuildHallEvent;
+
+#[derive(Event)]
+struct EquipItemEvent {
+    hero: Entity,
+    item_index: usize, // Index into the hero's Inventory items
+}
+
+#[derive(Event)]
+struct UnequipItemEvent(Entity);
+
+#[derive(Event)]
+struct BuyItemEvent {
+    hero: Entity,
+    listing_index: usize, // Index into the Market's listings
+}
+
+#[derive(Event)]
+struct BuySuppliesEvent {
+    amount: u32,
+}
+
+#[derive(Event)]
+struct BanditRaidEvent; // Bandits raid the guild's trade routes, closing all of them
+
+#[derive(Event)]
+struct SellItemEvent {
+    hero: Entity,
+    item_index: usize, // Index into the hero's Inventory items
+}
+
+#[derive(Event)]
+struct SaveEvent(PathBuf); // Save the game to a JSON file at this path
+
+#[derive(Event)]
+struct LoadEvent(PathBuf); // Replace the current game with the one saved at this path
+
+#[derive(Event)]
+struct HeroRetirementEvent {
+    hero: Entity,
+    name: String,
+    final_level: u32,
+}
+
+#[derive(Event)]
+struct LevelUpEvent {
+    hero: Entity,
+    new_level: u32,
+}
+
+#[derive(Event)]
+struct SkillLevelUpEvent {
+    hero: Entity,
+    skill_type: SkillType,
+    new_level: u32,
+}
+
+#[derive(Event)]
+struct SpecializationUnlockEvent {
+    hero: Entity,
+    options: Vec<Specialization>,
+}
+
+#[derive(Event)]
+struct ChooseSpecializationEvent {
+    hero: Entity,
+    specialization: Specialization,
+}
+
+#[test]
+fn percent_clamps_to_valid_range() {
+    assert_eq!(Percent(-20).clamped(), Percent(0));
+    assert_eq!(Percent(150).clamped(), Percent(100));
+    assert_eq!(Percent(75).clamped(), Percent(75));
+}
+
+#[test]
+fn percent_converts_to_and_from_f32() {
+    assert_eq!(Percent(75).as_f32(), 0.75);
+    assert_eq!(Percent::from_f32(0.333), Percent(33));
+    for value in [-20, 0, 1, 33, 50, 75, 99, 100, 150] {
+        let p = Percent(value);
+        let round_trip = Percent::from_f32(p.as_f32());
+        assert!(
+            (round_trip.0 - p.0).abs() <= 1,
+            "{} came back as {}",
+            p,
+            round_trip
+        );
+    }
+}
+
+#[test]
+fn percent_displays_with_percent_sign() {
+    assert_eq!(format!("{}", Percent(75)), "75%");
+    assert_eq!(format!("{}", Percent(-5)), "-5%");
+}
+
+#[test]
+fn percent_multiplies_divides_and_scales() {
+    assert_eq!(Percent(70) * 2, Percent(140));
+    assert_eq!(Percent(70) / 2, Percent(35));
+    assert_eq!(Percent(70).scale(1.5), Percent(105));
+    assert_eq!(Percent(70).scale(0.25), Percent(18)); // 17.5 rounds up
+}
+
+#[test]
+fn percent_sums_like_repeated_addition() {
+    assert_eq!(
+        Vec::<Percent>::new().into_iter().sum::<Percent>(),
+        Percent(0)
+    );
+    assert_eq!([Percent(42)].into_iter().sum::<Percent>(), Percent(42));
+    let percents = [Percent(70), Percent(-15), Percent(30)];
+    let expected = percents[0] + percents[1] + percents[2];
+    assert_eq!(percents.iter().sum::<Percent>(), expected);
+    assert_eq!(percents.into_iter().sum::<Percent>(), Percent(85));
+}
+
+#[test]
+fn percent_divide_by_zero_is_zero() {
+    assert_eq!(Percent(70) / 0, Percent(0));
+}
+
+#[test]
+fn percent_arithmetic_saturates_instead_of_overflowing() {
+    assert_eq!(Percent(i32::MAX) + Percent(10), Percent(i32::MAX));
+    assert_eq!(Percent(i32::MIN) - Percent(10), Percent(i32::MIN));
+    assert_eq!(Percent(i32::MAX / 2) * 3, Percent(i32::MAX));
+}
+
+// Opens the game's window, and runs the game until it's closed.
+pub fn run() {
+    App::new().add_plugins((DefaultPlugins, GamePlugin)).run();
+}
+
+// Every resource and event of the game, registered by both the GamePlugin and the tests.
+fn add_game_resources_and_events(app: &mut App) {
+    app.init_resource::<Turn>()
+        .init_resource::<Season>()
+        .init_resource::<CurrentWeather>()
+        .init_resource::<Notificiations>()
+        .init_resource::<Guild>()
+        .init_resource::<GuildDebt>()
+        .init_resource::<Supplies>()
+        .init_resource::<ConsumptionConfig>()
+        .init_resource::<GuildConfig>()
+        .init_resource::<GuildStats>()
+        .init_resource::<GuildRank>()
+        .init_resource::<GuildRankThresholds>()
+        .init_resource::<RivalGuilds>()
+        .init_resource::<RivalGrowth>()
+        .init_resource::<RandomEventConfig>()
+        .init_resource::<ChapterConfig>()
+        .init_resource::<TutorialMode>()
+        .init_resource::<Achievements>()
+        .init_resource::<ReplayRecorder>()
+        .init_resource::<ReplayPlayback>()
+        .init_resource::<QuestGenerationConfig>()
+        .init_resource::<QuestBoardConfig>()
+        .init_resource::<QuestAutoAssign>()
+        .init_resource::<FatigueConfig>()
+        .init_resource::<MoraleConfig>()
+        .init_resource::<FallenHeroes>()
+        .init_resource::<FamilyTree>()
+        .init_resource::<HeroConfig>()
+        .init_resource::<LevelConfig>()
+        .init_resource::<RetiredHeroes>()
+        .init_resource::<RetirementBonus>()
+        .init_resource::<GuildReputation>()
+        .init_resource::<GuildMorale>()
+        .init_resource::<GuildHallLevel>()
+        .init_resource::<BuildingSynergyConfig>()
+        .init_resource::<ActiveSynergies>()
+        .init_resource::<TavernConfig>()
+        .init_resource::<RecruitmentConfig>()
+        .init_resource::<RelationshipConfig>()
+        .init_resource::<GuildBuildingConfig>()
+        .init_resource::<MarketConfig>()
+        .init_resource::<Market>()
+        .init_resource::<MarketPriceModifier>()
+        .add_event::<NotificationEvent>()
+        .add_event::<TurnDeltaEvent>()
+        .add_event::<SeasonChangeEvent>()
+        .add_event::<WeatherChangeEvent>()
+        .add_event::<TurnTimerCompleteEvent>()
+        .add_event::<PauseTimerEvent>()
+        .add_event::<ResumeTimerEvent>()
+        .add_event::<StartQuestEvent>()
+        .add_event::<QuestCompleteEvent>()
+        .add_event::<LevelUpEvent>()
+        .add_event::<SkillLevelUpEvent>()
+        .add_event::<SpecializationUnlockEvent>()
+        .add_event::<ChooseSpecializationEvent>()
+        .add_event::<HireHeroEvent>()
+        .add_event::<DismissHeroEvent>()
+        .add_event::<HeroDeathEvent>()
+        .add_event::<BondCompanionsEvent>()
+        .add_event::<CreateRivalryEvent>()
+        .add_event::<LegacyHeroSpawnEvent>()
+        .add_event::<HeroRetirementEvent>()
+        .add_event::<BuildBuildingEvent>()
+        .add_event::<UpgradeGuildHallEvent>()
+        .add_event::<RestHeroEvent>()
+        .add_event::<EquipItemEvent>()
+        .add_event::<UnequipItemEvent>()
+        .add_event::<BuyItemEvent>()
+        .add_event::<BuySuppliesEvent>()
+        .add_event::<SellItemEvent>()
+        .add_event::<BanditRaidEvent>()
+        .add_event::<SaveEvent>()
+        .add_event::<LoadEvent>()
+        .add_event::<PauseEvent>()
+        .add_event::<ResumeEvent>()
+        .add_event::<GameOverEvent>();
+}
+
+// Every resource, event and system of the game, so it can also run headless for the stress test.
+struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        add_game_resources_and_events(app);
+        app.init_state::<GameState>()
+            .configure_sets(Update, GameplaySystems.run_if(in_state(GameState::Running)))
+            .add_systems(Startup, setup)
+            .add_systems(Startup, load_hero_portraits)
+            .add_systems(Update, toggle_pause)
+            .add_systems(Update, handle_game_over)
+            // Check for the end of the game once the turn's quests, payroll and deaths are settled
+            .add_systems(
+                Update,
+                check_victory_conditions
+                    .after(advance_guild_rank)
+                    .after(deduct_hero_salaries)
+                    .after(handle_hero_death)
+                    .after(handle_hero_retirement)
+                    .after(compute_retirement_bonus)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(Update, log_new_hero)
+            .add_systems(Update, handle_notifcation_events)
+            .add_systems(Update, advance_turn.in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                update_season.after(advance_turn).in_set(GameplaySystems),
+            )
+            .add_systems(Update, change_weather.in_set(GameplaySystems))
+            // Pausing or resuming a timer takes effect on the same turn
+            .add_systems(
+                Update,
+                (pause_timer, resume_timer)
+                    .before(advance_turn_timer)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(Update, advance_turn_timer.in_set(GameplaySystems))
+            .add_systems(Update, expire_quest.in_set(GameplaySystems))
+            // Auto-assigned quests must be started before the next update, so the same quest and heroes aren't picked twice
+            .add_systems(
+                Update,
+                auto_assign_quests
+                    .before(start_quest)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(Update, start_quest.in_set(GameplaySystems))
+            .add_systems(Update, complete_quest.in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                complete_quest_assign_exp
+                    .after(compute_building_synergies)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(
+                Update,
+                level_up_heroes
+                    .after(complete_quest_assign_exp)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(
+                Update,
+                choose_specialization
+                    .after(level_up_heroes)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(Update, complete_quest_updates_guild.in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                check_milestones
+                    .after(complete_quest_updates_guild)
+                    .after(advance_guild_rank)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(
+                Update,
+                check_achievements
+                    .run_if(on_event::<TurnDeltaEvent>)
+                    .after(advance_turn)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(
+                Update,
+                complete_quest_distribute_items.in_set(GameplaySystems),
+            )
+            .add_systems(Update, bond_companions.in_set(GameplaySystems))
+            .add_systems(Update, create_rivalry.in_set(GameplaySystems))
+            .add_systems(Update, check_romance.in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                (check_legacy_spawn, spawn_legacy_hero)
+                    .chain()
+                    .after(advance_turn)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(Update, dissolve_rivalries.in_set(GameplaySystems))
+            .add_systems(Update, equip_item.in_set(GameplaySystems))
+            .add_systems(Update, unequip_item.in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                complete_quest_apply_injuries.in_set(GameplaySystems),
+            )
+            .add_systems(Update, recover_from_injury.in_set(GameplaySystems))
+            .add_systems(Update, complete_quest_apply_fatigue.in_set(GameplaySystems))
+            .add_systems(Update, recover_from_fatigue.in_set(GameplaySystems))
+            .add_systems(Update, rest_hero.in_set(GameplaySystems))
+            .add_systems(Update, complete_rest.in_set(GameplaySystems))
+            .add_systems(Update, wear_off_rested.in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                complete_quest_apply_cooldown.in_set(GameplaySystems),
+            )
+            .add_systems(Update, recover_from_cooldown.in_set(GameplaySystems))
+            .add_systems(Update, age_heroes.in_set(GameplaySystems))
+            .add_systems(Update, complete_quest_update_morale.in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                complete_quest_apply_rival_resentment.in_set(GameplaySystems),
+            )
+            .add_systems(
+                Update,
+                complete_quest_apply_bonded_morale.in_set(GameplaySystems),
+            )
+            .add_systems(
+                Update,
+                complete_quest_update_hero_stats.in_set(GameplaySystems),
+            )
+            .add_systems(
+                Update,
+                recover_hero_morale
+                    .after(compute_building_synergies)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(Update, complete_quest_apply_deaths.in_set(GameplaySystems))
+            // Despawn dead heroes only once every other system has finished applying the quest's outcome to them
+            .add_systems(
+                Update,
+                handle_hero_death
+                    .after(complete_quest_apply_deaths)
+                    .after(complete_quest_apply_injuries)
+                    .after(complete_quest_apply_fatigue)
+                    .after(complete_quest_apply_cooldown)
+                    .in_set(GameplaySystems),
+            )
+            // A hero who died this update can't also retire
+            .add_systems(
+                Update,
+                handle_hero_retirement
+                    .after(level_up_heroes)
+                    .after(handle_hero_death)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(
+                Update,
+                compute_retirement_bonus
+                    .run_if(on_event::<TurnDeltaEvent>)
+                    .after(handle_hero_retirement)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(
+                Update,
+                complete_quest_send_notification.in_set(GameplaySystems),
+            )
+            .add_systems(Update, spawn_chain_follow_up.in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                update_relationships_after_quest.in_set(GameplaySystems),
+            )
+            // Heal only once the injuries are in place
+            .add_systems(
+                Update,
+                support_post_quest_healing
+                    .after(complete_quest_apply_injuries)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(Update, update_guild_stats.in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                emit_guild_report
+                    .after(advance_turn_timer)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(
+                Update,
+                announce_best_quests
+                    .after(advance_turn_timer)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(Update, advance_guild_rank.in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                (grow_rival_guilds, rival_guilds_claim_quests)
+                    .chain()
+                    .after(advance_guild_rank)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(Update, update_guild_reputation.in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                update_guild_morale
+                    .after(complete_quest_updates_guild)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(
+                Update,
+                generate_quests
+                    .after(advance_turn_timer)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(
+                Update,
+                generate_recruits
+                    .after(advance_turn_timer)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(
+                Update,
+                run_tutorial_step
+                    .after(start_quest)
+                    .after(complete_quest)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(Update, expire_recruit.in_set(GameplaySystems))
+            .add_systems(Update, hire_hero.in_set(GameplaySystems))
+            .add_systems(Update, dismiss_hero.in_set(GameplaySystems))
+            .add_systems(Update, deduct_hero_salaries.in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                (buy_supplies, consume_supplies)
+                    .chain()
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(Update, start_construction.in_set(GameplaySystems))
+            .add_systems(Update, start_guild_hall_upgrade.in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                refresh_market
+                    .after(advance_turn_timer)
+                    .after(fluctuate_market_prices)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(Update, fluctuate_market_prices.in_set(GameplaySystems))
+            .add_systems(Update, collect_trade_route_income.in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                (roll_bandit_raid, trigger_random_events, handle_bandit_raid)
+                    .chain()
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(Update, (buy_item, sell_item).in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                apply_gold_cap
+                    .after(complete_quest_updates_guild)
+                    .after(check_milestones)
+                    .after(check_achievements)
+                    .after(hire_hero)
+                    .after(dismiss_hero)
+                    .after(deduct_hero_salaries)
+                    .after(start_construction)
+                    .after(start_guild_hall_upgrade)
+                    .after(buy_item)
+                    .after(sell_item)
+                    .after(collect_trade_route_income)
+                    .after(trigger_random_events)
+                    .after(buy_supplies)
+                    .after(load_game)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(Update, complete_construction.in_set(GameplaySystems))
+            .add_systems(
+                Update,
+                compute_building_synergies
+                    .after(complete_construction)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(Update, complete_guild_upgrade.in_set(GameplaySystems))
+            .add_systems(Update, save_game)
+            .add_systems(Update, load_game)
+            // Replayed input is sent before the game's systems run, and recorded once they're done
+            .add_systems(PreUpdate, play_replay)
+            .add_systems(PostUpdate, record_frame)
+            .add_systems(
+                Update,
+                apply_relationship_decay
+                    .after(advance_turn_timer)
+                    .in_set(GameplaySystems),
+            )
+            .add_systems(
+                Update,
+                enforce_relationship_bounds
+                    .after(update_relationships_after_quest)
+                    .after(apply_relationship_decay)
+                    .in_set(GameplaySystems),
+            );
+    }
+}
+// Shared setup for tests, so each test only needs to add the systems and entities it cares about.
+#[cfg(test)]
+mod test_helpers {
+    use super::*;
+
+    // An app with every event and resource the game registers, and a seeded RNG.
+    pub fn make_test_app() -> App {
+        let mut app = App::new();
+        add_game_resources_and_events(&mut app);
+        app.insert_resource(RandomSource(Box::new(ChaCha8Rng::seed_from_u64(
+            GuildConfig::default().rng_seed,
+        ))));
+        app
+    }
+
+    // Stands in for the game's RNG, so a test can decide how each random roll turns out.
+    // A true outcome rolls as low as possible, so any chance above 0% happens, and a false
+    // outcome rolls as high as possible, so any chance below 100% doesn't.
+    pub struct MockRng {
+        outcomes: VecDeque<bool>,
+    }
+
+    impl MockRng {
+        fn next_outcome(&mut self) -> bool {
+            self.outcomes
+                .pop_front()
+                .expect("MockRng ran out of outcomes")
+        }
+    }
+
+    impl RngCore for MockRng {
+        fn next_u32(&mut self) -> u32 {
+            if self.next_outcome() { 0 } else { u32::MAX }
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            if self.next_outcome() { 0 } else { u64::MAX }
+        }
+
+        fn fill_bytes(&mut self, dst: &mut [u8]) {
+            dst.fill(if self.next_outcome() { 0 } else { u8::MAX });
+        }
+    }
+
+    // Replace the app's RNG with one that plays back the given outcomes, in order.
+    pub fn install_mock_rng(app: &mut App, outcomes: Vec<bool>) {
+        app.insert_resource(RandomSource(Box::new(MockRng {
+            outcomes: outcomes.into(),
+        })));
+    }
+
+    // An idle hero with no name, items or relationships.
+    pub fn spawn_hero(app: &mut App, class: HeroClass, level: u32) -> Entity {
+        app.world_mut()
+            .spawn((
+                Hero,
+                LevelState { level, ..default() },
+                class,
+                Person::default(),
+                Age::default(),
+                Morale::default(),
+                QuestCount::default(),
+                Inventory::default(),
+                HeroStatusIdle,
+            ))
+            .id()
+    }
+
+    // An available quest, counting down to its expiry.
+    pub fn spawn_quest(app: &mut App, difficulty: u32, turns: u32) -> Entity {
+        let description = QuestDescriptionBuilder::new()
+            .name("The Dragon's Lair".to_string())
+            .difficulty(difficulty)
+            .turns(turns)
+            .build();
+        app.world_mut()
+            .spawn(QuestBundle {
+                marker: Quest,
+                progress: TurnTimer::new(description.turns_to_expiry),
+                description,
+                status: QuestStatusAvailable,
+            })
+            .id()
+    }
+
+    // Send an idle hero out on a quest, the same way start_quest does.
+    pub fn link_hero_to_quest(app: &mut App, hero: Entity, quest: Entity) {
+        app.world_mut()
+            .entity_mut(hero)
+            .remove::<HeroStatusIdle>()
+            .insert((ChildOf(quest), HeroStatusOnQuest));
+    }
+
+    // Let the given number of turns pass, and run an update.
+    pub fn send_turns(app: &mut App, turns: u32) {
+        app.world_mut()
+            .resource_mut::<Events<TurnDeltaEvent>>()
+            .send(TurnDeltaEvent(turns));
+        app.update();
+    }
+
+    // The given heroes finishing The Dragon's Lair, which pays 50 exp, and 100 gold if they succeed.
+    pub fn quest_complete_event(heroes: Vec<Entity>, is_successful: bool) -> QuestCompleteEvent {
+        QuestCompleteEvent {
+            quest_description: QuestDescriptionBuilder::new()
+                .name("The Dragon's Lair".to_string())
+                .build(),
+            heroes,
+            success_probability: Percent(70),
+            is_successful,
+            injured_heroes: vec![],
+            exp_reward: 50,
+            gold_reward: if is_successful { 100 } else { 0 },
+            follow_up: None,
+        }
+    }
+
+    // Complete The Dragon's Lair with the given heroes, and run an update.
+    pub fn send_quest_complete(app: &mut App, heroes: Vec<Entity>, is_successful: bool) {
+        app.world_mut()
+            .resource_mut::<Events<QuestCompleteEvent>>()
+            .send(quest_complete_event(heroes, is_successful));
+        app.update();
+    }
+
+    // The most recent notification, from the last two updates.
+    pub fn last_notification(app: &App) -> String {
+        let notification_events = app.world().resource::<Events<NotificationEvent>>();
+        let mut reader = notification_events.get_cursor();
+        reader
+            .read(notification_events)
+            .last()
+            .expect("No notifications were sent")
+            .0
+            .clone()
+    }
+}
+
+// Start loading every hero portrait up front, so a UI won't hitch loading them later.
+// There's no AssetServer when running headless, and nothing to load.
+fn load_hero_portraits(mut commands: Commands, asset_server: Option<Res<AssetServer>>) {
+    let Some(asset_server) = asset_server else {
+        return;
+    };
+    let handles = ALL_CLASSES
+        .iter()
+        .flat_map(HeroPortrait::paths_for)
+        .map(|path| asset_server.load(*path))
+        .collect();
+    commands.insert_resource(HeroPortraitHandles(handles));
+}
+
+fn setup(
+    mut commands: Commands,
+    quest_generation_config: Res<QuestGenerationConfig>,
+    recruitment_config: Res<RecruitmentConfig>,
+    relationship_config: Res<RelationshipConfig>,
+    market_config: Res<MarketConfig>,
+    level_config: Res<LevelConfig>,
+    guild_config: Res<GuildConfig>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let mut seeded_rng = ChaCha8Rng::seed_from_u64(guild_config.rng_seed);
+    commands.insert_resource(Guild {
+        gold: guild_config.starting_gold,
+    });
+
+    commands.spawn((
+        QuestGenerator,
+        TurnTimer::repeating(quest_generation_config.cadence_turns),
+    ));
+    commands.spawn((
+        RecruitGenerator,
+        TurnTimer::repeating(recruitment_config.cadence_turns),
+    ));
+    commands.spawn((
+        RelationshipDecayTimer,
+        TurnTimer::repeating(relationship_config.decay_interval_turns),
+    ));
+    commands.spawn((
+        MarketRefreshTimer,
+        TurnTimer::repeating(market_config.refresh_interval_turns),
+    ));
+    let report_interval_turns = 20; // Turns between each guild report
+    commands.spawn((
+        GuildReportTimer,
+        TurnTimer::repeating(report_interval_turns),
+    ));
+
+    // Setup some initial heros and quests
+    commands.spawn(HeroBundle {
+        name: HeroName(generate_hero_name(&mut seeded_rng)),
+        level: LevelState {
+            exp_to_next: exp_required_for_level(&level_config, 1),
+            ..default()
+        },
+        ..default()
+    });
+    commands.spawn(HeroBundle {
+        name: HeroName(generate_hero_name(&mut seeded_rng)),
+        level: LevelState {
+            exp_to_next: exp_required_for_level(&level_config, 1),
+            ..default()
+        },
+        class: HeroClass::Tank,
+        person: Person {
+            personality: Personality::ResultOriented,
+            ..default()
+        },
+        portrait: HeroPortrait::first_for(&HeroClass::Tank),
+        ..default()
+    });
+
+    commands.spawn(QuestBundle {
+        marker: Quest,
+        description: QuestDescriptionBuilder::new()
+            .name(generate_quest_name(&mut seeded_rng))
+            .build(),
+        progress: TurnTimer::new(5),
+        status: QuestStatusAvailable,
+    });
+
+    commands.insert_resource(RandomSource(Box::new(seeded_rng)));
+    next_state.set(GameState::Running);
+}
+
+// Pick a random name for a new quest, such as "The Dragon's Lair".
+fn generate_quest_name(rng: &mut impl Rng) -> String {
+    QUEST_NAMES.choose(rng).unwrap().to_string()
+}
+
+// Pick a random name for a new hero, such as "Aldric the Steadfast".
+fn generate_hero_name(rng: &mut impl Rng) -> String {
+    let first_name = HERO_FIRST_NAMES.choose(rng).unwrap();
+    let epithet = HERO_EPITHETS.choose(rng).unwrap();
+    format!("{} {}", first_name, epithet)
+}
+
+#[test]
+fn generate_hero_name_combines_first_name_and_epithet() {
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    let name = generate_hero_name(&mut rng);
+    assert!(!name.is_empty());
+
+    let (first_name, epithet) = name.split_once(' ').unwrap();
+    assert!(!first_name.is_empty());
+    assert!(!epithet.is_empty());
+    assert!(HERO_FIRST_NAMES.contains(&first_name));
+    assert!(HERO_EPITHETS.contains(&epithet));
+}
+
+#[test]
+fn generate_hero_name_varies_between_calls() {
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    let names: Vec<String> = (0..10).map(|_| generate_hero_name(&mut rng)).collect();
+    assert!(names.iter().any(|name| *name != names[0]));
+}
+
+// How a hero is referred to in notifications. Falls back to the entity id for heroes without a HeroName.
+fn hero_display_name(names_query: &Query<&HeroName>, hero: Entity) -> String {
+    names_query
+        .get(hero)
+        .map_or_else(|_| format!("{:?}", hero), |name| name.0.clone())
+}
+
+fn log_new_hero(
+    query: Query<(&HeroName, &LevelState, &HeroClass, &Person), Added<Hero>>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for (name, level, class, person) in query.iter() {
+        ev_notify.write(NotificationEvent(format!(
+            "New hero created: {}, Level: {}, Class: {:?}, Personality: {:?}",
+            name.0, level.level, class, person.personality
+        )));
+    }
+}
+
+fn handle_notifcation_events(
+    mut ev_notifcations: EventReader<NotificationEvent>,
+    mut notifications: ResMut<Notificiations>,
+) {
+    for event in ev_notifcations.read() {
+        let n = Notification {
+            message: event.0.clone(),
+            is_unread: true,
+        };
+        info!("Notification: {}", n.message);
+        notifications.0.push(n);
+    }
+}
+
+// Pause a running game, or resume a paused one. Neither has any effect once the game is over.
+fn toggle_pause(
+    mut ev_pause: EventReader<PauseEvent>,
+    mut ev_resume: EventReader<ResumeEvent>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // Read both readers every time, so events sent in the wrong state are dropped rather than applied later
+    let paused = ev_pause.read().count() > 0;
+    let resumed = ev_resume.read().count() > 0;
+    match state.get() {
+        GameState::Running if paused => next_state.set(GameState::Paused),
+        GameState::Paused if resumed => next_state.set(GameState::Running),
+        _ => {}
+    }
+}
+
+// End the game, as a win or a loss depending on the reason.
+fn handle_game_over(
+    mut ev_game_over: EventReader<GameOverEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for GameOverEvent { reason } in ev_game_over.read() {
+        let (state, message) = match reason {
+            GameOverReason::Victory => (
+                GameState::Won,
+                "The guild has reached the highest rank, and its name will be remembered for generations. You win!",
+            ),
+            GameOverReason::Bankruptcy => (
+                GameState::Lost,
+                "With its heroes unpaid for too long, the guild has gone bankrupt and its doors are closed. Game over!",
+            ),
+            GameOverReason::AllHeroesDead => (
+                GameState::Lost,
+                "The guild hall stands empty, with no heroes left to answer the call. Game over!",
+            ),
+        };
+        next_state.set(state);
+        ev_notify.write(NotificationEvent(message.to_string()));
+    }
+}
+
+#[cfg(test)]
+fn make_game_state_test_app(state: GameState) -> App {
+    let mut app = make_test_app();
+    app.add_plugins(StatesPlugin);
+    app.insert_state(state);
+    app.configure_sets(Update, GameplaySystems.run_if(in_state(GameState::Running)));
+    app.add_systems(Update, (toggle_pause, handle_game_over));
+    app.add_systems(Update, advance_turn.in_set(GameplaySystems));
+    app
+}
+
+#[test]
+fn advance_turn_does_not_run_while_paused() {
+    let mut app = make_game_state_test_app(GameState::Paused);
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().resource::<Turn>().0, 0);
+
+    let mut app = make_game_state_test_app(GameState::Running);
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().resource::<Turn>().0, 1);
+}
+
+#[test]
+fn toggle_pause_moves_between_running_and_paused() {
+    let mut app = make_game_state_test_app(GameState::Running);
+
+    app.world_mut()
+        .resource_mut::<Events<PauseEvent>>()
+        .send(PauseEvent);
+    app.update(); // The transition is applied at the start of the next update
+    app.update();
+    assert_eq!(
+        *app.world().resource::<State<GameState>>().get(),
+        GameState::Paused
+    );
+
+    app.world_mut()
+        .resource_mut::<Events<ResumeEvent>>()
+        .send(ResumeEvent);
+    app.update();
+    app.update();
+    assert_eq!(
+        *app.world().resource::<State<GameState>>().get(),
+        GameState::Running
+    );
+}
+
+#[test]
+fn handle_game_over_moves_to_won_or_lost() {
+    let mut app = make_game_state_test_app(GameState::Running);
+    app.world_mut()
+        .resource_mut::<Events<GameOverEvent>>()
+        .send(GameOverEvent {
+            reason: GameOverReason::Victory,
+        });
+    app.update();
+    app.update();
+    assert_eq!(
+        *app.world().resource::<State<GameState>>().get(),
+        GameState::Won
+    );
+
+    let mut app = make_game_state_test_app(GameState::Running);
+    app.world_mut()
+        .resource_mut::<Events<GameOverEvent>>()
+        .send(GameOverEvent {
+            reason: GameOverReason::Bankruptcy,
+        });
+    app.update();
+    app.update();
+    assert_eq!(
+        *app.world().resource::<State<GameState>>().get(),
+        GameState::Lost
+    );
+
+    // A finished game can't be resumed
+    app.world_mut()
+        .resource_mut::<Events<ResumeEvent>>()
+        .send(ResumeEvent);
+    app.update();
+    app.update();
+    assert_eq!(
+        *app.world().resource::<State<GameState>>().get(),
+        GameState::Lost
+    );
+}
+
+// Each turn, end the game if the guild has won, gone bankrupt, or run out of heroes.
+fn check_victory_conditions(
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    rank: Res<GuildRank>,
+    debt: Res<GuildDebt>,
+    config: Res<GuildConfig>,
+    heroes_query: Query<(), With<Hero>>,
+    mut ev_game_over: EventWriter<GameOverEvent>,
+) {
+    if ev_turn_delta.read().count() == 0 {
+        return;
+    }
+    let reason = if rank.0 >= config.max_rank {
+        GameOverReason::Victory
+    } else if debt.turns_in_debt > config.bankruptcy_threshold {
+        GameOverReason::Bankruptcy
+    } else if heroes_query.is_empty() {
+        GameOverReason::AllHeroesDead // Recruits still waiting to be hired count as heroes
+    } else {
+        return;
+    };
+    ev_game_over.write(GameOverEvent { reason });
+}
+
+#[cfg(test)]
+fn read_victory_test_game_over(app: &mut App) -> Option<GameOverReason> {
+    send_turns(app, 1);
+    let events = app.world().resource::<Events<GameOverEvent>>();
+    let mut reader = events.get_cursor();
+    reader.read(events).last().map(|event| event.reason)
+}
+
+#[test]
+fn check_victory_conditions_detects_each_ending() {
+    let mut app = make_test_app();
+    app.add_systems(Update, check_victory_conditions);
+    spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.world_mut().resource_mut::<GuildDebt>().turns_in_debt = 5;
+    assert_eq!(read_victory_test_game_over(&mut app), None);
+
+    // Reaching the top rank wins
+    let mut app = make_test_app();
+    app.add_systems(Update, check_victory_conditions);
+    spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.insert_resource(GuildRank(10));
+    assert_eq!(
+        read_victory_test_game_over(&mut app),
+        Some(GameOverReason::Victory)
+    );
+
+    // Staying in debt for too long loses
+    let mut app = make_test_app();
+    app.add_systems(Update, check_victory_conditions);
+    spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.world_mut().resource_mut::<GuildDebt>().turns_in_debt = 6;
+    assert_eq!(
+        read_victory_test_game_over(&mut app),
+        Some(GameOverReason::Bankruptcy)
+    );
+
+    // So does losing every hero, though an unhired recruit keeps the guild going
+    let mut app = make_test_app();
+    app.add_systems(Update, check_victory_conditions);
+    let recruit = app.world_mut().spawn((Hero, HeroStatusAvailable)).id();
+    assert_eq!(read_victory_test_game_over(&mut app), None);
+    app.world_mut().despawn(recruit);
+    assert_eq!(
+        read_victory_test_game_over(&mut app),
+        Some(GameOverReason::AllHeroesDead)
+    );
+}
+
+// When TurnDelta event happens, advance Turn resource
+fn advance_turn(
+    mut turn: ResMut<Turn>,
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let total_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    turn.0 += total_delta;
+    ev_notify.write(NotificationEvent(format!(
+        "Turn advanced by {}. Current turn: {}",
+        total_delta, turn.0
+    )));
+}
+
+#[test]
+fn turn_delta_did_advance_turn() {
+    let mut app = make_test_app();
+
+    // Add the system under test
+    app.add_systems(Update, advance_turn);
+
+    // Send multiple TurnDeltaEvents
+    app.world_mut()
+        .resource_mut::<Events<TurnDeltaEvent>>()
+        .send(TurnDeltaEvent(3));
+    app.world_mut()
+        .resource_mut::<Events<TurnDeltaEvent>>()
+        .send(TurnDeltaEvent(2));
+
+    // Run the system
+    app.update();
+
+    // Check that the turn was incremented
+    assert_eq!(app.world().resource::<Turn>().0, 5);
+}
+
+#[test]
+fn turn_delta_did_send_notification() {
+    let mut app = make_test_app();
+
+    // Add the system under test
+    app.add_systems(Update, advance_turn);
+
+    // Send a TurnDeltaEvent
+    app.world_mut()
+        .resource_mut::<Events<TurnDeltaEvent>>()
+        .send(TurnDeltaEvent(1));
+
+    // Run the system
+    app.update();
+
+    // Check that a notification was sent
+    let notification_events = app.world().resource::<Events<NotificationEvent>>();
+    let mut notification_reader = notification_events.get_cursor();
+    let notification = notification_reader
+        .read(notification_events)
+        .next()
+        .unwrap();
+    assert_eq!(notification.0, "Turn advanced by 1. Current turn: 1");
+}
+
+// When the Turn crosses into a new season, update the Season and announce it.
+fn update_season(
+    turn: Res<Turn>,
+    mut season: ResMut<Season>,
+    mut ev_season_change: EventWriter<SeasonChangeEvent>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let current = SeasonKind::for_turn(turn.0);
+    if current != season.current {
+        season.current = current;
+        season.turn_of_last_change = turn.0 / 100 * 100;
+        ev_season_change.write(SeasonChangeEvent(current));
+        ev_notify.write(NotificationEvent(format!("{:?} has arrived", current)));
+    }
+}
+
+#[test]
+fn season_modifiers_match_each_season() {
+    assert_eq!(SeasonKind::Spring.difficulty_modifier(), 0);
+    assert_eq!(SeasonKind::Spring.success_modifier(), Percent(0));
+    assert_eq!(SeasonKind::Spring.gold_multiplier(), 1.0);
+
+    assert_eq!(SeasonKind::Summer.difficulty_modifier(), 0);
+    assert_eq!(SeasonKind::Summer.success_modifier(), Percent(0));
+    assert_eq!(SeasonKind::Summer.gold_multiplier(), 1.05);
+
+    assert_eq!(SeasonKind::Autumn.difficulty_modifier(), 0);
+    assert_eq!(SeasonKind::Autumn.success_modifier(), Percent(-10));
+    assert_eq!(SeasonKind::Autumn.gold_multiplier(), 1.0);
+
+    assert_eq!(SeasonKind::Winter.difficulty_modifier(), 1);
+    assert_eq!(SeasonKind::Winter.success_modifier(), Percent(0));
+    assert_eq!(SeasonKind::Winter.gold_multiplier(), 1.30);
+}
+
+#[test]
+fn update_season_changes_every_100_turns() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_season);
+
+    let mut cursor = app
+        .world()
+        .resource::<Events<SeasonChangeEvent>>()
+        .get_cursor();
+    let mut seasons_fired = Vec::new();
+    for turn in [1, 99, 100, 150, 199, 200, 299, 300, 301] {
+        app.world_mut().resource_mut::<Turn>().0 = turn;
+        app.update();
+        let events = app.world().resource::<Events<SeasonChangeEvent>>();
+        for SeasonChangeEvent(season) in cursor.read(events) {
+            seasons_fired.push((turn, *season));
+        }
+    }
+
+    // A season changes only as the turn crosses a boundary
+    assert_eq!(
+        seasons_fired,
+        vec![
+            (100, SeasonKind::Summer),
+            (200, SeasonKind::Autumn),
+            (300, SeasonKind::Winter),
+        ]
+    );
+    let season = app.world().resource::<Season>();
+    assert_eq!(season.current, SeasonKind::Winter);
+    assert_eq!(season.turn_of_last_change, 300);
+
+    // And the year starts over with Spring
+    app.world_mut().resource_mut::<Turn>().0 = 400;
+    app.update();
+    assert_eq!(app.world().resource::<Season>().current, SeasonKind::Spring);
+}
+
+// Each turn, the weather may turn fairer or fouler by one step.
+fn change_weather(
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    mut weather: ResMut<CurrentWeather>,
+    mut random_src: ResMut<RandomSource>,
+    mut ev_weather_change: EventWriter<WeatherChangeEvent>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let change_chance = Percent(10); // Chance of the weather changing each turn
+    let turn_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    let rng = &mut random_src.0;
+    for _ in 0..turn_delta {
+        if !change_chance.distribution().sample(rng) {
+            continue;
+        }
+        let next = match (weather.0.fouler(), weather.0.fairer()) {
+            (Some(fouler), Some(fairer)) => {
+                if rng.random_bool(0.5) {
+                    fouler
+                } else {
+                    fairer
+                }
+            }
+            (Some(fouler), None) => fouler,
+            (None, Some(fairer)) => fairer,
+            (None, None) => continue,
+        };
+        weather.0 = next;
+        ev_weather_change.write(WeatherChangeEvent(next));
+        ev_notify.write(NotificationEvent(format!(
+            "The weather has turned {:?}",
+            next
+        )));
+    }
+}
+
+#[test]
+fn change_weather_moves_to_an_adjacent_condition() {
+    let mut app = make_test_app();
+    app.add_systems(Update, change_weather);
+    // Clear weather can only turn Rainy, then Rainy turns fouler, then nothing changes
+    install_mock_rng(&mut app, vec![true, true, true, false]);
+
+    let mut cursor = app
+        .world()
+        .resource::<Events<WeatherChangeEvent>>()
+        .get_cursor();
+    send_turns(&mut app, 3);
+
+    let events = app.world().resource::<Events<WeatherChangeEvent>>();
+    let changes: Vec<WeatherCondition> = cursor
+        .read(events)
+        .map(|WeatherChangeEvent(weather)| *weather)
+        .collect();
+    assert_eq!(
+        changes,
+        vec![WeatherCondition::Rainy, WeatherCondition::Stormy]
+    );
+    assert_eq!(
+        app.world().resource::<CurrentWeather>().0,
+        WeatherCondition::Stormy
+    );
+}
+
+// On TurnDelta event, for TurnTimer components, advance progress. If progress complete, emit TurnTimerComplete event.
+fn advance_turn_timer(
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    mut query: Query<(Entity, &mut TurnTimer), Without<TimerPaused>>,
+    mut ev_turn_timer_complete: EventWriter<TurnTimerCompleteEvent>,
+) {
+    let turn_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    query
+        .iter_mut()
+        .filter(|(_, timer)| timer.turns_remaining > 0) // Only process timers that aren't yet complete
+        .for_each(|(entity, mut timer)| {
+            timer.turns_remaining = timer.turns_remaining.saturating_sub(turn_delta);
+            if timer.turns_remaining == 0 {
+                ev_turn_timer_complete.write(TurnTimerCompleteEvent(entity));
+                info!("Turn timer complete for entity: {:?}", entity);
+                if timer.timer_mode == TimerMode::Repeating {
+                    timer.reset();
+                }
+            }
+        });
+}
+
+#[test]
+fn turn_delta_did_advance_turn_timer() {
+    let mut app = make_test_app();
+
+    // Add a TurnTimer component to an entity
+    let entity = app.world_mut().spawn(TurnTimer::new(5)).id();
+
+    // Add the system under test
+    app.add_systems(Update, advance_turn_timer);
+
+    // Send a TurnDeltaEvent
+    app.world_mut()
+        .resource_mut::<Events<TurnDeltaEvent>>()
+        .send(TurnDeltaEvent(1));
+    app.world_mut()
+        .resource_mut::<Events<TurnDeltaEvent>>()
+        .send(TurnDeltaEvent(2));
+
+    // Run the system
+    app.update();
+
+    // Check that the timer was decremented
+    let timer = app.world().get::<TurnTimer>(entity).unwrap();
+    assert_eq!(timer.turns_remaining, 2);
+}
+
+#[test]
+fn advance_turn_timer_did_emit_completion_event() {
+    let mut app = make_test_app();
+
+    // Add a TurnTimer component to an entity
+    let entity = app.world_mut().spawn(TurnTimer::new(5)).id();
+
+    // Add the system under test
+    app.add_systems(Update, advance_turn_timer);
+
+    // Send a TurnDeltaEvent that will complete the timer
+    app.world_mut()
+        .resource_mut::<Events<TurnDeltaEvent>>()
+        .send(TurnDeltaEvent(5));
+
+    // Run the system
+    app.update();
+
+    // Check that the TurnTimerCompleteEvent was emitted
+    let turn_timer_events = app.world().resource::<Events<TurnTimerCompleteEvent>>();
+    let mut reader = turn_timer_events.get_cursor();
+    let event = reader.read(turn_timer_events).next().unwrap();
+    assert_eq!(event.0, entity);
+}
+
+#[test]
+fn advance_turn_timer_doesnt_go_past_zero() {
+    let mut app = make_test_app();
+
+    // Add a TurnTimer component to an entity
+    let entity = app.world_mut().spawn(TurnTimer::new(5)).id();
+
+    // Add the system under test
+    app.add_systems(Update, advance_turn_timer);
+
+    // Send a TurnDeltaEvent that will go past zero
+    app.world_mut()
+        .resource_mut::<Events<TurnDeltaEvent>>()
+        .send(TurnDeltaEvent(5));
+
+    // Run the system
+    app.update();
+
+    // Check that the timer was not decremented below zero
+    let timer = app.world().get::<TurnTimer>(entity).unwrap();
+    assert_eq!(timer.turns_remaining, 0);
+}
+
+#[test]
+fn advance_turn_timer_restarts_repeating_timer() {
+    let mut app = make_test_app();
+    app.add_systems(Update, advance_turn_timer);
+    let entity = app.world_mut().spawn(TurnTimer::repeating(5)).id();
+
+    let mut reader = app
+        .world()
+        .resource::<Events<TurnTimerCompleteEvent>>()
+        .get_cursor();
+    let mut completed_turns = Vec::new();
+    for turn in 1..=15 {
+        send_turns(&mut app, 1);
+        let turn_timer_events = app.world().resource::<Events<TurnTimerCompleteEvent>>();
+        if reader
+            .read(turn_timer_events)
+            .any(|event| event.0 == entity)
+        {
+            completed_turns.push(turn);
+        }
+    }
+
+    assert_eq!(completed_turns, vec![5, 10, 15]);
+    let timer = app.world().get::<TurnTimer>(entity).unwrap();
+    assert_eq!(timer.turns_remaining, 5);
+}
+
+fn pause_timer(
+    mut commands: Commands,
+    mut ev_pause_timer: EventReader<PauseTimerEvent>,
+    timers_query: Query<(), With<TurnTimer>>,
+) {
+    for PauseTimerEvent(entity) in ev_pause_timer.read() {
+        if timers_query.contains(*entity) {
+            commands.entity(*entity).insert(TimerPaused);
+        }
+    }
+}
+
+fn resume_timer(
+    mut commands: Commands,
+    mut ev_resume_timer: EventReader<ResumeTimerEvent>,
+    paused_query: Query<(), With<TimerPaused>>,
+) {
+    for ResumeTimerEvent(entity) in ev_resume_timer.read() {
+        if paused_query.contains(*entity) {
+            commands.entity(*entity).remove::<TimerPaused>();
+        }
+    }
+}
+
+#[cfg(test)]
+fn advance_pause_test_turns(app: &mut App, turns: u32) {
+    for _ in 0..turns {
+        send_turns(app, 1);
+    }
+}
+
+#[test]
+fn pause_timer_stops_timer_counting_down() {
+    let mut app = make_test_app();
+    app.add_systems(
+        Update,
+        (pause_timer, resume_timer, advance_turn_timer).chain(),
+    );
+    let paused = app.world_mut().spawn(TurnTimer::new(20)).id();
+    let running = app.world_mut().spawn(TurnTimer::new(20)).id();
+
+    // Pausing twice is the same as pausing once
+    for _ in 0..2 {
+        app.world_mut()
+            .resource_mut::<Events<PauseTimerEvent>>()
+            .send(PauseTimerEvent(paused));
+    }
+    advance_pause_test_turns(&mut app, 10);
+
+    assert!(app.world().get::<TimerPaused>(paused).is_some());
+    assert_eq!(
+        app.world()
+            .get::<TurnTimer>(paused)
+            .unwrap()
+            .turns_remaining,
+        20
+    );
+    assert_eq!(
+        app.world()
+            .get::<TurnTimer>(running)
+            .unwrap()
+            .turns_remaining,
+        10
+    );
+}
+
+#[test]
+fn resume_timer_continues_from_where_it_paused() {
+    let mut app = make_test_app();
+    app.add_systems(
+        Update,
+        (pause_timer, resume_timer, advance_turn_timer).chain(),
+    );
+    let timer = app.world_mut().spawn(TurnTimer::new(5)).id();
+
+    advance_pause_test_turns(&mut app, 2);
+    app.world_mut()
+        .resource_mut::<Events<PauseTimerEvent>>()
+        .send(PauseTimerEvent(timer));
+    advance_pause_test_turns(&mut app, 4);
+    assert_eq!(
+        app.world().get::<TurnTimer>(timer).unwrap().turns_remaining,
+        3
+    );
+
+    // The turns spent paused aren't caught up on
+    app.world_mut()
+        .resource_mut::<Events<ResumeTimerEvent>>()
+        .send(ResumeTimerEvent(timer));
+    advance_pause_test_turns(&mut app, 1);
+    assert!(app.world().get::<TimerPaused>(timer).is_none());
+    assert_eq!(
+        app.world().get::<TurnTimer>(timer).unwrap().turns_remaining,
+        2
+    );
+}
+
+// When turn timer completes for Available quest, despawn the quest and notify.
+fn expire_quest(
+    mut commands: Commands,
+    mut ev_turn_timer_complete: EventReader<TurnTimerCompleteEvent>,
+    query: Query<&QuestDescription, (With<Quest>, With<QuestStatusAvailable>)>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for TurnTimerCompleteEvent(entity) in ev_turn_timer_complete.read() {
+        if let Ok(description) = query.get(*entity) {
+            commands.entity(*entity).despawn();
+            ev_notify.write(NotificationEvent(format!(
+                "An available {:?} quest expired: {}",
+                description.quest_type, description.name
+            )));
+        }
+    }
+}
+
+#[test]
+fn expire_quest_despawns_available_quest() {
+    let mut app = make_test_app();
+
+    // Add an available quest with a TurnTimer
+    let entity = app
+        .world_mut()
+        .spawn((
+            Quest,
+            QuestStatusAvailable,
+            QuestDescriptionBuilder::new()
+                .name("The Dragon's Lair".to_string())
+                .expiry(5)
+                .quest_type(QuestType::Escort)
+                .build(),
+            TurnTimer {
+                initial_value: 5,
+                turns_remaining: 0,
+                timer_mode: TimerMode::Once,
+            },
+        ))
+        .id();
+
+    // Add the system under test
+    app.add_systems(Update, expire_quest);
+
+    // Send a TurnTimerCompleteEvent for the quest
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(entity));
+
+    // Run the system
+    app.update();
+
+    // Check that the quest was despawned
+    assert!(!app.world().get::<Quest>(entity).is_some());
+
+    // Check that a notification was sent
+    assert_eq!(
+        last_notification(&app),
+        "An available Escort quest expired: The Dragon's Lair"
+    );
+}
+
+// When timer completes for an InProgress quest with a percentage of success, determine result, despawn the quest, and emit a End of Quest event.
+// TODO
+
+// When a quest is started, set quest and hero statuses, and begin the quest timer. A request that isn't valid is
+// rejected as a whole, with a notification explaining why.
+// Injured or fatigued heroes are left out, and the rest of the party goes without them if there are still enough.
+fn start_quest(
+    mut commands: Commands,
+    mut ev_start_quest: EventReader<StartQuestEvent>,
+    quests_query: Query<(&QuestDescription, Has<QuestStatusAvailable>), With<Quest>>,
+    idle_heroes_query: Query<(), (With<Hero>, With<HeroStatusIdle>)>,
+    available_heroes_query: Query<
+        Entity,
+        (
+            With<Hero>,
+            Without<Injured>,
+            Without<Fatigued>,
+            Without<HeroCooldown>,
+            Without<HeroStatusAvailable>,
+        ),
+    >,
+    personalities_query: Query<(&LevelState, &Person), With<Hero>>,
+    companions_query: Query<&Companion>,
+    on_quest_query: Query<(), (With<Hero>, With<HeroStatusOnQuest>)>,
+    names_query: Query<&HeroName>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for StartQuestEvent { quest, heroes } in ev_start_quest.read() {
+        let Ok((description, is_available)) = quests_query.get(*quest) else {
+            ev_notify.write(NotificationEvent(format!(
+                "Quest {:?} does not exist",
+                quest
+            )));
+            continue;
+        };
+        if !is_available {
+            ev_notify.write(NotificationEvent(format!(
+                "Quest {} is not available to start",
+                description.name
+            )));
+            continue;
+        }
+        if heroes.is_empty() {
+            ev_notify.write(NotificationEvent(format!(
+                "No heroes were assigned to quest {}",
+                description.name
+            )));
+            continue;
+        }
+        if let Some(hero) = heroes
+            .iter()
+            .find(|hero| !idle_heroes_query.contains(**hero))
+        {
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} is not idle, so quest {} was not started",
+                hero_display_name(&names_query, *hero),
+                description.name
+            )));
+            continue;
+        }
+        // Companions never go on different quests at the same time
+        let separated_hero = heroes.iter().find_map(|hero| {
+            companions_query
+                .get(*hero)
+                .ok()
+                .filter(|companion| on_quest_query.contains(companion.0))
+                .map(|companion| (*hero, companion.0))
+        });
+        if let Some((hero, companion)) = separated_hero {
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} won't go on a different quest from their companion {}",
+                hero_display_name(&names_query, hero),
+                hero_display_name(&names_query, companion)
+            )));
+            continue;
+        }
+        let scared_hero = heroes.iter().find(|hero| {
+            personalities_query
+                .get(**hero)
+                .is_ok_and(|(level, person)| {
+                    hero_refuses_quest(
+                        &person.personality,
+                        level.level,
+                        description.difficulty_level,
+                    )
+                })
+        });
+        if let Some(hero) = scared_hero {
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} is too scared of a level {} quest and refused to join",
+                hero_display_name(&names_query, *hero),
+                description.difficulty_level
+            )));
+            continue;
+        }
+
+        let (party, left_out): (Vec<Entity>, Vec<Entity>) = heroes
+            .iter()
+            .partition(|hero| available_heroes_query.contains(**hero));
+        for hero in left_out {
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} is not available and was left out of the quest",
+                hero_display_name(&names_query, hero)
+            )));
+        }
+        let party_size = party.len() as u32;
+        if party_size < description.min_party_size || party_size > description.max_party_size {
+            ev_notify.write(NotificationEvent(format!(
+                "Quest {} needs {} to {} heroes, but {} could go",
+                description.name,
+                description.min_party_size,
+                description.max_party_size,
+                party_size
+            )));
+            continue;
+        }
+
+        // Set the quest status to InProgress
+        commands
+            .entity(*quest)
+            .remove::<QuestStatusAvailable>()
+            .insert(QuestStatusInProgress)
+            // TODO: Will this work if quest already has a TurnTimer?
+            .insert(TurnTimer::new(description.turns_to_complete));
+
+        // Assign heros to quest, using ChildOf/Children relationships
+        for hero in party {
+            commands
+                .entity(hero)
+                .remove::<HeroStatusIdle>()
+                .insert((ChildOf(*quest), HeroStatusOnQuest));
+        }
+    }
+}
+
+// Whether a hero's personality makes them refuse to go on a quest of this difficulty.
+fn hero_refuses_quest(personality: &Personality, hero_level: u32, difficulty_level: u32) -> bool {
+    match personality {
+        Personality::Cowardly => difficulty_level > hero_level + 1,
+        _ => false,
+    }
+}
+
+#[test]
+fn start_quest_cowardly_hero_refuses_hard_quest() {
+    let mut app = make_test_app();
+    app.add_systems(Update, start_quest);
+
+    let quest_entity = spawn_quest(&mut app, 3, 5);
+    let cowardly_level_1 = app
+        .world_mut()
+        .spawn((
+            Hero,
+            HeroStatusIdle,
+            LevelState::default(),
+            Person {
+                personality: Personality::Cowardly,
+                ..default()
+            },
+        ))
+        .id();
+    let cowardly_level_2 = app
+        .world_mut()
+        .spawn((
+            Hero,
+            HeroStatusIdle,
+            LevelState {
+                level: 2,
+                ..default()
+            },
+            Person {
+                personality: Personality::Cowardly,
+                ..default()
+            },
+        ))
+        .id();
+
+    app.world_mut()
+        .resource_mut::<Events<StartQuestEvent>>()
+        .send(StartQuestEvent {
+            quest: quest_entity,
+            heroes: vec![cowardly_level_1, cowardly_level_2],
+        });
+    app.update();
+
+    // Two levels above is too much, and the party won't go without them
+    assert!(app.world().get::<ChildOf>(cowardly_level_1).is_none());
+    assert!(app.world().get::<ChildOf>(cowardly_level_2).is_none());
+    assert!(
+        app.world()
+            .get::<QuestStatusAvailable>(quest_entity)
+            .is_some()
+    );
+
+    assert_eq!(
+        last_notification(&app),
+        format!(
+            "Hero {:?} is too scared of a level 3 quest and refused to join",
+            cowardly_level_1
+        )
+    );
+}
+
+#[test]
+fn start_quest_cowardly_hero_joins_quest_one_level_above() {
+    let mut app = make_test_app();
+    app.add_systems(Update, start_quest);
+    let quest = spawn_quest(&mut app, 3, 5);
+    let cowardly_level_2 = spawn_hero(&mut app, HeroClass::Warrior, 2);
+    app.world_mut().entity_mut(cowardly_level_2).insert(Person {
+        personality: Personality::Cowardly,
+        ..default()
+    });
+
+    app.world_mut()
+        .resource_mut::<Events<StartQuestEvent>>()
+        .send(StartQuestEvent {
+            quest,
+            heroes: vec![cowardly_level_2],
+        });
+    app.update();
+
+    assert_eq!(
+        app.world()
+            .get::<ChildOf>(cowardly_level_2)
+            .unwrap()
+            .parent(),
+        quest
+    );
+    assert!(app.world().get::<QuestStatusInProgress>(quest).is_some());
+}
+
+#[test]
+fn start_quest_skips_fatigued_heroes() {
+    let mut app = make_test_app();
+    app.add_systems(Update, start_quest);
+
+    let quest_entity = spawn_quest(&mut app, 1, 5);
+    let fatigued_hero = app
+        .world_mut()
+        .spawn((
+            Hero,
+            HeroStatusIdle,
+            Fatigued(TurnTimer {
+                initial_value: 3,
+                turns_remaining: 1,
+                timer_mode: TimerMode::Once,
+            }),
+        ))
+        .id();
+
+    app.world_mut()
+        .resource_mut::<Events<StartQuestEvent>>()
+        .send(StartQuestEvent {
+            quest: quest_entity,
+            heroes: vec![fatigued_hero],
+        });
+    app.update();
+
+    assert!(app.world().get::<ChildOf>(fatigued_hero).is_none());
+}
+
+#[test]
+fn start_quest_skips_injured_heroes() {
+    let mut app = make_test_app();
+    app.add_systems(Update, start_quest);
+
+    let quest_entity = spawn_quest(&mut app, 1, 5);
+    let healthy_hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    let injured_hero = app.world_mut().spawn((Hero, HeroStatusIdle, Injured)).id();
+
+    app.world_mut()
+        .resource_mut::<Events<StartQuestEvent>>()
+        .send(StartQuestEvent {
+            quest: quest_entity,
+            heroes: vec![healthy_hero, injured_hero],
+        });
+    app.update();
+
+    // Check that only the healthy hero joined the quest
+    assert_eq!(
+        app.world().get::<ChildOf>(healthy_hero).unwrap().parent(),
+        quest_entity
+    );
+    assert!(app.world().get::<ChildOf>(injured_hero).is_none());
+    assert!(
+        app.world()
+            .get::<QuestStatusInProgress>(quest_entity)
+            .is_some()
+    );
+
+    // Check that a notification was sent for the injured hero
+    assert_eq!(
+        last_notification(&app),
+        format!(
+            "Hero {:?} is not available and was left out of the quest",
+            injured_hero
+        )
+    );
+}
+
+#[test]
+fn start_quest_swaps_hero_status_to_on_quest() {
+    let mut app = make_test_app();
+    app.add_systems(Update, start_quest);
+
+    let quest_entity = spawn_quest(&mut app, 1, 5);
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+
+    app.world_mut()
+        .resource_mut::<Events<StartQuestEvent>>()
+        .send(StartQuestEvent {
+            quest: quest_entity,
+            heroes: vec![hero],
+        });
+    app.update();
+
+    assert!(app.world().get::<HeroStatusIdle>(hero).is_none());
+    assert!(app.world().get::<HeroStatusOnQuest>(hero).is_some());
+    assert_eq!(
+        app.world().get::<ChildOf>(hero).unwrap().parent(),
+        quest_entity
+    );
+}
+
+#[test]
+fn start_quest_rejects_hero_already_on_quest() {
+    let mut app = make_test_app();
+    app.add_systems(Update, start_quest);
+
+    let first_quest = spawn_quest(&mut app, 1, 5);
+    let second_quest = spawn_quest(&mut app, 1, 5);
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+
+    app.world_mut()
+        .resource_mut::<Events<StartQuestEvent>>()
+        .send(StartQuestEvent {
+            quest: first_quest,
+            heroes: vec![hero],
+        });
+    app.update();
+    app.world_mut()
+        .resource_mut::<Events<StartQuestEvent>>()
+        .send(StartQuestEvent {
+            quest: second_quest,
+            heroes: vec![hero],
+        });
+    app.update();
+
+    // Check that the hero stayed on the first quest, and the player was told why
+    assert_eq!(
+        app.world().get::<ChildOf>(hero).unwrap().parent(),
+        first_quest
+    );
+    assert!(
+        app.world()
+            .get::<QuestStatusAvailable>(second_quest)
+            .is_some()
+    );
+    assert_eq!(
+        read_start_quest_test_notifications(&app),
+        vec![format!(
+            "Hero {:?} is not idle, so quest The Dragon's Lair was not started",
+            hero
+        )]
+    );
+}
+
+#[test]
+fn start_quest_rejects_hero_whose_companion_is_on_another_quest() {
+    let mut app = make_test_app();
+    app.add_systems(Update, start_quest);
+    let first_quest = spawn_quest(&mut app, 1, 5);
+    let second_quest = spawn_quest(&mut app, 1, 5);
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    let companion = spawn_hero(&mut app, HeroClass::Tank, 1);
+    app.world_mut()
+        .entity_mut(hero)
+        .insert(Companion(companion));
+    app.world_mut()
+        .entity_mut(companion)
+        .insert(Companion(hero));
+    link_hero_to_quest(&mut app, companion, first_quest);
+
+    app.world_mut()
+        .resource_mut::<Events<StartQuestEvent>>()
+        .send(StartQuestEvent {
+            quest: second_quest,
+            heroes: vec![hero],
+        });
+    app.update();
+
+    assert!(app.world().get::<ChildOf>(hero).is_none());
+    assert!(
+        app.world()
+            .get::<QuestStatusAvailable>(second_quest)
+            .is_some()
+    );
+    assert_eq!(
+        read_start_quest_test_notifications(&app),
+        vec![format!(
+            "Hero {:?} won't go on a different quest from their companion {:?}",
+            hero, companion
+        )]
+    );
+}
+
+#[cfg(test)]
+fn read_start_quest_test_notifications(app: &App) -> Vec<String> {
+    let notification_events = app.world().resource::<Events<NotificationEvent>>();
+    let mut reader = notification_events.get_cursor();
+    reader
+        .read(notification_events)
+        .map(|notification| notification.0.clone())
+        .collect()
+}
+
+#[cfg(test)]
+fn make_start_quest_test_app() -> App {
+    let mut app = make_test_app();
+    app.add_systems(Update, start_quest);
+    app
+}
+
+#[test]
+fn start_quest_rejects_quest_that_isnt_available() {
+    let mut app = make_start_quest_test_app();
+    let quest = spawn_quest(&mut app, 1, 5);
+    app.world_mut()
+        .entity_mut(quest)
+        .remove::<QuestStatusAvailable>()
+        .insert(QuestStatusInProgress);
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+
+    app.world_mut()
+        .resource_mut::<Events<StartQuestEvent>>()
+        .send(StartQuestEvent {
+            quest,
+            heroes: vec![hero],
+        });
+    app.update();
+
+    assert!(app.world().get::<ChildOf>(hero).is_none());
+    assert_eq!(
+        read_start_quest_test_notifications(&app),
+        vec!["Quest The Dragon's Lair is not available to start"]
+    );
+}
+
+#[test]
+fn start_quest_rejects_empty_party() {
+    let mut app = make_start_quest_test_app();
+    let quest = spawn_quest(&mut app, 1, 5);
+
+    app.world_mut()
+        .resource_mut::<Events<StartQuestEvent>>()
+        .send(StartQuestEvent {
+            quest,
+            heroes: vec![],
+        });
+    app.update();
+
+    assert!(app.world().get::<QuestStatusAvailable>(quest).is_some());
+    // The quest is still counting down to its expiry
+    assert_eq!(
+        app.world().get::<TurnTimer>(quest).unwrap().turns_remaining,
+        10
+    );
+    assert_eq!(
+        read_start_quest_test_notifications(&app),
+        vec!["No heroes were assigned to quest The Dragon's Lair"]
+    );
+}
+
+#[test]
+fn start_quest_rejects_party_outside_size_limits() {
+    let mut app = make_start_quest_test_app();
+    let quest = spawn_quest(&mut app, 1, 5);
+    {
+        let mut description = app.world_mut().get_mut::<QuestDescription>(quest).unwrap();
+        description.min_party_size = 2;
+        description.max_party_size = 2;
+    }
+    let heroes: Vec<Entity> = (0..3)
+        .map(|_| spawn_hero(&mut app, HeroClass::Warrior, 1))
+        .collect();
+
+    // Too many heroes
+    app.world_mut()
+        .resource_mut::<Events<StartQuestEvent>>()
+        .send(StartQuestEvent {
+            quest,
+            heroes: heroes.clone(),
+        });
+    app.update();
+    assert!(app.world().get::<QuestStatusAvailable>(quest).is_some());
+    assert_eq!(
+        read_start_quest_test_notifications(&app),
+        vec!["Quest The Dragon's Lair needs 2 to 2 heroes, but 3 could go"]
+    );
+
+    // Too few heroes, once the injured one is left out
+    app.world_mut().entity_mut(heroes[1]).insert(Injured);
+    app.update(); // Clear the previous notification
+    app.world_mut()
+        .resource_mut::<Events<StartQuestEvent>>()
+        .send(StartQuestEvent {
+            quest,
+            heroes: vec![heroes[0], heroes[1]],
+        });
+    app.update();
+    assert!(app.world().get::<QuestStatusAvailable>(quest).is_some());
+    assert!(
+        heroes
+            .iter()
+            .all(|hero| app.world().get::<ChildOf>(*hero).is_none())
+    );
+    assert_eq!(
+        read_start_quest_test_notifications(&app),
+        vec![
+            format!(
+                "Hero {:?} is not available and was left out of the quest",
+                heroes[1]
+            ),
+            "Quest The Dragon's Lair needs 2 to 2 heroes, but 1 could go".to_string(),
+        ]
+    );
+}
+
+// The parts of a hero that decide how strong they are on a given kind of quest.
+struct HeroInfo<'a> {
+    level: &'a LevelState,
+    class: &'a HeroClass,
+    morale: &'a Morale,
+    equipped: Option<&'a Item>,
+}
+
+// A single comparable measure of how strong a hero is on a quest, in the same units as success probability:
+// each effective level is worth the guild's effectiveness_per_level, plus the hero's class and morale modifiers.
+fn hero_effective_power(
+    level: &LevelState,
+    class: &HeroClass,
+    morale: &Morale,
+    equipped: Option<&Item>,
+    quest_type: QuestType,
+    effectiveness_per_level: i32,
+) -> f32 {
+    let effective_level = level.level as f32 + equipment_level_bonus(class, equipped);
+    effective_level * effectiveness_per_level as f32
+        + (quest_type_class_modifier(quest_type, class) + morale_modifier(morale)) as f32
+}
+
+#[test]
+fn hero_effective_power_combines_modifiers() {
+    let level_3 = LevelState {
+        level: 3,
+        ..default()
+    };
+    let legendary_sword = Item {
+        class: HeroClass::Warrior,
+        rarity: ItemRarity::Legendary,
+    };
+    let power = |class: &HeroClass, morale: i32, equipped: Option<&Item>, quest_type: QuestType| {
+        hero_effective_power(&level_3, class, &Morale(morale), equipped, quest_type, 20)
+    };
+
+    assert_eq!(
+        power(&HeroClass::Warrior, 50, None, QuestType::Bounty),
+        60.0
+    );
+    // The quest suits Warriors
+    assert_eq!(
+        power(&HeroClass::Warrior, 50, None, QuestType::Dungeon),
+        70.0
+    );
+    // Morale counts for or against the hero
+    assert_eq!(
+        power(&HeroClass::Warrior, 100, None, QuestType::Bounty),
+        65.0
+    );
+    assert_eq!(power(&HeroClass::Warrior, 0, None, QuestType::Bounty), 55.0);
+    // A Legendary item is worth one and a half levels, but only to its own class
+    assert_eq!(
+        power(
+            &HeroClass::Warrior,
+            50,
+            Some(&legendary_sword),
+            QuestType::Bounty
+        ),
+        90.0
+    );
+    assert_eq!(
+        power(
+            &HeroClass::Tank,
+            50,
+            Some(&legendary_sword),
+            QuestType::Bounty
+        ),
+        60.0
+    );
+}
+
+// The heroes in order of their effective power on the quest, strongest first. Equally strong heroes are kept in
+// entity order, so the ranking doesn't depend on query order.
+fn rank_heroes_for_quest(
+    heroes: &[(Entity, HeroInfo)],
+    quest: &QuestDescription,
+    effectiveness_per_level: i32,
+) -> Vec<Entity> {
+    let mut powers: Vec<(Entity, f32)> = heroes
+        .iter()
+        .map(|(hero, info)| {
+            (
+                *hero,
+                hero_effective_power(
+                    info.level,
+                    info.class,
+                    info.morale,
+                    info.equipped,
+                    quest.quest_type,
+                    effectiveness_per_level,
+                ),
+            )
+        })
+        .collect();
+    powers.sort_by(|(hero_a, power_a), (hero_b, power_b)| {
+        power_b.total_cmp(power_a).then(hero_a.cmp(hero_b))
+    });
+    powers.into_iter().map(|(hero, _)| hero).collect()
+}
+
+#[test]
+fn rank_heroes_for_quest_orders_by_power() {
+    let mut world = World::new();
+    let heroes: Vec<Entity> = (0..5).map(|_| world.spawn_empty().id()).collect();
+    let level_1 = LevelState::default();
+    let level_2 = LevelState {
+        level: 2,
+        ..default()
+    };
+    let neutral_morale = Morale::default();
+    let high_morale = Morale(100);
+    let epic_shield = Item {
+        class: HeroClass::Tank,
+        rarity: ItemRarity::Epic,
+    };
+    let info = |level, class, morale, equipped| HeroInfo {
+        level,
+        class,
+        morale,
+        equipped,
+    };
+    let candidates = [
+        (
+            heroes[0],
+            info(&level_1, &HeroClass::Warrior, &neutral_morale, None),
+        ), // 20
+        (
+            heroes[1],
+            info(&level_2, &HeroClass::Support, &neutral_morale, None),
+        ), // 40
+        (
+            heroes[2],
+            info(
+                &level_1,
+                &HeroClass::Tank,
+                &neutral_morale,
+                Some(&epic_shield),
+            ),
+        ), // 40, and 50 on an Escort
+        (
+            heroes[3],
+            info(&level_1, &HeroClass::Warrior, &high_morale, None),
+        ), // 25
+        (
+            heroes[4],
+            info(&level_2, &HeroClass::Warrior, &neutral_morale, None),
+        ), // 40, and 50 in a Dungeon
+    ];
+
+    let bounty = QuestDescriptionBuilder::new().build();
+    assert_eq!(
+        rank_heroes_for_quest(&candidates, &bounty, 20),
+        vec![heroes[1], heroes[2], heroes[4], heroes[3], heroes[0]]
+    );
+    let escort = QuestDescriptionBuilder::new()
+        .quest_type(QuestType::Escort)
+        .build();
+    assert_eq!(
+        rank_heroes_for_quest(&candidates, &escort, 20),
+        vec![heroes[2], heroes[1], heroes[4], heroes[3], heroes[0]]
+    );
+    let dungeon = QuestDescriptionBuilder::new()
+        .quest_type(QuestType::Dungeon)
+        .build();
+    assert_eq!(
+        rank_heroes_for_quest(&candidates, &dungeon, 20),
+        vec![heroes[4], heroes[1], heroes[2], heroes[3], heroes[0]]
+    );
+}
+
+#[test]
+fn rank_heroes_for_quest_breaks_ties_by_entity() {
+    let mut world = World::new();
+    let heroes: Vec<Entity> = (0..4).map(|_| world.spawn_empty().id()).collect();
+    let level = LevelState::default();
+    let morale = Morale::default();
+    // Equally strong heroes, listed out of entity order
+    let candidates: Vec<(Entity, HeroInfo)> = [heroes[2], heroes[0], heroes[3], heroes[1]]
+        .into_iter()
+        .map(|hero| {
+            (
+                hero,
+                HeroInfo {
+                    level: &level,
+                    class: &HeroClass::Warrior,
+                    morale: &morale,
+                    equipped: None,
+                },
+            )
+        })
+        .collect();
+
+    let quest = QuestDescriptionBuilder::new().build();
+    assert_eq!(rank_heroes_for_quest(&candidates, &quest, 20), heroes);
+    // Ranking the same heroes again gives the same order
+    assert_eq!(rank_heroes_for_quest(&candidates, &quest, 20), heroes);
+}
+
+// Sort quests so the ones closest to expiring come first. Quests expiring together keep their order.
+fn sort_quests_by_urgency(quests: &mut [(Entity, &QuestDescription)]) {
+    quests.sort_by_key(|(_, description)| description.turns_to_expiry);
+}
+
+// Sort quests so the ones worth the most gold and exp together come first. Quests worth the same keep their order.
+fn sort_quests_by_reward(quests: &mut [(Entity, &QuestDescription)]) {
+    quests.sort_by_key(|(_, description)| {
+        std::cmp::Reverse(description.gold_reward + description.exp_reward)
+    });
+}
+
+#[cfg(test)]
+fn sort_test_quests(rewards_and_expiries: &[(u32, u32, u32)]) -> Vec<(Entity, QuestDescription)> {
+    let mut world = World::new();
+    rewards_and_expiries
+        .iter()
+        .map(|&(gold, exp, expiry)| {
+            (
+                world.spawn_empty().id(),
+                QuestDescriptionBuilder::new()
+                    .gold(gold)
+                    .exp(exp)
+                    .expiry(expiry)
+                    .build(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn sort_quests_by_urgency_puts_soonest_expiry_first() {
+    let quests = sort_test_quests(&[(100, 50, 8), (100, 50, 2), (100, 50, 8), (100, 50, 5)]);
+    let mut sorted: Vec<(Entity, &QuestDescription)> = quests
+        .iter()
+        .map(|(quest, description)| (*quest, description))
+        .collect();
+    sort_quests_by_urgency(&mut sorted);
+    let order: Vec<Entity> = sorted.iter().map(|(quest, _)| *quest).collect();
+    // The two quests expiring in 8 turns stay in their original order
+    assert_eq!(
+        order,
+        vec![quests[1].0, quests[3].0, quests[0].0, quests[2].0]
+    );
+
+    sort_quests_by_urgency(&mut []);
+}
+
+#[test]
+fn sort_quests_by_reward_puts_most_valuable_first() {
+    let quests = sort_test_quests(&[(100, 50, 10), (300, 0, 10), (50, 100, 10), (200, 100, 10)]);
+    let mut sorted: Vec<(Entity, &QuestDescription)> = quests
+        .iter()
+        .map(|(quest, description)| (*quest, description))
+        .collect();
+    sort_quests_by_reward(&mut sorted);
+    let order: Vec<Entity> = sorted.iter().map(|(quest, _)| *quest).collect();
+    // The two quests worth 150 in total stay in their original order
+    assert_eq!(
+        order,
+        vec![quests[1].0, quests[3].0, quests[0].0, quests[2].0]
+    );
+
+    sort_quests_by_reward(&mut []);
+}
+
+// When enabled, send idle heroes on available quests, most urgent first. Each quest takes the strongest willing idle
+// heroes while they keep improving its success probability, and is only started if the party beats the minimum.
+fn auto_assign_quests(
+    auto_assign: Res<QuestAutoAssign>,
+    quests_query: Query<
+        (Entity, &QuestDescription, &TurnTimer),
+        (With<Quest>, With<QuestStatusAvailable>),
+    >,
+    idle_heroes_query: Query<
+        QuestHero,
+        (
+            With<Hero>,
+            Without<Injured>,
+            Without<Fatigued>,
+            Without<HeroCooldown>,
+            With<HeroStatusIdle>,
+            Without<HeroStatusAvailable>,
+        ),
+    >,
+    names_query: Query<&HeroName>,
+    hero_config: Res<HeroConfig>,
+    guild_config: Res<GuildConfig>,
+    mut ev_start_quest: EventWriter<StartQuestEvent>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    if !auto_assign.enabled {
+        return;
+    }
+    // A quest's urgency depends on how long it has left on the board, not how long it was posted for
+    let remaining: Vec<(Entity, QuestDescription)> = quests_query
+        .iter()
+        .map(|(quest, description, expiry_timer)| {
+            (
+                quest,
+                QuestDescription {
+                    turns_to_expiry: expiry_timer.turns_remaining,
+                    ..description.clone()
+                },
+            )
+        })
+        .collect();
+    let mut quests: Vec<(Entity, &QuestDescription)> = remaining
+        .iter()
+        .map(|(quest, description)| (*quest, description))
+        .collect();
+    sort_quests_by_urgency(&mut quests);
+    let mut idle_heroes: Vec<_> = idle_heroes_query.iter().collect();
+    for (quest, description) in quests {
+        let candidates: Vec<(Entity, HeroInfo)> = idle_heroes
+            .iter()
+            .filter(|hero| {
+                !hero_refuses_quest(
+                    &hero.person.personality,
+                    hero.level.level,
+                    description.difficulty_level,
+                )
+            })
+            .map(|hero| {
+                (
+                    hero.entity,
+                    HeroInfo {
+                        level: hero.level,
+                        class: hero.class,
+                        morale: hero.morale,
+                        equipped: hero.inventory.equipped_item(),
+                    },
+                )
+            })
+            .collect();
+        let mut party: Vec<_> = Vec::new();
+        let mut party_probability = Percent(i32::MIN);
+        for hero in rank_heroes_for_quest(
+            &candidates,
+            description,
+            guild_config.effectiveness_per_level,
+        ) {
+            if party.len() >= description.max_party_size as usize {
+                break;
+            }
+            let index = idle_heroes
+                .iter()
+                .position(|candidate| candidate.entity == hero)
+                .unwrap();
+            let heroes: Vec<_> = party.iter().chain([&idle_heroes[index]]).copied().collect();
+            let probability = probability_of_quest_success(
+                description.difficulty_level,
+                description.quest_type,
+                description.faction,
+                description.alignment_affinity,
+                &heroes,
+                &hero_config,
+                &guild_config,
+            );
+            if probability.0 <= party_probability.0 {
+                break;
+            }
+            party.push(idle_heroes.remove(index));
+            party_probability = probability;
+        }
+
+        if party.is_empty() {
+            continue;
+        }
+        if party.len() < description.min_party_size as usize
+            || party_probability.0 <= auto_assign.min_success_probability.0
+        {
+            // Give the heroes back for the next quest to consider
+            idle_heroes.extend(party);
+            continue;
+        }
+        let heroes: Vec<Entity> = party.iter().map(|hero| hero.entity).collect();
+        let hero_names: Vec<String> = heroes
+            .iter()
+            .map(|hero| hero_display_name(&names_query, *hero))
+            .collect();
+        ev_notify.write(NotificationEvent(format!(
+            "Auto-assigned {} to a level {} quest with a {} chance of success",
+            hero_names.join(", "),
+            description.difficulty_level,
+            party_probability
+        )));
+        ev_start_quest.write(StartQuestEvent { quest, heroes });
+    }
+}
+
+#[cfg(test)]
+fn make_auto_assign_test_app() -> App {
+    let mut app = make_test_app();
+    app.insert_resource(QuestAutoAssign {
+        enabled: true,
+        min_success_probability: Percent(50),
+    });
+    app.add_systems(Update, auto_assign_quests);
+    app
+}
+
+#[cfg(test)]
+fn spawn_auto_assign_test_quest(
+    app: &mut App,
+    difficulty_level: u32,
+    turns_to_expiry: u32,
+) -> Entity {
+    let quest = spawn_quest(app, difficulty_level, 5);
+    app.world_mut()
+        .get_mut::<TurnTimer>(quest)
+        .unwrap()
+        .turns_remaining = turns_to_expiry;
+    quest
+}
+
+#[cfg(test)]
+fn read_auto_assign_test_start_events(app: &App) -> Vec<(Entity, Vec<Entity>)> {
+    let start_quest_events = app.world().resource::<Events<StartQuestEvent>>();
+    let mut reader = start_quest_events.get_cursor();
+    reader
+        .read(start_quest_events)
+        .map(|event| (event.quest, event.heroes.clone()))
+        .collect()
+}
+
+#[test]
+fn auto_assign_quests_matches_heroes_to_quests() {
+    let mut app = make_auto_assign_test_app();
+    let urgent_quest = spawn_auto_assign_test_quest(&mut app, 3, 2);
+    let easy_quest = spawn_auto_assign_test_quest(&mut app, 1, 8);
+    let veteran = spawn_hero(&mut app, HeroClass::Warrior, 3);
+    let rookie = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    let tank = spawn_hero(&mut app, HeroClass::Tank, 1);
+
+    app.update();
+
+    // The veteran does best alone on the urgent quest (80%), and the rookie alone on the easy one (80%).
+    // Adding the tank to either party would only drag its average down.
+    let events = read_auto_assign_test_start_events(&app);
+    assert_eq!(
+        events,
+        vec![(urgent_quest, vec![veteran]), (easy_quest, vec![rookie])]
+    );
+    assert!(!events.iter().any(|(_, heroes)| heroes.contains(&tank)));
+}
+
+#[test]
+fn auto_assign_quests_assigns_each_quest_and_hero_once() {
+    let mut app = make_auto_assign_test_app();
+    let quests = [
+        spawn_auto_assign_test_quest(&mut app, 1, 5),
+        spawn_auto_assign_test_quest(&mut app, 1, 6),
+        spawn_auto_assign_test_quest(&mut app, 1, 7),
+    ];
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+
+    app.update();
+
+    // Only one quest can have the single hero
+    let events = read_auto_assign_test_start_events(&app);
+    assert_eq!(events, vec![(quests[0], vec![hero])]);
+}
+
+#[test]
+fn auto_assign_quests_skips_unlikely_parties_and_respects_disabled() {
+    let mut app = make_auto_assign_test_app();
+    // A level 1 Warrior has a 40% chance at a level 3 quest, below the 50% minimum
+    spawn_auto_assign_test_quest(&mut app, 3, 5);
+    spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.update();
+    assert!(read_auto_assign_test_start_events(&app).is_empty());
+
+    let mut app = make_auto_assign_test_app();
+    app.insert_resource(QuestAutoAssign {
+        enabled: false,
+        min_success_probability: Percent(50),
+    });
+    spawn_auto_assign_test_quest(&mut app, 1, 5);
+    spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.update();
+    assert!(read_auto_assign_test_start_events(&app).is_empty());
+}
+
+// When a in-progress quest is complete, determine success and other outcomes, despawn the quest, and create a QuestCompleteEvent.
+fn complete_quest(
+    mut commands: Commands,
+    mut ev_turn_timer_complete: EventReader<TurnTimerCompleteEvent>,
+    mut random_src: ResMut<RandomSource>,
+    mut quests_query: Query<
+        (
+            &QuestDescription,
+            &Children,
+            Option<&QuestChain>,
+            &mut TurnTimer,
+        ),
+        (With<Quest>, With<QuestStatusInProgress>),
+    >,
+    heroes_query: Query<QuestHero, With<Hero>>,
+    mut biographies_query: Query<&mut Biography, With<Hero>>,
+    hero_config: Res<HeroConfig>,
+    guild_config: Res<GuildConfig>,
+    reputation: Res<GuildReputation>,
+    season: Res<Season>,
+    weather: Res<CurrentWeather>,
+    guild_hall: Res<GuildHallLevel>,
+    turn: Res<Turn>,
+    mut ev_quest_complete: EventWriter<QuestCompleteEvent>,
+) {
+    for TurnTimerCompleteEvent(entity) in ev_turn_timer_complete.read() {
+        if let Ok((description, children, chain, mut timer)) = quests_query.get_mut(*entity) {
+            let heroes: Vec<_> = children
+                .iter()
+                .map(|child| heroes_query.get(child).unwrap())
+                .collect();
+            let success_probability = probability_of_quest_success(
+                description.difficulty_level + season.current.difficulty_modifier(),
+                description.quest_type,
+                description.faction,
+                description.alignment_affinity,
+                &heroes[..],
+                &hero_config,
+                &guild_config,
+            ) + season.current.success_modifier()
+                + weather.0.success_modifier();
+            let rng = &mut random_src.0;
+            let is_successful = success_probability.distribution().sample(rng);
+            let injured_heroes = if is_successful {
+                Vec::new()
+            } else {
+                let party: Vec<(Entity, &HeroClass)> = heroes
+                    .iter()
+                    .map(|hero| (hero.entity, hero.class))
+                    .collect();
+                roll_injuries(success_probability, &party, rng)
+            };
+            let (event_type, outcome) = if is_successful {
+                (BiographyEventType::QuestCompleted, "Completed")
+            } else {
+                (BiographyEventType::QuestFailed, "Failed")
+            };
+            for child in children.iter() {
+                if let Ok(mut biography) = biographies_query.get_mut(child) {
+                    biography.record(
+                        turn.0,
+                        event_type,
+                        format!("{} {}", outcome, description.name),
+                    );
+                }
+            }
+            ev_quest_complete.write(QuestCompleteEvent {
+                quest_description: description.clone(), // Keeps the quest's name available after despawn
+                heroes: children.to_vec(),              // Heroes that were part of the quest
+                success_probability,
+                is_successful,
+                injured_heroes,
+                exp_reward: description.exp_reward, // Heroes gain experience regardless of success
+                gold_reward: if is_successful {
+                    (description.gold_reward as f32
+                        * reputation_gold_multiplier(reputation.0)
+                        * season.current.gold_multiplier()
+                        * guild_hall.gold_reward_multiplier())
+                    .round() as u32
+                } else {
+                    0
+                }, // Guild gains gold only on success, and a well known guild (or a hard season, or a grand guild hall) pays more
+                follow_up: chain.map(|chain| chain.follow_up.clone()),
+            });
+            // Remove ChildOf components before despawning quest, or heroes will be despawned with it.
+            for child in children.iter() {
+                commands
+                    .entity(child)
+                    .remove::<(ChildOf, HeroStatusOnQuest)>()
+                    .insert(HeroStatusIdle);
+            }
+            if is_successful && description.quest_type == QuestType::TradeRoute {
+                // An established trade route stays open, paying out every turn
+                timer.reset();
+                commands
+                    .entity(*entity)
+                    .remove::<QuestStatusInProgress>()
+                    .insert(TradeRouteActive {
+                        gold_per_turn: trade_route_income(description),
+                    });
+            } else {
+                commands.entity(*entity).despawn(); // Despawn the quest entity
+            }
+        }
+    }
+}
+
+#[test]
+fn complete_quest_despawns_quest_and_unlinks_heroes() {
+    let mut app = make_test_app();
+
+    // Add a quest with a TurnTimer
+    let quest_entity = app
+        .world_mut()
+        .spawn((
+            Quest,
+            QuestStatusInProgress,
+            TurnTimer {
+                initial_value: 5,
+                turns_remaining: 0,
+                timer_mode: TimerMode::Once,
+            },
+            QuestDescriptionBuilder::new()
+                .name("The Dragon's Lair".to_string())
+                .build(),
+        ))
+        .id();
+
+    // Add a hero to the quest
+    let hero_entity = app
+        .world_mut()
+        .spawn(HeroBundle {
+            name: HeroName("Aldric the Steadfast".to_string()),
+            ..default()
+        })
+        .id();
+
+    link_hero_to_quest(&mut app, hero_entity, quest_entity);
+
+    // Add the system under test
+    app.add_systems(Update, complete_quest);
+
+    // Send a TurnTimerCompleteEvent for the quest
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(quest_entity));
+
+    // Run the system
+    app.update();
+
+    // Check that the quest was despawned
+    assert!(!app.world().get::<Quest>(quest_entity).is_some());
+
+    // Check that the hero is still present and not despawned
+    assert!(app.world().get::<Hero>(hero_entity).is_some());
+
+    // Check that the ChildOf component was removed from the hero, and they're idle again
+    assert!(!app.world().get::<ChildOf>(hero_entity).is_some());
+    assert!(app.world().get::<HeroStatusOnQuest>(hero_entity).is_none());
+    assert!(app.world().get::<HeroStatusIdle>(hero_entity).is_some());
+
+    // Check that a QuestCompleteEvent was emitted, and contains reference to hero
+    let quest_complete_events = app.world().resource::<Events<QuestCompleteEvent>>();
+    let mut reader = quest_complete_events.get_cursor();
+    let event = reader.read(quest_complete_events).next().unwrap();
+    assert_eq!(event.heroes, vec![hero_entity]);
+    assert_eq!(event.quest_description.difficulty_level, 1);
+}
+
+#[test]
+fn complete_quest_copies_chain_follow_up_into_event() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest);
+
+    let follow_up = QuestDescription {
+        name: "The Dragon's Return".to_string(),
+        difficulty_level: 2,
+        turns_to_complete: 5,
+        exp_reward: 100,
+        gold_reward: 200,
+        item_reward: None,
+        turns_to_expiry: 10,
+        quest_type: QuestType::Bounty,
+        min_party_size: 1,
+        max_party_size: 3,
+        faction: None,
+        alignment_affinity: None,
+    };
+    let quest_entity = app
+        .world_mut()
+        .spawn((
+            Quest,
+            QuestStatusInProgress,
+            QuestDescription {
+                name: "The Dragon's Lair".to_string(),
+                difficulty_level: 1,
+                turns_to_complete: 5,
+                exp_reward: 50,
+                gold_reward: 100,
+                item_reward: None,
+                turns_to_expiry: 10,
+                quest_type: QuestType::Bounty,
+                min_party_size: 1,
+                max_party_size: 3,
+                faction: None,
+                alignment_affinity: None,
+            },
+            QuestChain { follow_up },
+        ))
+        .id();
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    link_hero_to_quest(&mut app, hero, quest_entity);
+
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(quest_entity));
+    app.update();
+
+    // The quest is gone, but its follow-up survives in the event
+    assert!(app.world().get_entity(quest_entity).is_err());
+    let quest_complete_events = app.world().resource::<Events<QuestCompleteEvent>>();
+    let mut reader = quest_complete_events.get_cursor();
+    let event = reader.read(quest_complete_events).next().unwrap();
+    assert_eq!(event.quest_description.name, "The Dragon's Lair");
+    let follow_up = event.follow_up.as_ref().unwrap();
+    assert_eq!(follow_up.name, "The Dragon's Return");
+    assert_eq!(follow_up.difficulty_level, 2);
+    assert_eq!(follow_up.gold_reward, 200);
+}
+
+#[test]
+fn complete_quest_scales_gold_reward_by_guild_hall_level() {
+    let mut app = make_test_app();
+    app.insert_resource(GuildHallLevel(3));
+    app.add_systems(Update, complete_quest);
+
+    let quest = spawn_quest(&mut app, 1, 5);
+    app.world_mut()
+        .entity_mut(quest)
+        .remove::<QuestStatusAvailable>()
+        .insert(QuestStatusInProgress);
+    // A level 10 hero can't fail a level 1 quest
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 10);
+    link_hero_to_quest(&mut app, hero, quest);
+
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(quest));
+    app.update();
+
+    let quest_complete_events = app.world().resource::<Events<QuestCompleteEvent>>();
+    let mut reader = quest_complete_events.get_cursor();
+    let event = reader.read(quest_complete_events).next().unwrap();
+    assert!(event.is_successful);
+    assert_eq!(event.gold_reward, 110);
+}
+
+#[test]
+fn complete_quest_scales_gold_reward_by_reputation() {
+    let mut app = make_test_app();
+    app.insert_resource(GuildReputation(100));
+    app.add_systems(Update, complete_quest);
+
+    let quest_entity = app
+        .world_mut()
+        .spawn((
+            Quest,
+            QuestStatusInProgress,
+            QuestDescriptionBuilder::new()
+                .name("The Dragon's Lair".to_string())
+                .build(),
+        ))
+        .id();
+    // A level 10 hero can't fail a level 1 quest
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 10);
+    link_hero_to_quest(&mut app, hero, quest_entity);
+
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(quest_entity));
+    app.update();
+
+    let quest_complete_events = app.world().resource::<Events<QuestCompleteEvent>>();
+    let mut reader = quest_complete_events.get_cursor();
+    let event = reader.read(quest_complete_events).next().unwrap();
+    assert!(event.is_successful);
+    assert_eq!(event.gold_reward, 150);
+}
+
+#[test]
+fn complete_quest_rolls_for_success() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest);
+    // A level 1 party on a level 1 quest might succeed or fail, so each roll decides it
+    install_mock_rng(&mut app, vec![false, true]);
+
+    for _ in 0..2 {
+        let quest = spawn_quest(&mut app, 1, 5);
+        app.world_mut()
+            .entity_mut(quest)
+            .remove::<QuestStatusAvailable>()
+            .insert(QuestStatusInProgress);
+        let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+        link_hero_to_quest(&mut app, hero, quest);
+        app.world_mut()
+            .resource_mut::<Events<TurnTimerCompleteEvent>>()
+            .send(TurnTimerCompleteEvent(quest));
+        app.update();
+    }
+
+    let quest_complete_events = app.world().resource::<Events<QuestCompleteEvent>>();
+    let mut reader = quest_complete_events.get_cursor();
+    let outcomes: Vec<(bool, u32)> = reader
+        .read(quest_complete_events)
+        .map(|event| (event.is_successful, event.gold_reward))
+        .collect();
+    assert_eq!(outcomes, vec![(false, 0), (true, 100)]);
+}
+
+#[test]
+fn complete_quest_records_injured_heroes() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest);
+    // The hopeless quest fails, then the Tank's 10% injury roll hits and the Warrior's halved 5% roll misses.
+    // The easy quest succeeds, so nobody rolls for injuries.
+    install_mock_rng(&mut app, vec![false, true, false, true]);
+
+    let hopeless_quest = spawn_quest(&mut app, 10, 5);
+    let easy_quest = spawn_quest(&mut app, 1, 5);
+    let tank = spawn_hero(&mut app, HeroClass::Tank, 1);
+    let warrior = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    let lucky_warrior = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    for (quest, party) in [
+        (hopeless_quest, vec![tank, warrior]),
+        (easy_quest, vec![lucky_warrior]),
+    ] {
+        app.world_mut()
+            .entity_mut(quest)
+            .remove::<QuestStatusAvailable>()
+            .insert(QuestStatusInProgress);
+        for hero in party {
+            link_hero_to_quest(&mut app, hero, quest);
+        }
+        app.world_mut()
+            .resource_mut::<Events<TurnTimerCompleteEvent>>()
+            .send(TurnTimerCompleteEvent(quest));
+        app.update();
+    }
+
+    let quest_complete_events = app.world().resource::<Events<QuestCompleteEvent>>();
+    let mut reader = quest_complete_events.get_cursor();
+    let injured_heroes: Vec<Vec<Entity>> = reader
+        .read(quest_complete_events)
+        .map(|event| event.injured_heroes.clone())
+        .collect();
+    assert_eq!(injured_heroes, vec![vec![tank], vec![]]);
+}
+
+#[test]
+fn complete_quest_keeps_successful_trade_route() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest);
+    install_mock_rng(&mut app, vec![true]);
+    let quest = spawn_quest(&mut app, 1, 5);
+    app.world_mut()
+        .entity_mut(quest)
+        .remove::<QuestStatusAvailable>()
+        .insert(QuestStatusInProgress);
+    app.world_mut()
+        .get_mut::<QuestDescription>(quest)
+        .unwrap()
+        .quest_type = QuestType::TradeRoute;
+    app.world_mut()
+        .get_mut::<TurnTimer>(quest)
+        .unwrap()
+        .turns_remaining = 0;
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    link_hero_to_quest(&mut app, hero, quest);
+
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(quest));
+    app.update();
+
+    // The quest stays on as the trade route, with its timer restarted, and the hero comes home
+    assert_eq!(
+        app.world()
+            .get::<TradeRouteActive>(quest)
+            .unwrap()
+            .gold_per_turn,
+        10
+    );
+    assert!(app.world().get::<QuestStatusInProgress>(quest).is_none());
+    assert_eq!(
+        app.world().get::<TurnTimer>(quest).unwrap().turns_remaining,
+        10
+    );
+    assert!(app.world().get::<ChildOf>(hero).is_none());
+    assert!(app.world().get::<HeroStatusIdle>(hero).is_some());
+}
+
+#[test]
+fn complete_quest_records_biography_entries_in_turn_order() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest);
+    // A level 1 party on a level 1 quest is likely enough that failing doesn't roll for injuries
+    install_mock_rng(&mut app, vec![true, false]);
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.world_mut()
+        .entity_mut(hero)
+        .insert(Biography::default());
+
+    for turn in [3, 7] {
+        app.world_mut().resource_mut::<Turn>().0 = turn;
+        let quest = spawn_quest(&mut app, 1, 5);
+        app.world_mut()
+            .entity_mut(quest)
+            .remove::<QuestStatusAvailable>()
+            .insert(QuestStatusInProgress);
+        link_hero_to_quest(&mut app, hero, quest);
+        app.world_mut()
+            .resource_mut::<Events<TurnTimerCompleteEvent>>()
+            .send(TurnTimerCompleteEvent(quest));
+        app.update();
+    }
+
+    let entries: Vec<(u32, BiographyEventType, String)> = app
+        .world()
+        .get::<Biography>(hero)
+        .unwrap()
+        .entries
+        .iter()
+        .map(|entry| (entry.turn, entry.event_type, entry.description.clone()))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            (
+                3,
+                BiographyEventType::QuestCompleted,
+                "Completed The Dragon's Lair".to_string()
+            ),
+            (
+                7,
+                BiographyEventType::QuestFailed,
+                "Failed The Dragon's Lair".to_string()
+            ),
+        ]
+    );
+}
+
+#[cfg(test)]
+fn complete_seasonal_test_quest(season: SeasonKind) -> (Percent, u32) {
+    complete_test_quest_in_conditions(season, WeatherCondition::Clear)
+}
+
+#[cfg(test)]
+fn complete_test_quest_in_conditions(
+    season: SeasonKind,
+    weather: WeatherCondition,
+) -> (Percent, u32) {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest);
+    app.world_mut().resource_mut::<Season>().current = season;
+    app.world_mut().resource_mut::<CurrentWeather>().0 = weather;
+    install_mock_rng(&mut app, vec![true]);
+    let quest = spawn_quest(&mut app, 1, 5);
+    app.world_mut()
+        .entity_mut(quest)
+        .remove::<QuestStatusAvailable>()
+        .insert(QuestStatusInProgress);
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 2);
+    link_hero_to_quest(&mut app, hero, quest);
+
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(quest));
+    app.update();
+
+    let events = app.world().resource::<Events<QuestCompleteEvent>>();
+    let mut reader = events.get_cursor();
+    let event = reader.read(events).next().unwrap();
+    (event.success_probability, event.gold_reward)
+}
+
+#[test]
+fn complete_quest_applies_season_modifiers() {
+    let (spring_probability, spring_gold) = complete_seasonal_test_quest(SeasonKind::Spring);
+    assert_eq!(spring_gold, 100);
+
+    let (summer_probability, summer_gold) = complete_seasonal_test_quest(SeasonKind::Summer);
+    assert_eq!(summer_probability, spring_probability);
+    assert_eq!(summer_gold, 105);
+
+    let (autumn_probability, autumn_gold) = complete_seasonal_test_quest(SeasonKind::Autumn);
+    assert_eq!(autumn_probability, spring_probability - Percent(10));
+    assert_eq!(autumn_gold, 100);
+
+    // Winter makes the quest a level harder
+    let (winter_probability, winter_gold) = complete_seasonal_test_quest(SeasonKind::Winter);
+    assert_eq!(
+        winter_probability,
+        spring_probability - Percent(GuildConfig::default().effectiveness_per_level)
+    );
+    assert_eq!(winter_gold, 130);
+}
+
+#[test]
+fn complete_quest_applies_weather_modifier() {
+    let (clear_probability, _) =
+        complete_test_quest_in_conditions(SeasonKind::Spring, WeatherCondition::Clear);
+    for (weather, modifier) in [
+        (WeatherCondition::Rainy, 5),
+        (WeatherCondition::Stormy, 15),
+        (WeatherCondition::Blizzard, 25),
+    ] {
+        let (probability, _) = complete_test_quest_in_conditions(SeasonKind::Spring, weather);
+        assert_eq!(probability, clear_probability - Percent(modifier));
+    }
+}
+
+// Gold a newly established trade route pays every turn: a tenth of the quest's gold reward, but always something.
+fn trade_route_income(description: &QuestDescription) -> u32 {
+    (description.gold_reward / 10).max(1)
+}
+
+// Each turn, every open trade route pays the guild.
+fn collect_trade_route_income(
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    routes_query: Query<&TradeRouteActive>,
+    mut guild: ResMut<Guild>,
+) {
+    let turn_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    if turn_delta == 0 {
+        return;
+    }
+    let income: u64 = routes_query
+        .iter()
+        .map(|route| u64::from(route.gold_per_turn) * u64::from(turn_delta))
+        .sum();
+    guild.gold = guild.gold.saturating_add(income);
+}
+
+#[test]
+fn collect_trade_route_income_pays_every_turn() {
+    let mut app = make_test_app();
+    app.add_systems(Update, collect_trade_route_income);
+    app.world_mut()
+        .spawn(TradeRouteActive { gold_per_turn: 10 });
+    app.world_mut().spawn(TradeRouteActive { gold_per_turn: 5 });
+
+    for (turns, expected_gold) in [(1, 15), (1, 30), (3, 75)] {
+        send_turns(&mut app, turns);
+        assert_eq!(app.world().resource::<Guild>().gold, expected_gold);
+    }
+}
+
+// Each turn there's a small chance of bandits raiding the guild's trade routes, as long as it has any.
+fn roll_bandit_raid(
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    routes_query: Query<(), With<TradeRouteActive>>,
+    mut random_src: ResMut<RandomSource>,
+    mut ev_bandit_raid: EventWriter<BanditRaidEvent>,
+) {
+    let raid_chance = Percent(5); // Chance of a raid each turn
+    let turn_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    if routes_query.is_empty() {
+        return;
+    }
+    for _ in 0..turn_delta {
+        if raid_chance.distribution().sample(&mut random_src.0) {
+            ev_bandit_raid.write(BanditRaidEvent);
+            return;
+        }
+    }
+}
+
+// Bandits close every open trade route.
+fn handle_bandit_raid(
+    mut commands: Commands,
+    mut ev_bandit_raid: EventReader<BanditRaidEvent>,
+    routes_query: Query<(Entity, &QuestDescription), With<TradeRouteActive>>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    if ev_bandit_raid.read().count() == 0 {
+        return;
+    }
+    for (route, description) in routes_query.iter() {
+        commands.entity(route).despawn();
+        ev_notify.write(NotificationEvent(format!(
+            "Bandits raided the trade route from {}, and it has closed",
+            description.name
+        )));
+    }
+}
+
+#[test]
+fn bandit_raid_closes_trade_routes() {
+    let mut app = make_test_app();
+    app.add_systems(Update, (roll_bandit_raid, handle_bandit_raid).chain());
+    let route = spawn_quest(&mut app, 1, 5);
+    app.world_mut()
+        .entity_mut(route)
+        .remove::<QuestStatusAvailable>()
+        .insert(TradeRouteActive { gold_per_turn: 10 });
+    let quest = spawn_quest(&mut app, 1, 5);
+
+    // The first turn's roll misses, and the second's hits
+    install_mock_rng(&mut app, vec![false, true]);
+    send_turns(&mut app, 1);
+    assert!(app.world().get_entity(route).is_ok());
+
+    send_turns(&mut app, 1);
+    assert!(app.world().get_entity(route).is_err());
+    // Ordinary quests aren't affected
+    assert!(app.world().get_entity(quest).is_ok());
+    assert_eq!(
+        last_notification(&app),
+        "Bandits raided the trade route from The Dragon's Lair, and it has closed"
+    );
+}
+
+// Pick an event from the table, each with a chance in proportion to its weight.
+// The roll is between 0 and 1, and None is picked only if no event has any weight.
+fn pick_random_event(
+    events: &[RandomEventDefinition],
+    roll: f64,
+) -> Option<&RandomEventDefinition> {
+    let total_weight: u32 = events.iter().map(|event| event.weight).sum();
+    let target = roll * f64::from(total_weight);
+    let mut cumulative_weight = 0;
+    events
+        .iter()
+        .filter(|event| event.weight > 0)
+        .find(|event| {
+            cumulative_weight += event.weight;
+            target < f64::from(cumulative_weight)
+        })
+}
+
+// Each turn there's a small chance of a random event striking the guild, for better or worse.
+fn trigger_random_events(
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    config: Res<RandomEventConfig>,
+    mut guild: ResMut<Guild>,
+    mut morale_query: Query<&mut Morale, With<Hero>>,
+    mut random_src: ResMut<RandomSource>,
+    mut ev_bandit_raid: EventWriter<BanditRaidEvent>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let morale_change = 10; // Morale gained by inspiration, or lost to fire, by each hero
+    let turn_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    let probability = f64::from(config.event_probability_per_turn.clamp(0.0, 1.0));
+    for _ in 0..turn_delta {
+        if !random_src.0.random_bool(probability) {
+            continue;
+        }
+        let roll: f64 = random_src.0.random();
+        let Some(event) = pick_random_event(&config.events, roll) else {
+            continue;
+        };
+        let message = match event.effect {
+            RandomEventEffect::GoldWindfall(gold) => {
+                guild.gold = guild.gold.saturating_add(u64::from(gold));
+                format!("{}: the guild gained {} gold", event.name, gold)
+            }
+            RandomEventEffect::BanditRaid { gold_loss } => {
+                let lost = u64::from(gold_loss).min(guild.gold);
+                guild.gold -= lost;
+                ev_bandit_raid.write(BanditRaidEvent);
+                format!("{}: bandits stole {} gold", event.name, lost)
+            }
+            RandomEventEffect::HeroInspiration => {
+                for mut morale in morale_query.iter_mut() {
+                    morale.0 = (morale.0 + morale_change).min(100);
+                }
+                format!("{}: the guild's heroes are inspired", event.name)
+            }
+            RandomEventEffect::GuildFire => {
+                for mut morale in morale_query.iter_mut() {
+                    morale.0 = (morale.0 - morale_change).max(0);
+                }
+                format!("{}: the guild's heroes are shaken", event.name)
+            }
+        };
+        ev_notify.write(NotificationEvent(message));
+    }
+}
+
+#[test]
+fn trigger_random_events_gold_windfall() {
+    let mut app = make_test_app();
+    app.insert_resource(RandomEventConfig {
+        event_probability_per_turn: 0.5,
+        events: vec![
+            RandomEventDefinition {
+                name: "Test Event".to_string(),
+                weight: 1,
+                effect: RandomEventEffect::GoldWindfall(100),
+            },
+            RandomEventDefinition {
+                name: "Test Event".to_string(),
+                weight: 1,
+                effect: RandomEventEffect::GuildFire,
+            },
+        ],
+    });
+    app.add_systems(Update, (trigger_random_events, handle_bandit_raid).chain());
+    app.insert_resource(Guild { gold: 200 });
+
+    // No event strikes
+    install_mock_rng(&mut app, vec![false]);
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().resource::<Guild>().gold, 200);
+
+    // An event strikes, and the lowest roll picks the first event
+    install_mock_rng(&mut app, vec![true, true]);
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().resource::<Guild>().gold, 300);
+    assert_eq!(
+        last_notification(&app),
+        "Test Event: the guild gained 100 gold"
+    );
+}
+
+#[test]
+fn trigger_random_events_bandit_raid() {
+    let mut app = make_test_app();
+    app.insert_resource(RandomEventConfig {
+        event_probability_per_turn: 0.5,
+        events: vec![
+            RandomEventDefinition {
+                name: "Test Event".to_string(),
+                weight: 1,
+                effect: RandomEventEffect::GoldWindfall(100),
+            },
+            RandomEventDefinition {
+                name: "Test Event".to_string(),
+                weight: 1,
+                effect: RandomEventEffect::BanditRaid { gold_loss: 50 },
+            },
+        ],
+    });
+    app.add_systems(Update, (trigger_random_events, handle_bandit_raid).chain());
+    app.insert_resource(Guild { gold: 200 });
+    let route = spawn_quest(&mut app, 1, 5);
+    app.world_mut()
+        .entity_mut(route)
+        .remove::<QuestStatusAvailable>()
+        .insert(TradeRouteActive { gold_per_turn: 10 });
+
+    // The highest roll picks the last event
+    install_mock_rng(&mut app, vec![true, false]);
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().resource::<Guild>().gold, 150);
+    assert!(app.world().get_entity(route).is_err());
+
+    // Bandits can't steal more gold than the guild has
+    app.insert_resource(Guild { gold: 30 });
+    install_mock_rng(&mut app, vec![true, false]);
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().resource::<Guild>().gold, 0);
+}
+
+#[test]
+fn trigger_random_events_changes_hero_morale() {
+    let mut app = make_test_app();
+    app.insert_resource(RandomEventConfig {
+        event_probability_per_turn: 0.5,
+        events: vec![
+            RandomEventDefinition {
+                name: "Test Event".to_string(),
+                weight: 1,
+                effect: RandomEventEffect::HeroInspiration,
+            },
+            RandomEventDefinition {
+                name: "Test Event".to_string(),
+                weight: 1,
+                effect: RandomEventEffect::GuildFire,
+            },
+        ],
+    });
+    app.add_systems(Update, (trigger_random_events, handle_bandit_raid).chain());
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+
+    install_mock_rng(&mut app, vec![true, true]);
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().get::<Morale>(hero).unwrap().0, 60);
+
+    install_mock_rng(&mut app, vec![true, false]);
+    send_turns(&mut app, 1);
+    install_mock_rng(&mut app, vec![true, false]);
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().get::<Morale>(hero).unwrap().0, 40);
+}
+
+// How much quest gold rewards are scaled by the guild's reputation.
+fn reputation_gold_multiplier(reputation: i32) -> f32 {
+    (1.0 + reputation as f32 / 200.0).clamp(0.5, 2.0)
+}
+
+#[test]
+fn reputation_gold_multiplier_scales_with_reputation() {
+    assert_eq!(reputation_gold_multiplier(0), 1.0);
+    assert_eq!(reputation_gold_multiplier(100), 1.5);
+    assert_eq!(reputation_gold_multiplier(-50), 0.75);
+    // The multiplier is clamped at both ends
+    assert_eq!(reputation_gold_multiplier(1000), 2.0);
+    assert_eq!(reputation_gold_multiplier(-1000), 0.5);
+}
+
+// Every guild building that has finished construction.
+type BuildingOperationalQuery<'w, 's> = Query<'w, 's, &'static GuildBuilding, With<Operational>>;
+
+// The components of a hero that affect their chance of success on a quest.
+#[derive(QueryData)]
+#[query_data(derive(Clone, Copy))]
+struct QuestHero {
+    entity: Entity,
+    level: &'static LevelState,
+    class: &'static HeroClass,
+    person: &'static Person,
+    age: &'static Age,
+    morale: &'static Morale,
+    quest_count: &'static QuestCount,
+    inventory: &'static Inventory,
+    faction: Option<&'static HeroFaction>,
+    specialization: Option<&'static Specialization>,
+    skills: Option<&'static Skills>,
+    alignment: Option<&'static Alignment>,
+    companion: Option<&'static Companion>,
+    mourning: Option<&'static MourningDebuff>,
+    rival: Option<&'static Rival>,
+}
+
+// Owns everything a QuestHero borrows, so tests can build a party without spawning heroes.
+#[cfg(any(test, fuzzing))]
+struct QuestHeroFixture {
+    entity: Entity,
+    level: LevelState,
+    class: HeroClass,
+    person: Person,
+    age: Age,
+    morale: Morale,
+    quest_count: QuestCount,
+    inventory: Inventory,
+    faction: Option<HeroFaction>,
+    specialization: Option<Specialization>,
+    skills: Option<Skills>,
+    alignment: Option<Alignment>,
+    companion: Option<Companion>,
+    mourning: Option<MourningDebuff>,
+    rival: Option<Rival>,
+}
+
+#[cfg(any(test, fuzzing))]
+impl QuestHeroFixture {
+    // A young hero of the given class and level, with nothing else out of the ordinary.
+    fn new(class: HeroClass, level: u32) -> Self {
+        QuestHeroFixture {
+            entity: Entity::PLACEHOLDER,
+            level: LevelState { level, ..default() },
+            class,
+            person: Person::default(),
+            age: Age::default(),
+            morale: Morale::default(),
+            quest_count: QuestCount::default(),
+            inventory: Inventory::default(),
+            faction: None,
+            specialization: None,
+            skills: None,
+            alignment: None,
+            companion: None,
+            mourning: None,
+            rival: None,
+        }
+    }
+
+    fn item(&self) -> QuestHeroItem<'_> {
+        QuestHeroItem {
+            entity: self.entity,
+            level: &self.level,
+            class: &self.class,
+            person: &self.person,
+            age: &self.age,
+            morale: &self.morale,
+            quest_count: &self.quest_count,
+            inventory: &self.inventory,
+            faction: self.faction.as_ref(),
+            specialization: self.specialization.as_ref(),
+            skills: self.skills.as_ref(),
+            alignment: self.alignment.as_ref(),
+            companion: self.companion.as_ref(),
+            mourning: self.mourning.as_ref(),
+            rival: self.rival.as_ref(),
+        }
+    }
+}
+
+#[cfg(any(test, fuzzing))]
+fn quest_hero_items(heroes: &[QuestHeroFixture]) -> Vec<QuestHeroItem<'_>> {
+    heroes.iter().map(QuestHeroFixture::item).collect()
+}
+
+fn probability_of_quest_success(
+    difficulty_level: u32,
+    quest_type: QuestType,
+    quest_faction: Option<Faction>,
+    quest_alignment: Option<Alignment>,
+    heros: &[QuestHeroItem],
+    hero_config: &HeroConfig,
+    guild_config: &GuildConfig,
+) -> Percent {
+    if heros.is_empty() {
+        return Percent(0); // Nobody to attempt the quest
+    }
+    let party: Vec<Entity> = heros.iter().map(|hero| hero.entity).collect();
+    let total_effectiveness: Percent = heros
+        .iter()
+        .map(|hero| -> Percent {
+            let brave_bonus = 10; // Brave heroes rise to the challenge of quests above their level
+            let effective_level =
+                hero.level
+                    .effective_level(hero.class, hero.inventory, hero.age, hero_config);
+            let level_diff = effective_level - difficulty_level as f32; // Positive if hero is stronger than difficulty level
+            let personality_bonus = match hero.person.personality {
+                Personality::Brave if level_diff < 0.0 => brave_bonus,
+                _ => 0,
+            };
+            Percent(
+                guild_config.baseline_effectiveness
+                    + (level_diff * guild_config.effectiveness_per_level as f32) as i32
+                    + personality_bonus
+                    + quest_type_class_modifier(quest_type, hero.class)
+                    + morale_modifier(hero.morale)
+                    + veteran_bonus(hero.quest_count)
+                    + faction_modifier(hero.faction.map(|faction| faction.0), quest_faction)
+                    + hero
+                        .specialization
+                        .map_or(0, |specialization| specialization.effectiveness_bonus())
+                    + hero
+                        .skills
+                        .map_or(0, |skills| skills.effectiveness_bonus(quest_type))
+                    + alignment_modifier(hero.alignment.copied(), quest_alignment)
+                    + companion_modifier(hero.companion, hero.mourning, &party)
+                    + rival_modifier(hero.rival, &party),
+            )
+        })
+        .sum();
+    let average_effectiveness = total_effectiveness / heros.len() as i32;
+    let classes: Vec<&HeroClass> = heros.iter().map(|hero| hero.class).collect();
+    let members: Vec<(Entity, &Person)> = heros
+        .iter()
+        .map(|hero| (hero.entity, hero.person))
+        .collect();
+    let probability = average_effectiveness
+        + party_composition_modifiers(&classes)
+        + party_cohesion_modifier(&members);
+    let tank_floor = 40; // A Tank keeps the party alive, so success never drops below this
+    if classes.contains(&&HeroClass::Tank) {
+        Percent(probability.0.max(tank_floor))
+    } else {
+        probability
+    }
+}
+
+// Heroes take to a quest that suits their alignment, and balk at one that goes against it. Neutral heroes and quests don't mind either way.
+fn alignment_modifier(
+    hero_alignment: Option<Alignment>,
+    quest_alignment: Option<Alignment>,
+) -> i32 {
+    let matching_bonus = 10;
+    let mismatching_penalty = -10;
+    match (hero_alignment, quest_alignment) {
+        (Some(Alignment::Neutral), _) | (_, Some(Alignment::Neutral)) => 0,
+        (Some(hero), Some(quest)) if hero == quest => matching_bonus,
+        (Some(hero), Some(quest)) if hero.opposes(&quest) => mismatching_penalty,
+        _ => 0,
+    }
+}
+
+#[test]
+fn alignment_modifier_favours_matching_alignment() {
+    assert_eq!(
+        alignment_modifier(Some(Alignment::Law), Some(Alignment::Law)),
+        10
+    );
+    assert_eq!(
+        alignment_modifier(Some(Alignment::Chaos), Some(Alignment::Chaos)),
+        10
+    );
+    assert_eq!(
+        alignment_modifier(Some(Alignment::Law), Some(Alignment::Chaos)),
+        -10
+    );
+    assert_eq!(
+        alignment_modifier(Some(Alignment::Chaos), Some(Alignment::Law)),
+        -10
+    );
+    // Neutrality is neither a bonus nor a penalty
+    for alignment in [Alignment::Law, Alignment::Neutral, Alignment::Chaos] {
+        assert_eq!(
+            alignment_modifier(Some(Alignment::Neutral), Some(alignment)),
+            0
+        );
+        assert_eq!(
+            alignment_modifier(Some(alignment), Some(Alignment::Neutral)),
+            0
+        );
+        // Quests without an affinity, and heroes without an alignment, are unaffected
+        assert_eq!(alignment_modifier(Some(alignment), None), 0);
+        assert_eq!(alignment_modifier(None, Some(alignment)), 0);
+    }
+}
+
+#[test]
+fn probability_of_quest_success_applies_alignment_modifier() {
+    let config = HeroConfig::default();
+    let guild_config = GuildConfig::default();
+    let hero = || QuestHeroFixture {
+        alignment: Some(Alignment::Law),
+        ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+    };
+    let heroes = [hero(), hero()];
+    let party = quest_hero_items(&heroes);
+    let probability_for = |quest_alignment: Option<Alignment>| {
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            quest_alignment,
+            &party,
+            &config,
+            &guild_config,
+        )
+    };
+
+    assert_eq!(probability_for(None), Percent(70));
+    assert_eq!(probability_for(Some(Alignment::Law)), Percent(80));
+    assert_eq!(probability_for(Some(Alignment::Neutral)), Percent(70));
+    assert_eq!(probability_for(Some(Alignment::Chaos)), Percent(60));
+}
+
+// Companions fight better side by side, and a hero who has lost theirs never fully recovers.
+fn companion_modifier(
+    companion: Option<&Companion>,
+    mourning: Option<&MourningDebuff>,
+    party: &[Entity],
+) -> i32 {
+    let companion_bonus = 5;
+    let mourning_penalty = -10;
+    let bonus = match companion {
+        Some(companion) if party.contains(&companion.0) => companion_bonus,
+        _ => 0,
+    };
+    let penalty = if mourning.is_some() {
+        mourning_penalty
+    } else {
+        0
+    };
+    bonus + penalty
+}
+
+#[test]
+fn probability_of_quest_success_applies_companion_bonus() {
+    let config = HeroConfig::default();
+    let guild_config = GuildConfig::default();
+    let first = Entity::from_raw(1);
+    let second = Entity::from_raw(2);
+    let stranger = Entity::from_raw(3);
+    // Each hero in the party, with the hero they're bonded to, and whether they're mourning
+    let probability_for = |party: &[(Entity, Option<Entity>, bool)]| {
+        let heroes: Vec<QuestHeroFixture> = party
+            .iter()
+            .map(|(entity, companion, is_mourning)| QuestHeroFixture {
+                entity: *entity,
+                companion: companion.map(Companion),
+                mourning: is_mourning.then_some(MourningDebuff),
+                ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+            })
+            .collect();
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &quest_hero_items(&heroes),
+            &config,
+            &guild_config,
+        )
+    };
+
+    assert_eq!(
+        probability_for(&[(first, None, false), (second, None, false)]),
+        Percent(70)
+    );
+    // Each companion gets +5% with the other in the party
+    assert_eq!(
+        probability_for(&[(first, Some(second), false), (second, Some(first), false)]),
+        Percent(75)
+    );
+    // Without their companion along, there's no bonus
+    assert_eq!(
+        probability_for(&[(first, Some(second), false), (stranger, None, false)]),
+        Percent(70)
+    );
+    // A mourning hero is 10% less effective, whoever they quest with
+    assert_eq!(
+        probability_for(&[(first, None, true), (stranger, None, false)]),
+        Percent(65)
+    );
+}
+
+// A hero pushes themselves to outdo a rival in the same party.
+fn rival_modifier(rival: Option<&Rival>, party: &[Entity]) -> i32 {
+    let rival_bonus = 10;
+    match rival {
+        Some(rival) if party.contains(&rival.0) => rival_bonus,
+        _ => 0,
+    }
+}
+
+#[test]
+fn rival_modifier_applies_with_rival_in_party() {
+    let hero = Entity::from_raw(1);
+    let rival = Entity::from_raw(2);
+    let stranger = Entity::from_raw(3);
+    assert_eq!(rival_modifier(Some(&Rival(rival)), &[hero, rival]), 10);
+    assert_eq!(rival_modifier(Some(&Rival(rival)), &[hero, stranger]), 0);
+    assert_eq!(rival_modifier(None, &[hero, rival]), 0);
+}
+
+// Heroes fight harder for a quest of their own faction, and half-heartedly for an opposing one.
+fn faction_modifier(hero_faction: Option<Faction>, quest_faction: Option<Faction>) -> i32 {
+    let matching_bonus = 15;
+    let opposing_penalty = -10;
+    match (hero_faction, quest_faction) {
+        (Some(hero), Some(quest)) if hero == quest => matching_bonus,
+        (Some(hero), Some(quest)) if hero.opposes(&quest) => opposing_penalty,
+        _ => 0,
+    }
+}
+
+#[test]
+fn faction_modifier_favours_matching_faction() {
+    let factions = [
+        Faction::Order,
+        Faction::Chaos,
+        Faction::Nature,
+        Faction::Arcane,
+    ];
+    for hero in factions {
+        for quest in factions {
+            let expected = match (hero, quest) {
+                _ if hero == quest => 15,
+                (Faction::Order, Faction::Chaos)
+                | (Faction::Chaos, Faction::Order)
+                | (Faction::Nature, Faction::Arcane)
+                | (Faction::Arcane, Faction::Nature) => -10,
+                _ => 0,
+            };
+            assert_eq!(faction_modifier(Some(hero), Some(quest)), expected);
+        }
+        // Unaligned heroes and quests are unaffected
+        assert_eq!(faction_modifier(Some(hero), None), 0);
+        assert_eq!(faction_modifier(None, Some(hero)), 0);
+    }
+    assert_eq!(faction_modifier(None, None), 0);
+}
+
+#[test]
+fn probability_of_quest_success_applies_faction_modifier() {
+    let config = HeroConfig::default();
+    let guild_config = GuildConfig::default();
+    let hero = || QuestHeroFixture {
+        faction: Some(HeroFaction(Faction::Order)),
+        ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+    };
+    let heroes = [hero(), hero()];
+    let party = quest_hero_items(&heroes);
+    let probability_for = |quest_faction: Option<Faction>| {
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            quest_faction,
+            None,
+            &party,
+            &config,
+            &guild_config,
+        )
+    };
+
+    assert_eq!(probability_for(None), Percent(70));
+    assert_eq!(probability_for(Some(Faction::Order)), Percent(85));
+    assert_eq!(probability_for(Some(Faction::Chaos)), Percent(60));
+    assert_eq!(probability_for(Some(Faction::Nature)), Percent(70));
+}
+
+// High morale makes a hero more effective, and low morale less so.
+fn morale_modifier(morale: &Morale) -> i32 {
+    (morale.0 - 50) / 10
+}
+
+#[test]
+fn probability_of_quest_success_applies_morale_modifier() {
+    let config = HeroConfig::default();
+    let guild_config = GuildConfig::default();
+    let probability_with_morale = |morale: i32| {
+        let hero = || QuestHeroFixture {
+            morale: Morale(morale),
+            ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+        };
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &quest_hero_items(&[hero(), hero()]),
+            &config,
+            &guild_config,
+        )
+    };
+
+    assert_eq!(probability_with_morale(0), Percent(65));
+    assert_eq!(probability_with_morale(50), Percent(70));
+    assert_eq!(probability_with_morale(100), Percent(75));
+}
+
+// Experienced heroes with a strong track record get a flat bonus.
+fn veteran_bonus(quest_count: &QuestCount) -> i32 {
+    let min_completed = 10; // Quests a hero must have completed to count as a veteran
+    let min_success_rate = 0.8; // Fraction of quests a veteran must have succeeded at
+    let bonus = 5;
+    let total = quest_count.completed + quest_count.failed;
+    if quest_count.completed >= min_completed
+        && quest_count.completed as f32 / total as f32 >= min_success_rate
+    {
+        bonus
+    } else {
+        0
+    }
+}
+
+#[test]
+fn probability_of_quest_success_applies_veteran_bonus() {
+    let config = HeroConfig::default();
+    let guild_config = GuildConfig::default();
+    let probability_with_record = |completed, failed| {
+        let hero = || QuestHeroFixture {
+            quest_count: QuestCount { completed, failed },
+            ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+        };
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &quest_hero_items(&[hero(), hero()]),
+            &config,
+            &guild_config,
+        )
+    };
+
+    assert_eq!(probability_with_record(0, 0), Percent(70));
+    // Not enough quests yet, even with a perfect record
+    assert_eq!(probability_with_record(9, 0), Percent(70));
+    assert_eq!(probability_with_record(10, 0), Percent(75));
+    // Exactly an 80% success rate still counts
+    assert_eq!(probability_with_record(12, 3), Percent(75));
+    // One more failure drops the hero below 80%, losing the bonus
+    assert_eq!(probability_with_record(12, 4), Percent(70));
+}
+
+// Bonus to a hero's effectiveness when the quest type suits their class.
+fn quest_type_class_modifier(quest_type: QuestType, class: &HeroClass) -> i32 {
+    let specialist_bonus = 10; // A hero on the kind of quest their class is made for
+    match (quest_type, class) {
+        (QuestType::Dungeon, HeroClass::Warrior)
+        | (QuestType::Escort, HeroClass::Tank)
+        | (QuestType::Investigation, HeroClass::Support) => specialist_bonus,
+        (QuestType::Investigation, HeroClass::Rogue) => 20, // Nobody is better at digging up secrets
+        (QuestType::Bounty, HeroClass::Rogue) => 10,
+        (QuestType::Escort, HeroClass::Rogue) => -10, // Rogues attract unwanted attention
+        _ => 0,
+    }
+}
+
+#[test]
+fn probability_of_quest_success_applies_skill_bonus() {
+    let config = HeroConfig::default();
+    let guild_config = GuildConfig::default();
+    let mut skills = Skills::default();
+    skills.get_mut(SkillType::Scouting).unwrap().level = 2;
+    let probability_with = |skills: Option<&Skills>, quest_type: QuestType| {
+        let hero = || QuestHeroFixture {
+            skills: skills.cloned(),
+            ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+        };
+        probability_of_quest_success(
+            3,
+            quest_type,
+            None,
+            None,
+            &quest_hero_items(&[hero(), hero()]),
+            &config,
+            &guild_config,
+        )
+    };
+
+    // Level 2 Scouting gives +10% on Investigations, and nothing on other quests
+    assert_eq!(
+        probability_with(Some(&skills), QuestType::Investigation),
+        probability_with(None, QuestType::Investigation) + Percent(10)
+    );
+    assert_eq!(
+        probability_with(Some(&skills), QuestType::Bounty),
+        probability_with(None, QuestType::Bounty)
+    );
+    // Untrained skills give nothing
+    assert_eq!(
+        probability_with(Some(&Skills::default()), QuestType::Investigation),
+        probability_with(None, QuestType::Investigation)
+    );
+}
+
+#[test]
+fn quest_type_class_modifier_favours_matching_class() {
+    let quest_types = [
+        QuestType::Dungeon,
+        QuestType::Escort,
+        QuestType::Investigation,
+        QuestType::Bounty,
+        QuestType::TradeRoute,
+    ];
+    for quest_type in quest_types {
+        for class in ALL_CLASSES.iter() {
+            let expected = match (quest_type, class) {
+                (QuestType::Dungeon, HeroClass::Warrior) => 10,
+                (QuestType::Escort, HeroClass::Tank) => 10,
+                (QuestType::Investigation, HeroClass::Support) => 10,
+                (QuestType::Investigation, HeroClass::Rogue) => 20,
+                (QuestType::Bounty, HeroClass::Rogue) => 10,
+                (QuestType::Escort, HeroClass::Rogue) => -10,
+                _ => 0,
+            };
+            assert_eq!(quest_type_class_modifier(quest_type, class), expected);
+        }
+    }
+}
+
+#[test]
+fn probability_of_quest_success_applies_quest_type_modifier() {
+    let config = HeroConfig::default();
+    let guild_config = GuildConfig::default();
+    let heroes = [
+        QuestHeroFixture::new(HeroClass::Warrior, 3),
+        QuestHeroFixture::new(HeroClass::Tank, 3),
+    ];
+    let party = quest_hero_items(&heroes);
+
+    // Only the Warrior benefits from a Dungeon, so the party average rises by 5
+    assert_eq!(
+        probability_of_quest_success(
+            3,
+            QuestType::Dungeon,
+            None,
+            None,
+            &party,
+            &config,
+            &guild_config
+        ),
+        Percent(75)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            3,
+            QuestType::Escort,
+            None,
+            None,
+            &party,
+            &config,
+            &guild_config
+        ),
+        Percent(75)
+    );
+    // Nobody in the party is suited to an Investigation, which is as neutral as a Bounty
+    assert_eq!(
+        probability_of_quest_success(
+            3,
+            QuestType::Investigation,
+            None,
+            None,
+            &party,
+            &config,
+            &guild_config
+        ),
+        Percent(70)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &party,
+            &config,
+            &guild_config
+        ),
+        Percent(70)
+    );
+}
+
+// Modifier to the party's success probability based on which classes are in it. Only solo heroes are affected.
+fn party_composition_modifiers(classes: &[&HeroClass]) -> Percent {
+    let warrior_solo_bonus = 10; // A Warrior questing alone gets to fight their own way
+    let mage_solo_penalty = -15; // A Mage questing alone has no party to amplify their area spells
+    let solo_penalty = -5; // Anyone else questing alone has nobody to cover for them
+    let area_effect_bonus = 5; // Each Mage's area spells boost every other hero in a party of 3 or more
+    let solo_modifier = match classes {
+        [HeroClass::Warrior] => Percent(warrior_solo_bonus),
+        [HeroClass::Mage] => Percent(mage_solo_penalty),
+        [_] => Percent(solo_penalty),
+        _ => Percent(0),
+    };
+    let party_size = classes.len() as i32;
+    let mage_count = classes
+        .iter()
+        .filter(|class| ***class == HeroClass::Mage)
+        .count() as i32;
+    // Averaged over the party, since the bonus only goes to the other heroes, and rounded to the nearest percent
+    let area_effect_modifier = if party_size >= 3 {
+        let total_bonus = area_effect_bonus * mage_count * (party_size - 1);
+        Percent((total_bonus as f32 / party_size as f32).round() as i32)
+    } else {
+        Percent(0)
+    };
+    solo_modifier + area_effect_modifier
+}
+
+// Heroes who know each other well work better together, and heroes who dislike each other get in the way.
+fn party_cohesion_modifier(heroes: &[(Entity, &Person)]) -> Percent {
+    let close_threshold = 5.0; // Average opinion above which the party works as a team
+    let hostile_threshold = -3.0; // Average opinion below which the party squabbles
+    let opinions: Vec<i32> = heroes
+        .iter()
+        .flat_map(|(hero, person)| {
+            heroes
+                .iter()
+                .filter(move |(other, _)| other != hero)
+                .map(move |(other, _)| person.relationships.get(other).copied().unwrap_or(0))
+        })
+        .collect();
+    if opinions.is_empty() {
+        return Percent(0);
+    }
+    let average = opinions.iter().sum::<i32>() as f32 / opinions.len() as f32;
+    if average > close_threshold {
+        Percent(10)
+    } else if average < hostile_threshold {
+        Percent(-15)
+    } else {
+        Percent(0)
+    }
+}
+
+// Spawn a party of three heroes where everyone holds the given opinion of everyone else.
+#[cfg(test)]
+fn spawn_cohesion_test_party(app: &mut App, opinion: i32) -> Vec<Entity> {
+    let heroes: Vec<Entity> = (0..3)
+        .map(|_| spawn_hero(app, HeroClass::Warrior, 3))
+        .collect();
+    for hero in heroes.iter() {
+        app.world_mut()
+            .get_mut::<Person>(*hero)
+            .unwrap()
+            .relationships = heroes
+            .iter()
+            .filter(|other| *other != hero)
+            .map(|other| (*other, opinion))
+            .collect();
+    }
+    heroes
+}
+
+#[cfg(test)]
+fn read_party_cohesion(app: &App, heroes: &[Entity]) -> Percent {
+    let members: Vec<(Entity, &Person)> = heroes
+        .iter()
+        .map(|hero| (*hero, app.world().get::<Person>(*hero).unwrap()))
+        .collect();
+    party_cohesion_modifier(&members)
+}
+
+#[test]
+fn party_cohesion_modifier_follows_average_opinion() {
+    let cohesion_with_opinion = |opinion: i32| {
+        let mut app = make_test_app();
+        let party = spawn_cohesion_test_party(&mut app, opinion);
+        read_party_cohesion(&app, &party)
+    };
+
+    assert_eq!(cohesion_with_opinion(6), Percent(10));
+    assert_eq!(cohesion_with_opinion(-4), Percent(-15));
+    // The thresholds themselves are neutral
+    assert_eq!(cohesion_with_opinion(5), Percent(0));
+    assert_eq!(cohesion_with_opinion(-3), Percent(0));
+    assert_eq!(cohesion_with_opinion(0), Percent(0));
+
+    // Opinions are averaged over every pair, and strangers count as neutral
+    let mut app = make_test_app();
+    let party = spawn_cohesion_test_party(&mut app, 10);
+    for hero in &party[..2] {
+        app.world_mut()
+            .get_mut::<Person>(*hero)
+            .unwrap()
+            .relationships
+            .clear();
+    }
+    // Only 2 of the 6 opinions are held, averaging 20 / 6
+    assert_eq!(read_party_cohesion(&app, &party), Percent(0));
+    // With a third opinion held, the average of 30 / 6 is still at the threshold
+    let (first, second) = (party[0], party[1]);
+    app.world_mut()
+        .get_mut::<Person>(first)
+        .unwrap()
+        .relationships
+        .insert(second, 10);
+    assert_eq!(read_party_cohesion(&app, &party), Percent(0));
+    // And a fourth tips it over, averaging 40 / 6
+    app.world_mut()
+        .get_mut::<Person>(second)
+        .unwrap()
+        .relationships
+        .insert(first, 10);
+    assert_eq!(read_party_cohesion(&app, &party), Percent(10));
+
+    // A solo hero has nobody to get along with
+    assert_eq!(read_party_cohesion(&app, &party[..1]), Percent(0));
+}
+
+#[test]
+fn probability_of_quest_success_applies_party_cohesion() {
+    let probability_with_opinion = |opinion: i32| {
+        let mut app = make_test_app();
+        let heroes = spawn_cohesion_test_party(&mut app, opinion);
+        let mut query = app.world_mut().query::<QuestHero>();
+        let party: Vec<QuestHeroItem> = heroes
+            .iter()
+            .map(|hero| query.get(app.world(), *hero).unwrap())
+            .collect();
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &party,
+            &HeroConfig::default(),
+            &GuildConfig::default(),
+        )
+    };
+
+    assert_eq!(probability_with_opinion(8), Percent(80));
+    assert_eq!(probability_with_opinion(0), Percent(70));
+    assert_eq!(probability_with_opinion(-8), Percent(55));
+}
+
+#[test]
+fn probability_of_quest_success_is_zero_without_heroes() {
+    assert_eq!(
+        probability_of_quest_success(
+            1,
+            QuestType::Bounty,
+            None,
+            None,
+            &[],
+            &HeroConfig::default(),
+            &GuildConfig::default()
+        ),
+        Percent(0)
+    );
+}
+
+#[test]
+fn probability_of_quest_success_follows_guild_config() {
+    let config = HeroConfig::default();
+    let heroes = [
+        QuestHeroFixture::new(HeroClass::Warrior, 3),
+        QuestHeroFixture::new(HeroClass::Warrior, 3),
+    ];
+    let party = quest_hero_items(&heroes);
+    let mut guild_config = GuildConfig::default();
+    assert_eq!(
+        probability_of_quest_success(
+            1,
+            QuestType::Bounty,
+            None,
+            None,
+            &party,
+            &config,
+            &guild_config
+        ),
+        Percent(110)
+    );
+
+    guild_config.baseline_effectiveness = 60;
+    guild_config.effectiveness_per_level = 10;
+    assert_eq!(
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &party,
+            &config,
+            &guild_config
+        ),
+        Percent(60)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            1,
+            QuestType::Bounty,
+            None,
+            None,
+            &party,
+            &config,
+            &guild_config
+        ),
+        Percent(80)
+    );
+}
+
+#[test]
+fn probability_of_quest_success_finds_expected_values() {
+    let config = HeroConfig::default();
+    let guild_config = GuildConfig::default();
+    let heroes_lvl_3 = [
+        QuestHeroFixture::new(HeroClass::Warrior, 3),
+        QuestHeroFixture {
+            person: Person {
+                personality: Personality::ResultOriented,
+                ..default()
+            },
+            ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+        },
+        QuestHeroFixture {
+            person: Person {
+                personality: Personality::Learner,
+                ..default()
+            },
+            ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+        },
+    ];
+    let heros_lvl_3 = quest_hero_items(&heroes_lvl_3);
+    assert_eq!(
+        probability_of_quest_success(
+            5,
+            QuestType::Bounty,
+            None,
+            None,
+            &heros_lvl_3,
+            &config,
+            &guild_config
+        ),
+        Percent(30)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            4,
+            QuestType::Bounty,
+            None,
+            None,
+            &heros_lvl_3,
+            &config,
+            &guild_config
+        ),
+        Percent(50)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &heros_lvl_3,
+            &config,
+            &guild_config
+        ),
+        Percent(70)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            2,
+            QuestType::Bounty,
+            None,
+            None,
+            &heros_lvl_3,
+            &config,
+            &guild_config
+        ),
+        Percent(90)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            1,
+            QuestType::Bounty,
+            None,
+            None,
+            &heros_lvl_3,
+            &config,
+            &guild_config
+        ),
+        Percent(110)
+    );
+
+    let heroes_avg_3 = [
+        QuestHeroFixture::new(HeroClass::Warrior, 3),
+        QuestHeroFixture {
+            person: Person {
+                personality: Personality::ResultOriented,
+                ..default()
+            },
+            ..QuestHeroFixture::new(HeroClass::Warrior, 2)
+        },
+        QuestHeroFixture {
+            person: Person {
+                personality: Personality::Learner,
+                ..default()
+            },
+            ..QuestHeroFixture::new(HeroClass::Warrior, 4)
+        },
+    ];
+    let heros_avg_3 = quest_hero_items(&heroes_avg_3);
+    assert_eq!(
+        probability_of_quest_success(
+            5,
+            QuestType::Bounty,
+            None,
+            None,
+            &heros_avg_3,
+            &config,
+            &guild_config
+        ),
+        Percent(30)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            4,
+            QuestType::Bounty,
+            None,
+            None,
+            &heros_avg_3,
+            &config,
+            &guild_config
+        ),
+        Percent(50)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &heros_avg_3,
+            &config,
+            &guild_config
+        ),
+        Percent(70)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            2,
+            QuestType::Bounty,
+            None,
+            None,
+            &heros_avg_3,
+            &config,
+            &guild_config
+        ),
+        Percent(90)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            1,
+            QuestType::Bounty,
+            None,
+            None,
+            &heros_avg_3,
+            &config,
+            &guild_config
+        ),
+        Percent(110)
+    );
+
+    let heroes_avg_fractional = [
+        QuestHeroFixture::new(HeroClass::Warrior, 3),
+        QuestHeroFixture {
+            person: Person {
+                personality: Personality::ResultOriented,
+                ..default()
+            },
+            ..QuestHeroFixture::new(HeroClass::Warrior, 2)
+        },
+        QuestHeroFixture {
+            person: Person {
+                personality: Personality::Learner,
+                ..default()
+            },
+            ..QuestHeroFixture::new(HeroClass::Warrior, 5)
+        },
+    ];
+    let heros_avg_fractional = quest_hero_items(&heroes_avg_fractional);
+    assert_eq!(
+        probability_of_quest_success(
+            4,
+            QuestType::Bounty,
+            None,
+            None,
+            &heros_avg_fractional,
+            &config,
+            &guild_config
+        ),
+        Percent(56)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &heros_avg_fractional,
+            &config,
+            &guild_config
+        ),
+        Percent(76)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            2,
+            QuestType::Bounty,
+            None,
+            None,
+            &heros_avg_fractional,
+            &config,
+            &guild_config
+        ),
+        Percent(96)
+    );
+}
+
+#[test]
+fn party_composition_modifiers_finds_expected_values() {
+    assert_eq!(
+        party_composition_modifiers(&[&HeroClass::Warrior]),
+        Percent(10)
+    );
+    assert_eq!(
+        party_composition_modifiers(&[&HeroClass::Tank]),
+        Percent(-5)
+    );
+    assert_eq!(
+        party_composition_modifiers(&[&HeroClass::Support]),
+        Percent(-5)
+    );
+    assert_eq!(
+        party_composition_modifiers(&[&HeroClass::Warrior, &HeroClass::Support]),
+        Percent(0)
+    );
+    assert_eq!(
+        party_composition_modifiers(&[&HeroClass::Warrior, &HeroClass::Tank, &HeroClass::Support]),
+        Percent(0)
+    );
+    // A Mage boosts everyone else in a big enough party
+    assert_eq!(
+        party_composition_modifiers(&[&HeroClass::Mage]),
+        Percent(-15)
+    );
+    assert_eq!(
+        party_composition_modifiers(&[&HeroClass::Mage, &HeroClass::Warrior]),
+        Percent(0)
+    );
+    assert_eq!(
+        party_composition_modifiers(&[&HeroClass::Mage, &HeroClass::Warrior, &HeroClass::Tank]),
+        Percent(3) // +5% for 2 of the 3 heroes, averaging 3.33%
+    );
+    assert_eq!(
+        party_composition_modifiers(&[
+            &HeroClass::Mage,
+            &HeroClass::Warrior,
+            &HeroClass::Tank,
+            &HeroClass::Support
+        ]),
+        Percent(4) // +5% for 3 of the 4 heroes, averaging 3.75%
+    );
+    assert_eq!(
+        party_composition_modifiers(&[
+            &HeroClass::Mage,
+            &HeroClass::Mage,
+            &HeroClass::Tank,
+            &HeroClass::Support
+        ]),
+        Percent(8) // +5% twice for 3 of the 4 heroes, averaging 7.5%, which rounds up
+    );
+    assert_eq!(
+        party_composition_modifiers(&[&HeroClass::Mage, &HeroClass::Mage, &HeroClass::Mage]),
+        Percent(10) // +5% twice for each of the 3 heroes
+    );
+    // The solo bonus is for the party, not for each Warrior in it
+    assert_eq!(
+        party_composition_modifiers(&[&HeroClass::Warrior, &HeroClass::Warrior]),
+        Percent(0)
+    );
+    assert_eq!(
+        party_composition_modifiers(&[
+            &HeroClass::Warrior,
+            &HeroClass::Warrior,
+            &HeroClass::Warrior
+        ]),
+        Percent(0)
+    );
+}
+
+#[test]
+fn probability_of_quest_success_applies_class_modifiers() {
+    let config = HeroConfig::default();
+    let guild_config = GuildConfig::default();
+
+    // A solo Warrior gets +10%
+    let solo_warrior_hero = [QuestHeroFixture::new(HeroClass::Warrior, 3)];
+    let solo_warrior = quest_hero_items(&solo_warrior_hero);
+    assert_eq!(
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &solo_warrior,
+            &config,
+            &guild_config
+        ),
+        Percent(80)
+    );
+
+    // A solo Tank gets -5%, and floors the probability at 40%, but doesn't raise it above that
+    let solo_tank_hero = [QuestHeroFixture::new(HeroClass::Tank, 3)];
+    let solo_tank = quest_hero_items(&solo_tank_hero);
+    assert_eq!(
+        probability_of_quest_success(
+            5,
+            QuestType::Bounty,
+            None,
+            None,
+            &solo_tank,
+            &config,
+            &guild_config
+        ),
+        Percent(40)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            4,
+            QuestType::Bounty,
+            None,
+            None,
+            &solo_tank,
+            &config,
+            &guild_config
+        ),
+        Percent(45)
+    );
+
+    // Parties of more than one hero aren't affected by their classes
+    let two_supports_heroes = [
+        QuestHeroFixture::new(HeroClass::Warrior, 3),
+        QuestHeroFixture::new(HeroClass::Support, 3),
+        QuestHeroFixture::new(HeroClass::Support, 3),
+    ];
+    let two_supports = quest_hero_items(&two_supports_heroes);
+    assert_eq!(
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &two_supports,
+            &config,
+            &guild_config
+        ),
+        Percent(70)
+    );
+
+    // The Tank floor still applies to a mixed party
+    let mixed_party_heroes = [
+        QuestHeroFixture::new(HeroClass::Warrior, 3),
+        QuestHeroFixture::new(HeroClass::Tank, 3),
+        QuestHeroFixture::new(HeroClass::Support, 3),
+    ];
+    let mixed_party = quest_hero_items(&mixed_party_heroes);
+    assert_eq!(
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &mixed_party,
+            &config,
+            &guild_config
+        ),
+        Percent(70)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            5,
+            QuestType::Bounty,
+            None,
+            None,
+            &mixed_party,
+            &config,
+            &guild_config
+        ),
+        Percent(40)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            6,
+            QuestType::Bounty,
+            None,
+            None,
+            &mixed_party,
+            &config,
+            &guild_config
+        ),
+        Percent(40)
+    );
+}
+
+#[test]
+fn probability_of_quest_success_brave_bonus_above_level() {
+    let config = HeroConfig::default();
+    let guild_config = GuildConfig::default();
+    let heroes = [
+        QuestHeroFixture {
+            person: Person {
+                personality: Personality::Brave,
+                ..default()
+            },
+            ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+        },
+        QuestHeroFixture {
+            person: Person {
+                personality: Personality::Brave,
+                ..default()
+            },
+            ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+        },
+    ];
+    let party = quest_hero_items(&heroes);
+
+    // Bonus only applies when the quest is strictly harder than the hero's level
+    assert_eq!(
+        probability_of_quest_success(
+            4,
+            QuestType::Bounty,
+            None,
+            None,
+            &party,
+            &config,
+            &guild_config
+        ),
+        Percent(60)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            5,
+            QuestType::Bounty,
+            None,
+            None,
+            &party,
+            &config,
+            &guild_config
+        ),
+        Percent(40)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &party,
+            &config,
+            &guild_config
+        ),
+        Percent(70)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            2,
+            QuestType::Bounty,
+            None,
+            None,
+            &party,
+            &config,
+            &guild_config
+        ),
+        Percent(90)
+    );
+}
+
+#[test]
+fn probability_of_quest_success_applies_elder_penalty() {
+    let config = HeroConfig::default();
+    let guild_config = GuildConfig::default();
+    let elders = |turns_alive| {
+        let elder = || QuestHeroFixture {
+            age: Age { turns_alive },
+            ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+        };
+        [elder(), elder()]
+    };
+
+    // Just past the threshold, there's no penalty yet
+    let new_elders = elders(599);
+    let party = quest_hero_items(&new_elders);
+    assert_eq!(
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &party,
+            &config,
+            &guild_config
+        ),
+        Percent(70)
+    );
+
+    // 200 turns past the threshold, the heroes fight like level 1 heroes
+    let old_elders = elders(700);
+    let party = quest_hero_items(&old_elders);
+    assert_eq!(
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &party,
+            &config,
+            &guild_config
+        ),
+        Percent(30)
+    );
+    assert_eq!(
+        probability_of_quest_success(
+            1,
+            QuestType::Bounty,
+            None,
+            None,
+            &party,
+            &config,
+            &guild_config
+        ),
+        Percent(70)
+    );
+
+    // The heroes' actual levels are untouched
+    assert_eq!(old_elders[0].level.level, 3);
+}
+
+// How many effective levels an elderly hero has lost to age.
+fn elder_level_penalty(age: &Age, hero_config: &HeroConfig) -> u32 {
+    age.turns_alive
+        .saturating_sub(hero_config.elder_threshold)
+        .checked_div(hero_config.turns_per_elder_penalty)
+        .unwrap_or(0)
+}
+
+fn complete_quest_assign_exp(
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    mut heroes_query: Query<&mut LevelState, With<Hero>>,
+    synergies: Res<ActiveSynergies>,
+    mut stats: ResMut<GuildStats>,
+) {
+    let exp_multiplier = (100 + synergies.exp_gain_bonus().0).max(0) as u32;
+    for event in ev_quest_complete.read() {
+        let exp_reward = event.exp_reward * exp_multiplier / 100;
+        for hero in &event.heroes {
+            if let Ok(mut level_state) = heroes_query.get_mut(*hero) {
+                level_state.exp += exp_reward;
+                stats.total_exp_awarded += exp_reward as u64;
+            }
+        }
+    }
+}
+
+#[test]
+fn complete_quest_assign_exp_increments_hero_exp() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_assign_exp);
+    // Add a hero with initial exp
+    let hero_entity = app
+        .world_mut()
+        .spawn(HeroBundle {
+            name: HeroName("Brenna the Bold".to_string()),
+            level: LevelState {
+                exp: 50,
+                ..default()
+            },
+            ..default()
+        })
+        .id();
+    // Add a QuestCompleteEvent with exp reward
+    send_quest_complete(&mut app, vec![hero_entity], true);
+    // Check that the hero's exp was incremented
+    let level_state = app.world().get::<LevelState>(hero_entity).unwrap();
+    assert_eq!(level_state.exp, 100);
+    assert_eq!(app.world().resource::<GuildStats>().total_exp_awarded, 50);
+}
+
+// Gold that doesn't fit in the vault is discarded afterwards, by apply_gold_cap.
+fn complete_quest_updates_guild(
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    morale: Res<GuildMorale>,
+    mut guild: ResMut<Guild>,
+    mut debt: ResMut<GuildDebt>,
+    mut stats: ResMut<GuildStats>,
+) {
+    for event in ev_quest_complete.read() {
+        stats.total_quests_completed += 1;
+        if event.is_successful {
+            let gold_reward = (event.gold_reward as f32 * morale.gold_multiplier()).round() as u64;
+            // Any debt is paid off before the guild keeps the reward
+            guild.gold = guild.gold.saturating_add(debt.pay_down(gold_reward));
+            stats.total_gold_earned = stats.total_gold_earned.saturating_add(gold_reward);
+        } else {
+            stats.total_quests_failed += 1;
+        }
+    }
+}
+
+// When a quest succeeds with an item reward, give the item to a hero of the matching class, or else to the lowest level hero in the party.
+fn complete_quest_distribute_items(
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    mut heroes_query: Query<(&HeroClass, &LevelState, &mut Inventory), With<Hero>>,
+    names_query: Query<&HeroName>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for event in ev_quest_complete.read() {
+        if !event.is_successful {
+            continue;
+        }
+        if let Some(item) = event.quest_description.item_reward {
+            let candidates: Vec<(Entity, HeroClass, u32)> = event
+                .heroes
+                .iter()
+                .filter_map(|hero| {
+                    heroes_query
+                        .get(*hero)
+                        .ok()
+                        .map(|(class, level, _)| (*hero, *class, level.level))
+                })
+                .collect();
+            let recipient = candidates
+                .iter()
+                .find(|(_, class, _)| *class == item.class)
+                .or_else(|| candidates.iter().min_by_key(|(_, _, level)| *level))
+                .map(|(hero, _, _)| *hero);
+            if let Some(hero) = recipient {
+                if let Ok((_, _, mut inventory)) = heroes_query.get_mut(hero) {
+                    inventory.items.push(item);
+                    ev_notify.write(NotificationEvent(format!(
+                        "Hero {} received an item: {} {}",
+                        hero_display_name(&names_query, hero),
+                        item.rarity,
+                        item.class
+                    )));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+fn send_item_test_quest_complete(
+    app: &mut App,
+    heroes: Vec<Entity>,
+    item_reward: Option<Item>,
+    is_successful: bool,
+) {
+    let mut event = quest_complete_event(heroes, is_successful);
+    event.quest_description.item_reward = item_reward;
+    app.world_mut()
+        .resource_mut::<Events<QuestCompleteEvent>>()
+        .send(event);
+}
+
+#[test]
+fn complete_quest_distribute_items_gives_item_to_matching_class() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_distribute_items);
+
+    let warrior = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    let support = spawn_hero(&mut app, HeroClass::Support, 3);
+    let item = Item {
+        class: HeroClass::Support,
+        rarity: ItemRarity::Common,
+    };
+
+    send_item_test_quest_complete(&mut app, vec![warrior, support], Some(item), true);
+    app.update();
+
+    // Check that the Support got the item, even though the Warrior is lower level
+    assert_eq!(
+        app.world().get::<Inventory>(support).unwrap().items,
+        vec![item]
+    );
+    assert!(
+        app.world()
+            .get::<Inventory>(warrior)
+            .unwrap()
+            .items
+            .is_empty()
+    );
+
+    // Check that the notification names the recipient and item
+    assert_eq!(
+        last_notification(&app),
+        format!("Hero {:?} received an item: Common Support", support)
+    );
+}
+
+#[test]
+fn complete_quest_distribute_items_falls_back_to_lowest_level_hero() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_distribute_items);
+
+    let high_level = spawn_hero(&mut app, HeroClass::Warrior, 4);
+    let low_level = spawn_hero(&mut app, HeroClass::Warrior, 2);
+    let item = Item {
+        class: HeroClass::Tank,
+        rarity: ItemRarity::Common,
+    };
+
+    send_item_test_quest_complete(&mut app, vec![high_level, low_level], Some(item), true);
+    app.update();
+
+    assert_eq!(
+        app.world().get::<Inventory>(low_level).unwrap().items,
+        vec![item]
+    );
+    assert!(
+        app.world()
+            .get::<Inventory>(high_level)
+            .unwrap()
+            .items
+            .is_empty()
+    );
+}
+
+#[test]
+fn complete_quest_distribute_items_skips_failed_quest() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_distribute_items);
+
+    let warrior = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    let item = Item {
+        class: HeroClass::Warrior,
+        rarity: ItemRarity::Common,
+    };
+
+    send_item_test_quest_complete(&mut app, vec![warrior], Some(item), false);
+    app.update();
+
+    assert!(
+        app.world()
+            .get::<Inventory>(warrior)
+            .unwrap()
+            .items
+            .is_empty()
+    );
+    let notification_events = app.world().resource::<Events<NotificationEvent>>();
+    assert!(notification_events.is_empty());
+}
+
+// Equip a hero with one of the items in their inventory. A hero can only use one item at a time.
+fn equip_item(
+    mut ev_equip_item: EventReader<EquipItemEvent>,
+    mut heroes_query: Query<&mut Inventory, With<Hero>>,
+    names_query: Query<&HeroName>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for event in ev_equip_item.read() {
+        let Ok(mut inventory) = heroes_query.get_mut(event.hero) else {
+            continue;
+        };
+        if event.item_index >= inventory.items.len() {
+            ev_notify.write(NotificationEvent(format!(
+                "{} has no item at index {} to equip",
+                hero_display_name(&names_query, event.hero),
+                event.item_index
+            )));
+            continue;
+        }
+        inventory.equipped = Some(event.item_index);
+    }
+}
+
+fn unequip_item(
+    mut ev_unequip_item: EventReader<UnequipItemEvent>,
+    mut heroes_query: Query<&mut Inventory, With<Hero>>,
+) {
+    for UnequipItemEvent(hero) in ev_unequip_item.read() {
+        if let Ok(mut inventory) = heroes_query.get_mut(*hero) {
+            inventory.equipped = None;
+        }
+    }
+}
+
+#[cfg(test)]
+fn make_equip_test_app() -> App {
+    let mut app = make_test_app();
+    app.add_systems(Update, (equip_item, unequip_item));
+    app
+}
+
+#[test]
+fn equip_item_equips_and_unequip_item_clears() {
+    let mut app = make_equip_test_app();
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.world_mut().get_mut::<Inventory>(hero).unwrap().items = vec![
+        Item {
+            class: HeroClass::Tank,
+            rarity: ItemRarity::Common,
+        },
+        Item {
+            class: HeroClass::Warrior,
+            rarity: ItemRarity::Common,
+        },
+    ];
+
+    app.world_mut()
+        .resource_mut::<Events<EquipItemEvent>>()
+        .send(EquipItemEvent {
+            hero,
+            item_index: 1,
+        });
+    app.update();
+    assert_eq!(
+        app.world().get::<Inventory>(hero).unwrap().equipped,
+        Some(1)
+    );
+
+    app.world_mut()
+        .resource_mut::<Events<UnequipItemEvent>>()
+        .send(UnequipItemEvent(hero));
+    app.update();
+    assert_eq!(app.world().get::<Inventory>(hero).unwrap().equipped, None);
+}
+
+#[test]
+fn equip_item_rejects_out_of_range_index() {
+    let mut app = make_equip_test_app();
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.world_mut().get_mut::<Inventory>(hero).unwrap().items = vec![Item {
+        class: HeroClass::Warrior,
+        rarity: ItemRarity::Common,
+    }];
+
+    app.world_mut()
+        .resource_mut::<Events<EquipItemEvent>>()
+        .send(EquipItemEvent {
+            hero,
+            item_index: 1,
+        });
+    app.update();
+
+    assert_eq!(app.world().get::<Inventory>(hero).unwrap().equipped, None);
+    assert_eq!(
+        last_notification(&app),
+        format!("{:?} has no item at index 1 to equip", hero)
+    );
+}
+
+#[test]
+fn probability_of_quest_success_applies_equipped_item_bonus() {
+    let config = HeroConfig::default();
+    let guild_config = GuildConfig::default();
+    let probability_with_inventory = |inventory: Inventory| {
+        let hero = || QuestHeroFixture {
+            inventory: Inventory {
+                items: inventory.items.clone(),
+                equipped: inventory.equipped,
+            },
+            ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+        };
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &quest_hero_items(&[hero(), hero()]),
+            &config,
+            &guild_config,
+        )
+    };
+    let sword = Item {
+        class: HeroClass::Warrior,
+        rarity: ItemRarity::Uncommon,
+    };
+    let shield = Item {
+        class: HeroClass::Tank,
+        rarity: ItemRarity::Uncommon,
+    };
+
+    // Half a level is worth 10%, but only while the item is equipped and made for the hero's class
+    assert_eq!(
+        probability_with_inventory(Inventory {
+            items: vec![sword],
+            equipped: Some(0),
+        }),
+        Percent(80)
+    );
+    assert_eq!(
+        probability_with_inventory(Inventory {
+            items: vec![sword],
+            equipped: None,
+        }),
+        Percent(70)
+    );
+    assert_eq!(
+        probability_with_inventory(Inventory {
+            items: vec![sword, shield],
+            equipped: Some(1),
+        }),
+        Percent(70)
+    );
+}
+
+#[test]
+fn probability_of_quest_success_scales_item_bonus_by_rarity() {
+    let config = HeroConfig::default();
+    let guild_config = GuildConfig::default();
+    let probability_with_rarity = |rarity: ItemRarity| {
+        let hero = || QuestHeroFixture {
+            inventory: Inventory {
+                items: vec![Item {
+                    class: HeroClass::Warrior,
+                    rarity,
+                }],
+                equipped: Some(0),
+            },
+            ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+        };
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &quest_hero_items(&[hero(), hero()]),
+            &config,
+            &guild_config,
+        )
+    };
+
+    assert_eq!(probability_with_rarity(ItemRarity::Common), Percent(75));
+    assert_eq!(probability_with_rarity(ItemRarity::Uncommon), Percent(80));
+    assert_eq!(probability_with_rarity(ItemRarity::Rare), Percent(85));
+    assert_eq!(probability_with_rarity(ItemRarity::Epic), Percent(90));
+    assert_eq!(probability_with_rarity(ItemRarity::Legendary), Percent(100));
+}
+
+#[test]
+fn random_item_rarity_favours_common_items() {
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for _ in 0..10_000 {
+        *counts
+            .entry(random_item_rarity(&mut rng).to_string())
+            .or_default() += 1;
+    }
+
+    // Weights are 70, 20, 8, 2 and 0.5, out of a total of 100.5
+    let count = |rarity: ItemRarity| counts.get(&rarity.to_string()).copied().unwrap_or(0);
+    assert!((6700..=7250).contains(&count(ItemRarity::Common)));
+    assert!((1800..=2200).contains(&count(ItemRarity::Uncommon)));
+    assert!((650..=950).contains(&count(ItemRarity::Rare)));
+    assert!((120..=280).contains(&count(ItemRarity::Epic)));
+    assert!((20..=90).contains(&count(ItemRarity::Legendary)));
+}
+
+#[test]
+fn item_rarity_display() {
+    assert_eq!(ItemRarity::Common.to_string(), "Common");
+    assert_eq!(ItemRarity::Uncommon.to_string(), "Uncommon");
+    assert_eq!(ItemRarity::Rare.to_string(), "Rare");
+    assert_eq!(ItemRarity::Epic.to_string(), "Epic");
+    assert_eq!(ItemRarity::Legendary.to_string(), "Legendary");
+}
+
+// What the market charges for an item of the given rarity, at today's prices.
+fn market_buy_price(rarity: ItemRarity, modifier: &MarketPriceModifier) -> u32 {
+    (rarity.base_price() as f32 * modifier.0).round() as u32
+}
+
+// Each turn, nudge market prices up or down by a small random amount. Averaging several rolls makes small moves
+// much more likely than large ones.
+fn fluctuate_market_prices(
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    mut modifier: ResMut<MarketPriceModifier>,
+    mut random_src: ResMut<RandomSource>,
+) {
+    let max_step = 0.05; // Furthest prices can move in a single turn
+    let rolls_per_step = 3;
+    let turn_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    let rng = &mut random_src.0;
+    for _ in 0..turn_delta {
+        let average_roll: f32 = (0..rolls_per_step)
+            .map(|_| rng.random_range(-1.0..=1.0))
+            .sum::<f32>()
+            / rolls_per_step as f32;
+        modifier.0 = (modifier.0 + average_roll * max_step).clamp(0.5, 2.0);
+    }
+}
+
+#[test]
+fn fluctuate_market_prices_stays_within_bounds() {
+    let mut app = make_test_app();
+    app.add_systems(Update, fluctuate_market_prices);
+
+    let mut modifiers = Vec::new();
+    for _ in 0..1000 {
+        send_turns(&mut app, 1);
+        modifiers.push(app.world().resource::<MarketPriceModifier>().0);
+    }
+
+    assert!(
+        modifiers
+            .iter()
+            .all(|modifier| (0.5..=2.0).contains(modifier))
+    );
+    // Prices actually move, rather than sitting at their starting point
+    assert!(modifiers.iter().any(|modifier| *modifier != 1.0));
+}
+
+#[test]
+fn market_buy_price_scales_base_price() {
+    let price = |modifier: f32| market_buy_price(ItemRarity::Rare, &MarketPriceModifier(modifier));
+    assert_eq!(price(1.0), 200);
+    assert_eq!(price(0.5), 100);
+    assert_eq!(price(2.0), 400);
+    assert_eq!(price(1.2345), 247);
+}
+
+// Each time the MarketRefreshTimer completes, replace the market's listings with a fresh batch of random items.
+fn refresh_market(
+    mut ev_turn_timer_complete: EventReader<TurnTimerCompleteEvent>,
+    timers_query: Query<(), With<MarketRefreshTimer>>,
+    config: Res<MarketConfig>,
+    modifier: Res<MarketPriceModifier>,
+    mut market: ResMut<Market>,
+    mut random_src: ResMut<RandomSource>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for TurnTimerCompleteEvent(entity) in ev_turn_timer_complete.read() {
+        if timers_query.contains(*entity) {
+            let rng = &mut random_src.0;
+            let listing_count = rng.random_range(config.min_listings..=config.max_listings);
+            market.listings = (0..listing_count)
+                .map(|_| {
+                    let class = *ALL_CLASSES.choose(rng).unwrap();
+                    let rarity = random_item_rarity(rng);
+                    MarketListing {
+                        item: Item { class, rarity },
+                        buy_price: market_buy_price(rarity, &modifier),
+                    }
+                })
+                .collect();
+            ev_notify.write(NotificationEvent(format!(
+                "The market has restocked with {} items",
+                listing_count
+            )));
+        }
+    }
+}
+
+#[test]
+fn refresh_market_replaces_listings() {
+    let mut app = make_test_app();
+    app.add_systems(Update, refresh_market);
+    let timer = app
+        .world_mut()
+        .spawn((MarketRefreshTimer, TurnTimer::repeating(10)))
+        .id();
+    app.world_mut().resource_mut::<Market>().listings = vec![MarketListing {
+        item: Item {
+            class: HeroClass::Tank,
+            rarity: ItemRarity::Legendary,
+        },
+        buy_price: 1,
+    }];
+
+    for _ in 0..10 {
+        app.world_mut()
+            .resource_mut::<Events<TurnTimerCompleteEvent>>()
+            .send(TurnTimerCompleteEvent(timer));
+        app.update();
+        let listings = &app.world().resource::<Market>().listings;
+        assert!((3..=5).contains(&listings.len()));
+        for listing in listings.iter() {
+            assert_eq!(listing.buy_price, listing.item.rarity.base_price());
+        }
+    }
+}
+
+#[test]
+fn refresh_market_prices_follow_modifier() {
+    let mut app = make_test_app();
+    app.add_systems(Update, (fluctuate_market_prices, refresh_market).chain());
+    let timer = app
+        .world_mut()
+        .spawn((MarketRefreshTimer, TurnTimer::repeating(10)))
+        .id();
+
+    let mut price_ratios = Vec::new();
+    for _ in 0..2 {
+        app.world_mut()
+            .resource_mut::<Events<TurnDeltaEvent>>()
+            .send(TurnDeltaEvent(10));
+        app.world_mut()
+            .resource_mut::<Events<TurnTimerCompleteEvent>>()
+            .send(TurnTimerCompleteEvent(timer));
+        app.update();
+        let modifier = app.world().resource::<MarketPriceModifier>();
+        let listings = &app.world().resource::<Market>().listings;
+        for listing in listings.iter() {
+            assert_eq!(
+                listing.buy_price,
+                market_buy_price(listing.item.rarity, modifier)
+            );
+        }
+        price_ratios
+            .push(listings[0].buy_price as f32 / listings[0].item.rarity.base_price() as f32);
+    }
+
+    // Prices moved between the two restocks
+    assert_ne!(price_ratios[0], price_ratios[1]);
+}
+
+// Buy an item from the market for a hero, if the guild can afford it. The item is no longer listed once bought.
+fn buy_item(
+    mut ev_buy_item: EventReader<BuyItemEvent>,
+    mut heroes_query: Query<&mut Inventory, With<Hero>>,
+    names_query: Query<&HeroName>,
+    mut market: ResMut<Market>,
+    mut guild: ResMut<Guild>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for event in ev_buy_item.read() {
+        let Ok(mut inventory) = heroes_query.get_mut(event.hero) else {
+            continue;
+        };
+        let Some(listing) = market.listings.get(event.listing_index).copied() else {
+            ev_notify.write(NotificationEvent(format!(
+                "The market has no listing at index {}",
+                event.listing_index
+            )));
+            continue;
+        };
+        let price = u64::from(listing.buy_price);
+        if guild.gold < price {
+            ev_notify.write(NotificationEvent(format!(
+                "Not enough gold to buy a {} {} item: costs {}, guild has {}",
+                listing.item.rarity, listing.item.class, price, guild.gold
+            )));
+            continue;
+        }
+        guild.gold -= price;
+        market.listings.remove(event.listing_index);
+        inventory.items.push(listing.item);
+        ev_notify.write(NotificationEvent(format!(
+            "Bought a {} {} item for {} for {} gold",
+            listing.item.rarity,
+            listing.item.class,
+            hero_display_name(&names_query, event.hero),
+            price
+        )));
+    }
+}
+
+// Sell one of a hero's items to the market, for a share of what the market would charge for it today.
+fn sell_item(
+    mut ev_sell_item: EventReader<SellItemEvent>,
+    mut heroes_query: Query<&mut Inventory, With<Hero>>,
+    names_query: Query<&HeroName>,
+    modifier: Res<MarketPriceModifier>,
+    mut guild: ResMut<Guild>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for event in ev_sell_item.read() {
+        let Ok(mut inventory) = heroes_query.get_mut(event.hero) else {
+            continue;
+        };
+        if event.item_index >= inventory.items.len() {
+            ev_notify.write(NotificationEvent(format!(
+                "{} has no item at index {} to sell",
+                hero_display_name(&names_query, event.hero),
+                event.item_index
+            )));
+            continue;
+        }
+        let item = inventory.items.remove(event.item_index);
+        // Keep the equipped index pointing at the same item, or clear it if that item was sold
+        inventory.equipped = match inventory.equipped {
+            Some(equipped) if equipped == event.item_index => None,
+            Some(equipped) if equipped > event.item_index => Some(equipped - 1),
+            equipped => equipped,
+        };
+        let sell_price = u64::from(market_buy_price(item.rarity, &modifier) * 6 / 10);
+        guild.gold = guild.gold.saturating_add(sell_price);
+        ev_notify.write(NotificationEvent(format!(
+            "{} sold a {} {} item for {} gold",
+            hero_display_name(&names_query, event.hero),
+            item.rarity,
+            item.class,
+            sell_price
+        )));
+    }
+}
+
+#[test]
+fn buy_item_adds_item_to_inventory_and_deducts_gold() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 250 });
+    app.insert_resource(Market {
+        listings: vec![
+            MarketListing {
+                item: Item {
+                    class: HeroClass::Warrior,
+                    rarity: ItemRarity::Rare,
+                },
+                buy_price: 200,
+            },
+            MarketListing {
+                item: Item {
+                    class: HeroClass::Tank,
+                    rarity: ItemRarity::Common,
+                },
+                buy_price: 50,
+            },
+        ],
+    });
+    app.add_systems(Update, (buy_item, sell_item));
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+
+    app.world_mut()
+        .resource_mut::<Events<BuyItemEvent>>()
+        .send(BuyItemEvent {
+            hero,
+            listing_index: 0,
+        });
+    app.update();
+
+    assert_eq!(app.world().resource::<Guild>().gold, 50);
+    assert_eq!(
+        app.world().get::<Inventory>(hero).unwrap().items,
+        vec![Item {
+            class: HeroClass::Warrior,
+            rarity: ItemRarity::Rare,
+        }]
+    );
+    // The bought item is no longer for sale
+    let listings = &app.world().resource::<Market>().listings;
+    assert_eq!(listings.len(), 1);
+    assert_eq!(listings[0].buy_price, 50);
+    assert_eq!(
+        last_notification(&app),
+        format!("Bought a Rare Warrior item for {:?} for 200 gold", hero)
+    );
+}
+
+#[test]
+fn buy_item_rejects_when_gold_is_insufficient() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 199 });
+    app.insert_resource(Market {
+        listings: vec![MarketListing {
+            item: Item {
+                class: HeroClass::Warrior,
+                rarity: ItemRarity::Rare,
+            },
+            buy_price: 200,
+        }],
+    });
+    app.add_systems(Update, (buy_item, sell_item));
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+
+    app.world_mut()
+        .resource_mut::<Events<BuyItemEvent>>()
+        .send(BuyItemEvent {
+            hero,
+            listing_index: 0,
+        });
+    app.update();
+
+    assert_eq!(app.world().resource::<Guild>().gold, 199);
+    assert!(app.world().get::<Inventory>(hero).unwrap().items.is_empty());
+    assert_eq!(app.world().resource::<Market>().listings.len(), 1);
+    assert_eq!(
+        last_notification(&app),
+        "Not enough gold to buy a Rare Warrior item: costs 200, guild has 199"
+    );
+}
+
+#[test]
+fn buy_item_rejects_out_of_range_listing() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 1000 });
+    app.add_systems(Update, (buy_item, sell_item));
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+
+    app.world_mut()
+        .resource_mut::<Events<BuyItemEvent>>()
+        .send(BuyItemEvent {
+            hero,
+            listing_index: 0,
+        });
+    app.update();
+
+    assert_eq!(app.world().resource::<Guild>().gold, 1000);
+    assert!(app.world().get::<Inventory>(hero).unwrap().items.is_empty());
+    assert_eq!(
+        last_notification(&app),
+        "The market has no listing at index 0"
+    );
+}
+
+#[test]
+fn sell_item_removes_item_and_adds_gold() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 0 });
+    app.add_systems(Update, (buy_item, sell_item));
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    let rare_sword = Item {
+        class: HeroClass::Warrior,
+        rarity: ItemRarity::Rare,
+    };
+    let common_sword = Item {
+        class: HeroClass::Warrior,
+        rarity: ItemRarity::Common,
+    };
+    {
+        let mut inventory = app.world_mut().get_mut::<Inventory>(hero).unwrap();
+        inventory.items = vec![rare_sword, common_sword];
+        inventory.equipped = Some(1);
+    }
+
+    app.world_mut()
+        .resource_mut::<Events<SellItemEvent>>()
+        .send(SellItemEvent {
+            hero,
+            item_index: 0,
+        });
+    app.update();
+
+    // Rare items cost 200, and sell for 60% of that
+    assert_eq!(app.world().resource::<Guild>().gold, 120);
+    let inventory = app.world().get::<Inventory>(hero).unwrap();
+    assert_eq!(inventory.items, vec![common_sword]);
+    // The hero is still using the same item
+    assert_eq!(inventory.equipped, Some(0));
+    assert_eq!(
+        last_notification(&app),
+        format!("{:?} sold a Rare Warrior item for 120 gold", hero)
+    );
+
+    // Selling the equipped item unequips it
+    app.world_mut()
+        .resource_mut::<Events<SellItemEvent>>()
+        .send(SellItemEvent {
+            hero,
+            item_index: 0,
+        });
+    app.update();
+    assert_eq!(app.world().resource::<Guild>().gold, 150);
+    let inventory = app.world().get::<Inventory>(hero).unwrap();
+    assert!(inventory.items.is_empty());
+    assert_eq!(inventory.equipped, None);
+}
+
+#[test]
+fn sell_item_rejects_out_of_range_index() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 0 });
+    app.add_systems(Update, (buy_item, sell_item));
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+
+    app.world_mut()
+        .resource_mut::<Events<SellItemEvent>>()
+        .send(SellItemEvent {
+            hero,
+            item_index: 0,
+        });
+    app.update();
+
+    assert_eq!(app.world().resource::<Guild>().gold, 0);
+    assert_eq!(
+        last_notification(&app),
+        format!("{:?} has no item at index 0 to sell", hero)
+    );
+}
+
+// Chance of a hero being injured on a failed quest. The further the success probability was below 50%, the more likely an injury is,
+// and a Tank in the party takes half the blows meant for everyone else.
+fn injury_probability(success_probability: Percent, protected_by_tank: bool) -> Percent {
+    let injury_threshold = Percent(50); // Quests failed at or above this success probability never injure heroes
+    let probability = Percent((injury_threshold - success_probability).0.max(0));
+    if protected_by_tank {
+        probability / 2
+    } else {
+        probability
+    }
+}
+
+#[test]
+fn injury_probability_is_halved_by_tank() {
+    assert_eq!(injury_probability(Percent(0), false), Percent(50));
+    assert_eq!(injury_probability(Percent(0), true), Percent(25));
+    assert_eq!(injury_probability(Percent(-50), false), Percent(100));
+    assert_eq!(injury_probability(Percent(-50), true), Percent(50));
+    assert_eq!(injury_probability(Percent(60), false), Percent(0));
+    assert_eq!(injury_probability(Percent(60), true), Percent(0));
+}
+
+// Roll for which heroes are injured on a failed quest. Heroes with no chance of injury don't roll at all.
+fn roll_injuries(
+    success_probability: Percent,
+    party: &[(Entity, &HeroClass)],
+    rng: &mut impl Rng,
+) -> Vec<Entity> {
+    let party_has_tank = party.iter().any(|(_, class)| **class == HeroClass::Tank);
+    party
+        .iter()
+        .filter(|(_, class)| {
+            let protected_by_tank = party_has_tank && **class != HeroClass::Tank;
+            let probability = injury_probability(success_probability, protected_by_tank);
+            probability.0 > 0 && probability.distribution().sample(rng)
+        })
+        .map(|(hero, _)| *hero)
+        .collect()
+}
+
+#[test]
+fn roll_injuries_tank_protects_other_heroes() {
+    // Each hero has a 50% injury chance, or 25% with a Tank in the party
+    let count_injuries = |with_tank: bool| {
+        let mut world = World::new();
+        let mut party: Vec<(Entity, &HeroClass)> = (0..200)
+            .map(|_| (world.spawn_empty().id(), &HeroClass::Warrior))
+            .collect();
+        if with_tank {
+            party.push((world.spawn_empty().id(), &HeroClass::Tank));
+        }
+        let injured = roll_injuries(Percent(0), &party, &mut ChaCha8Rng::seed_from_u64(42));
+        party[..200]
+            .iter()
+            .filter(|(hero, _)| injured.contains(hero))
+            .count()
+    };
+
+    let unprotected = count_injuries(false);
+    let protected = count_injuries(true);
+    assert!((80..=120).contains(&unprotected), "{}", unprotected);
+    assert!((30..=70).contains(&protected), "{}", protected);
+}
+
+#[test]
+fn roll_injuries_skips_likely_quests() {
+    let mut world = World::new();
+    let party: Vec<(Entity, &HeroClass)> = (0..20)
+        .map(|_| (world.spawn_empty().id(), &HeroClass::Warrior))
+        .collect();
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+
+    // A failed quest at or above 50% success probability can't injure anyone
+    assert!(roll_injuries(Percent(100), &party, &mut rng).is_empty());
+    assert!(roll_injuries(Percent(50), &party, &mut rng).is_empty());
+    // 50 points below the threshold means every hero is injured
+    assert_eq!(roll_injuries(Percent(-50), &party, &mut rng).len(), 20);
+}
+
+// Injure the heroes who were hurt on a failed quest, with harder quests taking longer to recover from.
+fn complete_quest_apply_injuries(
+    mut commands: Commands,
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    mut heroes_query: Query<Option<&mut Biography>, With<Hero>>,
+    names_query: Query<&HeroName>,
+    turn: Res<Turn>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let recovery_turns_per_difficulty = 5; // Harder quests cause injuries that take longer to heal
+    for event in ev_quest_complete.read() {
+        let recovery_turns =
+            event.quest_description.difficulty_level * recovery_turns_per_difficulty;
+        for hero in event.injured_heroes.iter() {
+            let Ok(biography) = heroes_query.get_mut(*hero) else {
+                continue;
+            };
+            if let Some(mut biography) = biography {
+                biography.record(
+                    turn.0,
+                    BiographyEventType::Injured,
+                    format!("Injured on {}", event.quest_description.name),
+                );
+            }
+            commands
+                .entity(*hero)
+                .insert((Injured, TurnTimer::new(recovery_turns)));
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} was injured and needs {} turns to recover",
+                hero_display_name(&names_query, *hero),
+                recovery_turns
+            )));
+        }
+    }
+}
+
+#[cfg(test)]
+fn send_injury_test_quest_complete(
+    app: &mut App,
+    heroes: Vec<Entity>,
+    injured_heroes: Vec<Entity>,
+) {
+    let mut event = quest_complete_event(heroes, false);
+    event.quest_description.difficulty_level = 2;
+    event.success_probability = Percent(0);
+    event.injured_heroes = injured_heroes;
+    app.world_mut()
+        .resource_mut::<Events<QuestCompleteEvent>>()
+        .send(event);
+}
+
+#[test]
+fn complete_quest_apply_injuries_injures_listed_heroes() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_apply_injuries);
+
+    let heroes: Vec<Entity> = (0..4).map(|_| app.world_mut().spawn(Hero).id()).collect();
+
+    send_injury_test_quest_complete(&mut app, heroes.clone(), vec![heroes[0], heroes[3]]);
+    app.update();
+
+    let injured: Vec<bool> = heroes
+        .iter()
+        .map(|hero| app.world().get::<Injured>(*hero).is_some())
+        .collect();
+    assert_eq!(injured, vec![true, false, false, true]);
+    // Recovery time scales with the quest's difficulty level
+    let timer = app.world().get::<TurnTimer>(heroes[0]).unwrap();
+    assert_eq!(timer.turns_remaining, 10);
+}
+
+#[test]
+fn complete_quest_apply_injuries_records_biography_entry() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_apply_injuries);
+    app.world_mut().resource_mut::<Turn>().0 = 12;
+    let hero = app.world_mut().spawn((Hero, Biography::default())).id();
+
+    send_injury_test_quest_complete(&mut app, vec![hero], vec![hero]);
+    app.update();
+
+    let entries = &app.world().get::<Biography>(hero).unwrap().entries;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].turn, 12);
+    assert_eq!(entries[0].event_type, BiographyEventType::Injured);
+    assert_eq!(entries[0].description, "Injured on The Dragon's Lair");
+}
+
+// After a quest, each Support in the party heals one injured party member, starting with whoever has the lowest morale.
+fn support_post_quest_healing(
+    mut commands: Commands,
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    classes_query: Query<&HeroClass, With<Hero>>,
+    morale_query: Query<&Morale, With<Hero>>,
+    names_query: Query<&HeroName>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for event in ev_quest_complete.read() {
+        let supports: Vec<Entity> = event
+            .heroes
+            .iter()
+            .filter(|hero| {
+                classes_query
+                    .get(**hero)
+                    .is_ok_and(|class| *class == HeroClass::Support)
+            })
+            .copied()
+            .collect();
+        let mut injured: Vec<(Entity, i32)> = event
+            .injured_heroes
+            .iter()
+            .filter_map(|hero| morale_query.get(*hero).ok().map(|morale| (*hero, morale.0)))
+            .collect();
+        // The sort is stable, so heroes with the same morale are healed in party order
+        injured.sort_by_key(|(_, morale)| *morale);
+        for (support, (patient, _)) in supports.iter().zip(injured) {
+            commands.entity(patient).remove::<(Injured, TurnTimer)>();
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} healed {}'s injuries",
+                hero_display_name(&names_query, *support),
+                hero_display_name(&names_query, patient)
+            )));
+        }
+    }
+}
+
+#[cfg(test)]
+fn spawn_healing_test_hero(app: &mut App, class: HeroClass, morale: i32) -> Entity {
+    let hero = spawn_hero(app, class, 1);
+    app.world_mut()
+        .entity_mut(hero)
+        .insert((Morale(morale), Injured, TurnTimer::new(10)));
+    hero
+}
+
+#[test]
+fn support_post_quest_healing_heals_one_hero_per_support() {
+    let mut app = make_test_app();
+    app.add_systems(Update, support_post_quest_healing);
+    let heroes = vec![
+        spawn_healing_test_hero(&mut app, HeroClass::Support, 50),
+        spawn_healing_test_hero(&mut app, HeroClass::Support, 60),
+        spawn_healing_test_hero(&mut app, HeroClass::Warrior, 30),
+        spawn_healing_test_hero(&mut app, HeroClass::Tank, 20),
+    ];
+
+    send_injury_test_quest_complete(&mut app, heroes.clone(), heroes.clone());
+    app.update();
+
+    // The two lowest morale heroes are healed, and the Supports are left to recover on their own
+    let injured: Vec<bool> = heroes
+        .iter()
+        .map(|hero| app.world().get::<Injured>(*hero).is_some())
+        .collect();
+    assert_eq!(injured, vec![true, true, false, false]);
+    assert!(app.world().get::<TurnTimer>(heroes[2]).is_none());
+    assert!(app.world().get::<TurnTimer>(heroes[0]).is_some());
+}
+
+#[test]
+fn support_post_quest_healing_heals_lowest_morale_hero() {
+    let mut app = make_test_app();
+    app.add_systems(Update, support_post_quest_healing);
+    let support = spawn_hero(&mut app, HeroClass::Support, 1);
+    let cheerful = spawn_healing_test_hero(&mut app, HeroClass::Warrior, 40);
+    let gloomy = spawn_healing_test_hero(&mut app, HeroClass::Warrior, 10);
+
+    send_injury_test_quest_complete(
+        &mut app,
+        vec![support, cheerful, gloomy],
+        vec![cheerful, gloomy],
+    );
+    app.update();
+
+    assert!(app.world().get::<Injured>(cheerful).is_some());
+    assert!(app.world().get::<Injured>(gloomy).is_none());
+    assert_eq!(
+        last_notification(&app),
+        format!("Hero {:?} healed {:?}'s injuries", support, gloomy)
+    );
+}
+
+#[test]
+fn support_post_quest_healing_needs_a_support() {
+    let mut app = make_test_app();
+    app.add_systems(Update, support_post_quest_healing);
+    let heroes = vec![
+        spawn_healing_test_hero(&mut app, HeroClass::Warrior, 10),
+        spawn_healing_test_hero(&mut app, HeroClass::Tank, 20),
+    ];
+
+    send_injury_test_quest_complete(&mut app, heroes.clone(), heroes.clone());
+    app.update();
+
+    for hero in heroes.iter() {
+        assert!(app.world().get::<Injured>(*hero).is_some());
+    }
+}
+
+#[test]
+fn support_post_quest_healing_heals_injuries_from_the_same_quest() {
+    let mut app = make_test_app();
+    app.add_systems(
+        Update,
+        (complete_quest_apply_injuries, support_post_quest_healing).chain(),
+    );
+    let support = spawn_hero(&mut app, HeroClass::Support, 1);
+    let warrior = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.world_mut().entity_mut(warrior).insert(Morale(10));
+
+    send_injury_test_quest_complete(&mut app, vec![support, warrior], vec![support, warrior]);
+    app.update();
+
+    // The Support's healing is applied in the same update as the injuries
+    assert!(app.world().get::<Injured>(support).is_some());
+    assert!(app.world().get::<Injured>(warrior).is_none());
+}
+
+// When a quest fails catastrophically, each hero may die. The further the success probability was below 20%, the more likely a death is.
+fn complete_quest_apply_deaths(
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    mut random_src: ResMut<RandomSource>,
+    heroes_query: Query<Entity, With<Hero>>,
+    mut ev_hero_death: EventWriter<HeroDeathEvent>,
+) {
+    let death_threshold = Percent(20); // Quests failed at or above this success probability never kill heroes
+    for event in ev_quest_complete.read() {
+        if event.is_successful || event.success_probability.0 >= death_threshold.0 {
+            continue;
+        }
+        let death_probability = death_threshold - event.success_probability;
+        for hero in event.heroes.iter() {
+            if heroes_query.contains(*hero)
+                && death_probability.distribution().sample(&mut random_src.0)
+            {
+                ev_hero_death.write(HeroDeathEvent(*hero));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+fn send_death_test_quest_complete(
+    app: &mut App,
+    heroes: Vec<Entity>,
+    success_probability: Percent,
+    is_successful: bool,
+) {
+    let mut event = quest_complete_event(heroes, is_successful);
+    event.quest_description.difficulty_level = 5;
+    event.success_probability = success_probability;
+    event.gold_reward = 0;
+    app.world_mut()
+        .resource_mut::<Events<QuestCompleteEvent>>()
+        .send(event);
+}
+
+#[cfg(test)]
+fn make_death_test_app() -> App {
+    let mut app = make_test_app();
+    app.add_systems(
+        Update,
+        (complete_quest_apply_deaths, handle_hero_death).chain(),
+    );
+    app
+}
+
+#[cfg(test)]
+fn spawn_death_test_heroes(app: &mut App, count: usize) -> Vec<Entity> {
+    (0..count)
+        .map(|_| {
+            app.world_mut()
+                .spawn((
+                    Hero,
+                    LevelState {
+                        level: 3,
+                        exp_to_next: 400,
+                        ..default()
+                    },
+                    HeroClass::Warrior,
+                    QuestCount::default(),
+                ))
+                .id()
+        })
+        .collect()
+}
+
+#[test]
+fn complete_quest_apply_deaths_kills_heroes_on_hopeless_quest() {
+    let mut app = make_death_test_app();
+    let heroes = spawn_death_test_heroes(&mut app, 3);
+
+    // 100 points below the threshold means every hero dies
+    send_death_test_quest_complete(&mut app, heroes.clone(), Percent(-80), false);
+    app.update();
+
+    for hero in heroes.iter() {
+        assert!(app.world().get_entity(*hero).is_err());
+    }
+    let fallen = app.world().resource::<FallenHeroes>();
+    assert_eq!(fallen.0.len(), 3);
+    assert_eq!(fallen.0[0].name, format!("{:?}", heroes[0]));
+    assert_eq!(fallen.0[0].level, 3);
+    assert_eq!(app.world().resource::<GuildStats>().total_heroes_lost, 3);
+}
+
+#[test]
+fn complete_quest_apply_deaths_kills_some_heroes_on_zero_percent_quest() {
+    let mut app = make_death_test_app();
+    // Each hero has a 20% chance to die, so the rolls decide who survives
+    install_mock_rng(&mut app, vec![false, true, false]);
+    let heroes = spawn_death_test_heroes(&mut app, 3);
+
+    send_death_test_quest_complete(&mut app, heroes.clone(), Percent(0), false);
+    app.update();
+
+    assert!(app.world().get_entity(heroes[0]).is_ok());
+    assert!(app.world().get_entity(heroes[1]).is_err());
+    assert!(app.world().get_entity(heroes[2]).is_ok());
+    let fallen = app.world().resource::<FallenHeroes>();
+    assert_eq!(fallen.0.len(), 1);
+    assert_eq!(fallen.0[0].name, format!("{:?}", heroes[1]));
+}
+
+#[test]
+fn complete_quest_apply_deaths_spares_heroes_on_likely_quest() {
+    let mut app = make_death_test_app();
+    // No outcomes, since quests at or above the threshold shouldn't roll for deaths at all
+    install_mock_rng(&mut app, vec![]);
+    let heroes = spawn_death_test_heroes(&mut app, 50);
+
+    send_death_test_quest_complete(&mut app, heroes.clone(), Percent(100), false);
+    send_death_test_quest_complete(&mut app, heroes.clone(), Percent(20), false);
+    app.update();
+
+    for hero in heroes.iter() {
+        assert!(app.world().get::<Hero>(*hero).is_some());
+    }
+    assert!(app.world().resource::<FallenHeroes>().0.is_empty());
+}
+
+// Record a dead hero in FallenHeroes, then despawn them. The guild is shown its memorial whenever a hero falls.
+fn handle_hero_death(
+    mut commands: Commands,
+    mut ev_hero_death: EventReader<HeroDeathEvent>,
+    heroes_query: Query<
+        (
+            &LevelState,
+            &HeroClass,
+            &QuestCount,
+            Option<&Biography>,
+            Option<&Companion>,
+            Option<&Bonded>,
+        ),
+        With<Hero>,
+    >,
+    mut morale_query: Query<&mut Morale, With<Hero>>,
+    names_query: Query<&HeroName>,
+    mut fallen_heroes: ResMut<FallenHeroes>,
+    mut stats: ResMut<GuildStats>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let fallen_count = fallen_heroes.0.len();
+    for HeroDeathEvent(hero) in ev_hero_death.read() {
+        if let Ok((level, class, quest_count, biography, companion, bonded)) =
+            heroes_query.get(*hero)
+        {
+            let name = hero_display_name(&names_query, *hero);
+            let memorial_entries = 5; // How much of the hero's biography is remembered
+            let entries = biography.map_or(&[][..], |biography| &biography.entries[..]);
+            fallen_heroes.0.push(FallenHeroRecord {
+                name: name.clone(),
+                level: level.level,
+                class: *class,
+                quest_count: *quest_count,
+                biography: Biography {
+                    entries: entries[entries.len().saturating_sub(memorial_entries)..].to_vec(),
+                },
+            });
+            // Make sure the hero isn't still attached to a quest, which would break the quest's Children
+            commands.entity(*hero).remove::<ChildOf>().despawn();
+            ev_notify.write(NotificationEvent(format!("Hero {} died on a quest", name)));
+            // The surviving companion may have died on the same quest, so don't insist on them still being around
+            let survivor = companion
+                .map(|companion| companion.0)
+                .filter(|survivor| heroes_query.contains(*survivor));
+            if let Some(survivor) = survivor {
+                commands
+                    .entity(survivor)
+                    .try_remove::<Companion>()
+                    .try_insert(MourningDebuff);
+                ev_notify.write(NotificationEvent(format!(
+                    "Hero {} mourns the loss of their companion {}",
+                    hero_display_name(&names_query, survivor),
+                    name
+                )));
+            }
+            let heartbroken = bonded
+                .map(|bonded| bonded.0)
+                .filter(|survivor| heroes_query.contains(*survivor));
+            if let Some(survivor) = heartbroken {
+                let heartbreak = 20; // Morale a hero never recovers after losing their partner
+                if let Ok(mut morale) = morale_query.get_mut(survivor) {
+                    morale.0 = (morale.0 - heartbreak).clamp(0, 100);
+                }
+                commands
+                    .entity(survivor)
+                    .try_remove::<(Bonded, BondedSince)>()
+                    .try_insert(PermanentMoralePenalty { amount: heartbreak });
+                ev_notify.write(NotificationEvent(format!(
+                    "Hero {} is heartbroken by the loss of {}",
+                    hero_display_name(&names_query, survivor),
+                    name
+                )));
+            }
+            stats.total_heroes_lost += 1;
+        }
+    }
+    if fallen_heroes.0.len() > fallen_count {
+        ev_notify.write(NotificationEvent(format!(
+            "In memory of the fallen:\n{}",
+            display_fallen_heroes(&fallen_heroes)
+        )));
+    }
+}
+
+#[test]
+fn handle_hero_death_leaves_companion_mourning() {
+    let mut app = make_test_app();
+    app.add_systems(Update, handle_hero_death);
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    let companion = spawn_hero(&mut app, HeroClass::Tank, 1);
+    let bystander = spawn_hero(&mut app, HeroClass::Support, 1);
+    app.world_mut()
+        .entity_mut(hero)
+        .insert(Companion(companion));
+    app.world_mut()
+        .entity_mut(companion)
+        .insert(Companion(hero));
+
+    app.world_mut()
+        .resource_mut::<Events<HeroDeathEvent>>()
+        .send(HeroDeathEvent(hero));
+    app.update();
+
+    assert!(app.world().get_entity(hero).is_err());
+    assert!(app.world().get::<MourningDebuff>(companion).is_some());
+    assert!(app.world().get::<Companion>(companion).is_none());
+    assert!(app.world().get::<MourningDebuff>(bystander).is_none());
+}
+
+#[test]
+fn handle_hero_death_records_fallen_hero() {
+    let mut app = make_test_app();
+    app.add_systems(Update, handle_hero_death);
+    let hero = spawn_hero(&mut app, HeroClass::Tank, 4);
+    let mut biography = Biography::default();
+    for turn in 1..=7 {
+        biography.record(
+            turn,
+            BiographyEventType::QuestCompleted,
+            format!("Completed quest {}", turn),
+        );
+    }
+    app.world_mut().entity_mut(hero).insert((
+        HeroName("Aldric the Steadfast".to_string()),
+        QuestCount {
+            completed: 7,
+            failed: 0,
+        },
+        biography,
+    ));
+
+    app.world_mut()
+        .resource_mut::<Events<HeroDeathEvent>>()
+        .send(HeroDeathEvent(hero));
+    app.update();
+
+    assert!(app.world().get_entity(hero).is_err());
+    let fallen = app.world().resource::<FallenHeroes>();
+    assert_eq!(fallen.0.len(), 1);
+    let record = &fallen.0[0];
+    assert_eq!(record.name, "Aldric the Steadfast");
+    assert_eq!(record.level, 4);
+    assert_eq!(record.class, HeroClass::Tank);
+    assert_eq!(record.quest_count.completed, 7);
+    // Only the last 5 entries are kept
+    let turns: Vec<u32> = record
+        .biography
+        .entries
+        .iter()
+        .map(|entry| entry.turn)
+        .collect();
+    assert_eq!(turns, vec![3, 4, 5, 6, 7]);
+    assert_eq!(
+        last_notification(&app),
+        format!(
+            "In memory of the fallen:\n{}",
+            display_fallen_heroes(fallen)
+        )
+    );
+}
+
+#[test]
+fn handle_hero_death_handles_companions_dying_together() {
+    let mut app = make_test_app();
+    app.add_systems(Update, handle_hero_death);
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    let companion = spawn_hero(&mut app, HeroClass::Tank, 1);
+    app.world_mut()
+        .entity_mut(hero)
+        .insert(Companion(companion));
+    app.world_mut()
+        .entity_mut(companion)
+        .insert(Companion(hero));
+
+    let mut events = app.world_mut().resource_mut::<Events<HeroDeathEvent>>();
+    events.send(HeroDeathEvent(hero));
+    events.send(HeroDeathEvent(companion));
+    app.update();
+
+    assert!(app.world().get_entity(hero).is_err());
+    assert!(app.world().get_entity(companion).is_err());
+    assert_eq!(app.world().resource::<FallenHeroes>().0.len(), 2);
+}
+
+#[test]
+fn handle_hero_death_penalizes_bonded_survivor_once() {
+    let mut app = make_test_app();
+    app.add_systems(Update, (handle_hero_death, recover_hero_morale));
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    let partner = spawn_hero(&mut app, HeroClass::Tank, 1);
+    app.world_mut().entity_mut(hero).insert(Bonded(partner));
+    app.world_mut().entity_mut(partner).insert(Bonded(hero));
+
+    app.world_mut()
+        .resource_mut::<Events<HeroDeathEvent>>()
+        .send(HeroDeathEvent(hero));
+    app.update();
+
+    assert_eq!(app.world().get::<Morale>(partner).unwrap().0, 30);
+    assert_eq!(
+        app.world()
+            .get::<PermanentMoralePenalty>(partner)
+            .unwrap()
+            .amount,
+        20
+    );
+    assert!(app.world().get::<Bonded>(partner).is_none());
+
+    // Morale settles at the lower level from then on, without the penalty being applied again
+    for _ in 0..10 {
+        send_turns(&mut app, 5);
+    }
+    assert_eq!(app.world().get::<Morale>(partner).unwrap().0, 30);
+}
+
+// Two heroes who each think the world of the other become a couple. Heroes only have one partner, and never
+// fall for their own family.
+fn check_romance(
+    mut commands: Commands,
+    heroes_query: Query<
+        (Entity, &Person),
+        (With<Hero>, Without<Bonded>, Without<HeroStatusAvailable>),
+    >,
+    names_query: Query<&HeroName>,
+    family_tree: Res<FamilyTree>,
+    turn: Res<Turn>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let romance_threshold = 8; // Both heroes must have at least this opinion of each other
+    let mut bonded: HashSet<Entity> = HashSet::new();
+    for (hero, person) in heroes_query.iter() {
+        for (other, opinion) in person.relationships.iter() {
+            if *opinion < romance_threshold || bonded.contains(&hero) || bonded.contains(other) {
+                continue;
+            }
+            let reciprocated = heroes_query.get(*other).is_ok_and(|(_, other_person)| {
+                other_person
+                    .relationships
+                    .get(&hero)
+                    .is_some_and(|opinion| *opinion >= romance_threshold)
+            });
+            if !reciprocated || is_family(&family_tree, hero, *other) {
+                continue;
+            }
+            bonded.insert(hero);
+            bonded.insert(*other);
+            let since = BondedSince { turn: turn.0 };
+            commands.entity(hero).insert((Bonded(*other), since));
+            let since = BondedSince { turn: turn.0 };
+            commands.entity(*other).insert((Bonded(hero), since));
+            ev_notify.write(NotificationEvent(format!(
+                "Heroes {} and {} have fallen for each other",
+                hero_display_name(&names_query, hero),
+                hero_display_name(&names_query, *other)
+            )));
+        }
+    }
+}
+
+#[test]
+fn check_romance_bonds_heroes_with_mutual_high_opinions() {
+    let mut app = make_test_app();
+    app.add_systems(Update, check_romance);
+    let spawn_romance_test_hero = |app: &mut App| {
+        app.world_mut()
+            .spawn((Hero, Person::default(), HeroStatusIdle))
+            .id()
+    };
+    let set_opinion = |app: &mut App, hero: Entity, other: Entity, opinion: i32| {
+        app.world_mut()
+            .get_mut::<Person>(hero)
+            .unwrap()
+            .relationships
+            .insert(other, opinion);
+    };
+    let romeo = spawn_romance_test_hero(&mut app);
+    let juliet = spawn_romance_test_hero(&mut app);
+    let admirer = spawn_romance_test_hero(&mut app);
+    let admired = spawn_romance_test_hero(&mut app);
+    set_opinion(&mut app, romeo, juliet, 8);
+    set_opinion(&mut app, juliet, romeo, 7);
+    // An unrequited admirer never bonds
+    set_opinion(&mut app, admirer, admired, 10);
+    set_opinion(&mut app, admired, admirer, 2);
+
+    app.update();
+    assert!(app.world().get::<Bonded>(romeo).is_none());
+    assert!(app.world().get::<Bonded>(juliet).is_none());
+
+    // Both directions have to reach the threshold of 8
+    set_opinion(&mut app, juliet, romeo, 8);
+    app.update();
+    assert_eq!(app.world().get::<Bonded>(romeo).unwrap().0, juliet);
+    assert_eq!(app.world().get::<Bonded>(juliet).unwrap().0, romeo);
+    assert!(app.world().get::<Bonded>(admirer).is_none());
+    assert!(app.world().get::<Bonded>(admired).is_none());
+}
+
+// A couple bonded for long enough raises a legacy hero, who joins the guild. Each couple only has one.
+fn check_legacy_spawn(
+    mut commands: Commands,
+    couples_query: Query<(Entity, &Bonded, &BondedSince), (With<Hero>, Without<LegacyParent>)>,
+    turn: Res<Turn>,
+    mut ev_legacy_spawn: EventWriter<LegacyHeroSpawnEvent>,
+) {
+    let turns_to_legacy = 200; // How long a couple must be bonded before raising a legacy hero
+    for (hero, partner, since) in couples_query.iter() {
+        // Both heroes of a couple match, so only the first of them sends the event
+        if hero > partner.0 || !couples_query.contains(partner.0) {
+            continue;
+        }
+        if turn.0.saturating_sub(since.turn) < turns_to_legacy {
+            continue;
+        }
+        commands.entity(hero).insert(LegacyParent);
+        commands.entity(partner.0).insert(LegacyParent);
+        ev_legacy_spawn.write(LegacyHeroSpawnEvent {
+            parent_a: hero,
+            parent_b: partner.0,
+        });
+    }
+}
+
+// A legacy hero starts at their parents' average level, with their first parent's class and a blend of both their personalities.
+fn spawn_legacy_hero(
+    mut commands: Commands,
+    mut ev_legacy_spawn: EventReader<LegacyHeroSpawnEvent>,
+    parents_query: Query<(&LevelState, &HeroClass, &Person), With<Hero>>,
+    names_query: Query<&HeroName>,
+    level_config: Res<LevelConfig>,
+    turn: Res<Turn>,
+    mut family_tree: ResMut<FamilyTree>,
+    mut random_src: ResMut<RandomSource>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for LegacyHeroSpawnEvent { parent_a, parent_b } in ev_legacy_spawn.read() {
+        let (Ok((level_a, class, person_a)), Ok((level_b, _, person_b))) =
+            (parents_query.get(*parent_a), parents_query.get(*parent_b))
+        else {
+            continue;
+        };
+        let level = (level_a.level + level_b.level) / 2;
+        let name = generate_hero_name(&mut random_src.0);
+        let parent_names = format!(
+            "{} and {}",
+            hero_display_name(&names_query, *parent_a),
+            hero_display_name(&names_query, *parent_b)
+        );
+        let child = commands
+            .spawn(HeroBundle {
+                name: HeroName(name.clone()),
+                level: LevelState {
+                    level,
+                    exp: 0,
+                    exp_to_next: exp_required_for_level(&level_config, level),
+                },
+                class: *class,
+                person: Person {
+                    personality: legacy_personality(&person_a.personality, &person_b.personality),
+                    ..default()
+                },
+                biography: Biography {
+                    entries: vec![BiographyEntry {
+                        turn: turn.0,
+                        event_type: BiographyEventType::LegacyBorn,
+                        description: format!("Born to {}", parent_names),
+                    }],
+                },
+                portrait: HeroPortrait::random_for(class, &mut random_src.0),
+                ..default()
+            })
+            .id();
+        for parent in [*parent_a, *parent_b] {
+            family_tree
+                .parent_to_children
+                .entry(parent)
+                .or_default()
+                .push(child);
+        }
+        family_tree
+            .child_to_parents
+            .insert(child, vec![*parent_a, *parent_b]);
+        ev_notify.write(NotificationEvent(format!(
+            "{}, child of {}, has joined the guild",
+            name, parent_names
+        )));
+    }
+}
+
+#[cfg(test)]
+fn spawn_legacy_test_couple(
+    app: &mut App,
+    levels: (u32, u32),
+    bonded_turn: u32,
+) -> (Entity, Entity) {
+    let parent_a = spawn_hero(app, HeroClass::Tank, levels.0);
+    let parent_b = spawn_hero(app, HeroClass::Support, levels.1);
+    app.world_mut()
+        .entity_mut(parent_a)
+        .insert((Bonded(parent_b), BondedSince { turn: bonded_turn }));
+    app.world_mut()
+        .entity_mut(parent_b)
+        .insert((Bonded(parent_a), BondedSince { turn: bonded_turn }));
+    (parent_a, parent_b)
+}
+
+#[test]
+fn check_legacy_spawn_fires_after_200_turns() {
+    let mut app = make_test_app();
+    app.add_systems(Update, check_legacy_spawn);
+    let (parent_a, parent_b) = spawn_legacy_test_couple(&mut app, (1, 1), 10);
+    let read_legacy_events = |app: &App| -> Vec<(Entity, Entity)> {
+        let events = app.world().resource::<Events<LegacyHeroSpawnEvent>>();
+        let mut reader = events.get_cursor();
+        reader
+            .read(events)
+            .map(|event| (event.parent_a, event.parent_b))
+            .collect()
+    };
+
+    app.world_mut().resource_mut::<Turn>().0 = 209;
+    app.update();
+    assert!(read_legacy_events(&app).is_empty());
+
+    app.world_mut().resource_mut::<Turn>().0 = 210;
+    app.update();
+    let events = read_legacy_events(&app);
+    assert_eq!(events.len(), 1);
+    let (event_a, event_b) = events[0];
+    assert!(
+        (event_a, event_b) == (parent_a, parent_b) || (event_a, event_b) == (parent_b, parent_a)
+    );
+
+    // A couple only has one legacy hero
+    app.world_mut().resource_mut::<Turn>().0 = 500;
+    app.update();
+    app.update();
+    assert!(read_legacy_events(&app).is_empty());
+}
+
+#[test]
+fn spawn_legacy_hero_averages_parents() {
+    let mut app = make_test_app();
+    app.add_systems(Update, spawn_legacy_hero);
+    app.world_mut().resource_mut::<Turn>().0 = 250;
+    let (parent_a, parent_b) = spawn_legacy_test_couple(&mut app, (3, 6), 0);
+
+    app.world_mut()
+        .resource_mut::<Events<LegacyHeroSpawnEvent>>()
+        .send(LegacyHeroSpawnEvent { parent_a, parent_b });
+    app.update();
+
+    let mut legacy_query = app
+        .world_mut()
+        .query::<(&LevelState, &HeroClass, &Person, &Biography)>();
+    let legacy_heroes: Vec<_> = legacy_query.iter(app.world()).collect();
+    assert_eq!(legacy_heroes.len(), 1);
+    let (level, class, person, biography) = legacy_heroes[0];
+    assert_eq!(level.level, 4);
+    assert_eq!(*class, HeroClass::Tank);
+    // Both parents are Friendly, so the child warms to party members twice as much, whatever the outcome
+    assert!(matches!(
+        person.personality,
+        Personality::Legacy {
+            on_success: 2,
+            on_failure: 2
+        }
+    ));
+    assert_eq!(biography.entries.len(), 1);
+    let entry = &biography.entries[0];
+    assert_eq!(entry.turn, 250);
+    assert_eq!(entry.event_type, BiographyEventType::LegacyBorn);
+    assert_eq!(
+        entry.description,
+        format!("Born to {:?} and {:?}", parent_a, parent_b)
+    );
+}
+
+#[test]
+fn spawn_legacy_hero_adds_child_to_family_tree() {
+    let mut app = make_test_app();
+    app.add_systems(Update, spawn_legacy_hero);
+    let (parent_a, parent_b) = spawn_legacy_test_couple(&mut app, (2, 2), 0);
+
+    app.world_mut()
+        .resource_mut::<Events<LegacyHeroSpawnEvent>>()
+        .send(LegacyHeroSpawnEvent { parent_a, parent_b });
+    app.update();
+
+    let child = app
+        .world_mut()
+        .query_filtered::<Entity, With<Biography>>()
+        .single(app.world())
+        .unwrap();
+    let family_tree = app.world().resource::<FamilyTree>();
+    assert_eq!(
+        family_tree.child_to_parents.get(&child),
+        Some(&vec![parent_a, parent_b])
+    );
+    assert_eq!(
+        family_tree.parent_to_children.get(&parent_a),
+        Some(&vec![child])
+    );
+    assert_eq!(
+        family_tree.parent_to_children.get(&parent_b),
+        Some(&vec![child])
+    );
+}
+
+// A hero's parents, then each parent's own ancestors, going back up to depth generations.
+fn ancestors(tree: &FamilyTree, hero: Entity, depth: u32) -> Vec<Entity> {
+    if depth == 0 {
+        return vec![];
+    }
+    let Some(parents) = tree.child_to_parents.get(&hero) else {
+        return vec![];
+    };
+    parents
+        .iter()
+        .flat_map(|parent| std::iter::once(*parent).chain(ancestors(tree, *parent, depth - 1)))
+        .collect()
+}
+
+#[test]
+fn ancestors_traverses_up_to_depth() {
+    let [grandparent_a, grandparent_b, parent_a, parent_b, child] =
+        [1, 2, 3, 4, 5].map(Entity::from_raw);
+    let tree = FamilyTree {
+        parent_to_children: HashMap::from([
+            (grandparent_a, vec![parent_a]),
+            (grandparent_b, vec![parent_a]),
+            (parent_a, vec![child]),
+            (parent_b, vec![child]),
+        ]),
+        child_to_parents: HashMap::from([
+            (parent_a, vec![grandparent_a, grandparent_b]),
+            (child, vec![parent_a, parent_b]),
+        ]),
+    };
+
+    assert!(ancestors(&tree, child, 0).is_empty());
+    assert_eq!(ancestors(&tree, child, 1), vec![parent_a, parent_b]);
+    assert_eq!(
+        ancestors(&tree, child, 2),
+        vec![parent_a, grandparent_a, grandparent_b, parent_b]
+    );
+    // Going further back than the tree goes finds nobody new
+    assert_eq!(ancestors(&tree, child, 5), ancestors(&tree, child, 2));
+    // Founding heroes have no ancestors
+    assert!(ancestors(&tree, grandparent_a, 3).is_empty());
+}
+
+// Whether one hero is the other's parent or grandparent, or the two share one.
+fn is_family(tree: &FamilyTree, hero_a: Entity, hero_b: Entity) -> bool {
+    let family_generations = 2; // How far back heroes count as family
+    let ancestors_a = ancestors(tree, hero_a, family_generations);
+    let ancestors_b = ancestors(tree, hero_b, family_generations);
+    ancestors_a.contains(&hero_b)
+        || ancestors_b.contains(&hero_a)
+        || ancestors_a
+            .iter()
+            .any(|ancestor| ancestors_b.contains(ancestor))
+}
+
+#[test]
+fn check_romance_skips_family() {
+    let mut app = make_test_app();
+    app.add_systems(Update, check_romance);
+    let [parent_a, parent_b, child_a, child_b] = [(); 4].map(|_| {
+        app.world_mut()
+            .spawn((Hero, Person::default(), HeroStatusIdle))
+            .id()
+    });
+    app.insert_resource(FamilyTree {
+        parent_to_children: HashMap::from([
+            (parent_a, vec![child_a, child_b]),
+            (parent_b, vec![child_a, child_b]),
+        ]),
+        child_to_parents: HashMap::from([
+            (child_a, vec![parent_a, parent_b]),
+            (child_b, vec![parent_a, parent_b]),
+        ]),
+    });
+    // Siblings, and a parent and child, who adore each other
+    for (hero, other) in [
+        (child_a, child_b),
+        (child_b, child_a),
+        (parent_a, child_a),
+        (child_a, parent_a),
+    ] {
+        app.world_mut()
+            .get_mut::<Person>(hero)
+            .unwrap()
+            .relationships
+            .insert(other, 10);
+    }
+
+    app.update();
+    for hero in [parent_a, parent_b, child_a, child_b] {
+        assert!(app.world().get::<Bonded>(hero).is_none());
+    }
+}
+
+// Bond two heroes as companions for life. Heroes can only have one companion.
+fn bond_companions(
+    mut commands: Commands,
+    mut ev_bond_companions: EventReader<BondCompanionsEvent>,
+    heroes_query: Query<Has<Companion>, (With<Hero>, Without<HeroStatusAvailable>)>,
+    names_query: Query<&HeroName>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for BondCompanionsEvent { hero_a, hero_b } in ev_bond_companions.read() {
+        let (Ok(a_bonded), Ok(b_bonded)) = (heroes_query.get(*hero_a), heroes_query.get(*hero_b))
+        else {
+            ev_notify.write(NotificationEvent(
+                "Only heroes in the guild can become companions".to_string(),
+            ));
+            continue;
+        };
+        if hero_a == hero_b || a_bonded || b_bonded {
+            ev_notify.write(NotificationEvent(format!(
+                "Heroes {} and {} can't become companions",
+                hero_display_name(&names_query, *hero_a),
+                hero_display_name(&names_query, *hero_b)
+            )));
+            continue;
+        }
+        commands.entity(*hero_a).insert(Companion(*hero_b));
+        commands.entity(*hero_b).insert(Companion(*hero_a));
+        ev_notify.write(NotificationEvent(format!(
+            "Heroes {} and {} are now companions",
+            hero_display_name(&names_query, *hero_a),
+            hero_display_name(&names_query, *hero_b)
+        )));
+    }
+}
+
+#[test]
+fn bond_companions_bonds_both_heroes() {
+    let mut app = make_test_app();
+    app.add_systems(Update, bond_companions);
+    let hero_a = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    let hero_b = spawn_hero(&mut app, HeroClass::Tank, 1);
+    let hero_c = spawn_hero(&mut app, HeroClass::Support, 1);
+
+    app.world_mut()
+        .resource_mut::<Events<BondCompanionsEvent>>()
+        .send(BondCompanionsEvent { hero_a, hero_b });
+    app.update();
+
+    assert_eq!(app.world().get::<Companion>(hero_a).unwrap().0, hero_b);
+    assert_eq!(app.world().get::<Companion>(hero_b).unwrap().0, hero_a);
+
+    // A hero who already has a companion can't take another
+    app.world_mut()
+        .resource_mut::<Events<BondCompanionsEvent>>()
+        .send(BondCompanionsEvent {
+            hero_a,
+            hero_b: hero_c,
+        });
+    app.update();
+
+    assert_eq!(app.world().get::<Companion>(hero_a).unwrap().0, hero_b);
+    assert!(app.world().get::<Companion>(hero_c).is_none());
+}
+
+// Make one hero the rival of another. The rivalry is one-sided unless the other hero reciprocates.
+fn create_rivalry(
+    mut commands: Commands,
+    mut ev_create_rivalry: EventReader<CreateRivalryEvent>,
+    heroes_query: Query<(), (With<Hero>, Without<HeroStatusAvailable>)>,
+    names_query: Query<&HeroName>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for CreateRivalryEvent { from, toward } in ev_create_rivalry.read() {
+        if from == toward || !heroes_query.contains(*from) || !heroes_query.contains(*toward) {
+            continue;
+        }
+        commands.entity(*from).insert(Rival(*toward));
+        ev_notify.write(NotificationEvent(format!(
+            "Hero {} has made a rival of {}",
+            hero_display_name(&names_query, *from),
+            hero_display_name(&names_query, *toward)
+        )));
+    }
+}
+
+// A rivalry ends once the hero has grown too fond of their rival.
+fn dissolve_rivalries(
+    mut commands: Commands,
+    rivals_query: Query<(Entity, &Rival, &Person), With<Hero>>,
+    names_query: Query<&HeroName>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let friendship_threshold = 7; // Opinions above this can't sustain a rivalry
+    for (hero, rival, person) in rivals_query.iter() {
+        let opinion = person.relationships.get(&rival.0).copied().unwrap_or(0);
+        if opinion > friendship_threshold {
+            commands.entity(hero).remove::<Rival>();
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} no longer sees {} as a rival",
+                hero_display_name(&names_query, hero),
+                hero_display_name(&names_query, rival.0)
+            )));
+        }
+    }
+}
+
+#[test]
+fn create_rivalry_is_one_sided() {
+    let mut app = make_test_app();
+    app.add_systems(Update, create_rivalry);
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    let rival = spawn_hero(&mut app, HeroClass::Warrior, 2);
+
+    app.world_mut()
+        .resource_mut::<Events<CreateRivalryEvent>>()
+        .send(CreateRivalryEvent {
+            from: hero,
+            toward: rival,
+        });
+    app.update();
+
+    assert_eq!(app.world().get::<Rival>(hero).unwrap().0, rival);
+    assert!(app.world().get::<Rival>(rival).is_none());
+}
+
+#[test]
+fn dissolve_rivalries_once_opinion_exceeds_7() {
+    let mut app = make_test_app();
+    app.add_systems(Update, dissolve_rivalries);
+    let rival = spawn_hero(&mut app, HeroClass::Warrior, 2);
+    let hero = app
+        .world_mut()
+        .spawn((
+            Hero,
+            Rival(rival),
+            Person {
+                relationships: HashMap::from([(rival, 7)]),
+                ..default()
+            },
+        ))
+        .id();
+
+    // A high opinion isn't enough on its own
+    app.update();
+    assert!(app.world().get::<Rival>(hero).is_some());
+
+    app.world_mut()
+        .get_mut::<Person>(hero)
+        .unwrap()
+        .relationships
+        .insert(rival, 8);
+    app.update();
+    assert!(app.world().get::<Rival>(hero).is_none());
+}
+
+#[test]
+fn probability_of_quest_success_applies_rival_bonus() {
+    let config = HeroConfig::default();
+    let guild_config = GuildConfig::default();
+    let hero = Entity::from_raw(1);
+    let rival = Entity::from_raw(2);
+    let heroes = [
+        QuestHeroFixture {
+            entity: hero,
+            rival: Some(Rival(rival)),
+            ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+        },
+        QuestHeroFixture {
+            entity: rival,
+            ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+        },
+    ];
+    let party = quest_hero_items(&heroes);
+
+    // Only the hero with the rivalry gets +10%, so the party's average goes up by 5%
+    assert_eq!(
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &party,
+            &config,
+            &guild_config
+        ),
+        Percent(75)
+    );
+}
+
+// When an injured hero's recovery timer completes, they are no longer injured.
+fn recover_from_injury(
+    mut commands: Commands,
+    mut ev_turn_timer_complete: EventReader<TurnTimerCompleteEvent>,
+    query: Query<Entity, (With<Hero>, With<Injured>)>,
+    names_query: Query<&HeroName>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for TurnTimerCompleteEvent(entity) in ev_turn_timer_complete.read() {
+        if query.contains(*entity) {
+            commands.entity(*entity).remove::<(Injured, TurnTimer)>();
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} recovered from their injury",
+                hero_display_name(&names_query, *entity)
+            )));
+        }
+    }
+}
+
+#[test]
+fn recover_from_injury_removes_injured() {
+    let mut app = make_test_app();
+    app.add_systems(Update, recover_from_injury);
+
+    let hero_entity = app
+        .world_mut()
+        .spawn((
+            Hero,
+            Injured,
+            TurnTimer {
+                initial_value: 5,
+                turns_remaining: 0,
+                timer_mode: TimerMode::Once,
+            },
+        ))
+        .id();
+
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(hero_entity));
+    app.update();
+
+    assert!(app.world().get::<Injured>(hero_entity).is_none());
+    assert!(app.world().get::<TurnTimer>(hero_entity).is_none());
+    assert!(app.world().get::<Hero>(hero_entity).is_some());
+}
+
+// When a chained quest succeeds, post its follow-up as a new quest, one level harder and with 20% better rewards.
+fn spawn_chain_follow_up(
+    mut commands: Commands,
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for event in ev_quest_complete.read() {
+        if !event.is_successful {
+            continue;
+        }
+        if let Some(follow_up) = &event.follow_up {
+            let description = QuestDescription {
+                difficulty_level: follow_up.difficulty_level + 1,
+                exp_reward: follow_up.exp_reward * 6 / 5,
+                gold_reward: follow_up.gold_reward * 6 / 5,
+                ..follow_up.clone()
+            };
+            ev_notify.write(NotificationEvent(format!(
+                "A follow-up {:?} quest is available: {}, level {}",
+                description.quest_type, description.name, description.difficulty_level
+            )));
+            commands.spawn(QuestBundle {
+                marker: Quest,
+                progress: TurnTimer::new(description.turns_to_expiry),
+                description,
+                status: QuestStatusAvailable,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+fn send_chain_test_quest_complete(app: &mut App, is_successful: bool) {
+    let mut event = quest_complete_event(vec![], is_successful);
+    event.follow_up = Some(
+        QuestDescriptionBuilder::new()
+            .name("The Dragon's Lair".to_string())
+            .difficulty(2)
+            .turns(6)
+            .exp(100)
+            .gold(200)
+            .expiry(12)
+            .build(),
+    );
+    app.world_mut()
+        .resource_mut::<Events<QuestCompleteEvent>>()
+        .send(event);
+}
+
+#[test]
+fn spawn_chain_follow_up_posts_harder_quest_on_success() {
+    let mut app = make_test_app();
+    app.add_systems(Update, spawn_chain_follow_up);
+
+    send_chain_test_quest_complete(&mut app, true);
+    app.update();
+
+    let mut quests_query = app
+        .world_mut()
+        .query_filtered::<(&QuestDescription, &TurnTimer), (With<Quest>, With<QuestStatusAvailable>)>();
+    let quests: Vec<(&QuestDescription, &TurnTimer)> = quests_query.iter(app.world()).collect();
+    assert_eq!(quests.len(), 1);
+    let (description, timer) = quests[0];
+    assert_eq!(description.difficulty_level, 3);
+    assert_eq!(description.exp_reward, 120);
+    assert_eq!(description.gold_reward, 240);
+    assert_eq!(description.turns_to_complete, 6);
+    assert_eq!(timer.turns_remaining, 12);
+}
+
+#[test]
+fn spawn_chain_follow_up_skips_failed_quest() {
+    let mut app = make_test_app();
+    app.add_systems(Update, spawn_chain_follow_up);
+
+    send_chain_test_quest_complete(&mut app, false);
+    app.update();
+
+    let quest_count = app
+        .world_mut()
+        .query_filtered::<(), With<Quest>>()
+        .iter(app.world())
+        .count();
+    assert_eq!(quest_count, 0);
+}
+
+// After a quest, every hero in the party needs to rest for a while, depending on how long the quest took.
+// Well-rested heroes are spared, but only the once.
+fn complete_quest_apply_fatigue(
+    mut commands: Commands,
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    heroes_query: Query<Has<HeroRested>, With<Hero>>,
+    config: Res<FatigueConfig>,
+) {
+    for event in ev_quest_complete.read() {
+        let rest_turns = rest_turns_after_quest(&config, event.quest_description.turns_to_complete);
+        for hero in event.heroes.iter() {
+            match heroes_query.get(*hero) {
+                Ok(true) => {
+                    commands.entity(*hero).remove::<HeroRested>();
+                }
+                Ok(false) => {
+                    commands
+                        .entity(*hero)
+                        .insert(Fatigued(TurnTimer::new(rest_turns)));
+                }
+                Err(_) => {}
+            }
+        }
+    }
+}
+
+fn rest_turns_after_quest(config: &FatigueConfig, turns_to_complete: u32) -> u32 {
+    let rest_turns = (turns_to_complete as f32 * config.rest_multiplier).ceil() as u32;
+    rest_turns.max(config.min_rest_turns)
+}
+
+#[test]
+fn rest_turns_after_quest_applies_multiplier_and_floor() {
+    let config = FatigueConfig {
+        rest_multiplier: 0.5,
+        min_rest_turns: 2,
+    };
+    assert_eq!(rest_turns_after_quest(&config, 10), 5);
+    assert_eq!(rest_turns_after_quest(&config, 7), 4); // Rounds up
+    assert_eq!(rest_turns_after_quest(&config, 3), 2); // Floor
+    assert_eq!(rest_turns_after_quest(&config, 0), 2); // Floor
+}
+
+#[test]
+fn complete_quest_apply_fatigue_fatigues_party() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_apply_fatigue);
+
+    let hero_entity = app.world_mut().spawn(Hero).id();
+    send_quest_complete(&mut app, vec![hero_entity], true);
+
+    let fatigued = app.world().get::<Fatigued>(hero_entity).unwrap();
+    assert_eq!(fatigued.0.turns_remaining, 3);
+}
+
+#[test]
+fn complete_quest_apply_fatigue_spares_rested_hero_once() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_apply_fatigue);
+    let hero = app
+        .world_mut()
+        .spawn((Hero, HeroRested(TurnTimer::new(10))))
+        .id();
+
+    send_quest_complete(&mut app, vec![hero], true);
+    assert!(app.world().get::<Fatigued>(hero).is_none());
+    assert!(app.world().get::<HeroRested>(hero).is_none());
+
+    send_quest_complete(&mut app, vec![hero], true);
+    assert!(app.world().get::<Fatigued>(hero).is_some());
+}
+
+// After a quest, every hero in the party goes on cooldown for half as long as the quest took.
+fn complete_quest_apply_cooldown(
+    mut commands: Commands,
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    heroes_query: Query<Entity, With<Hero>>,
+) {
+    for event in ev_quest_complete.read() {
+        let cooldown_turns = cooldown_turns_after_quest(event.quest_description.turns_to_complete);
+        for hero in event.heroes.iter() {
+            if heroes_query.contains(*hero) {
+                commands
+                    .entity(*hero)
+                    .insert(HeroCooldown(TurnTimer::new(cooldown_turns)));
+            }
+        }
+    }
+}
+
+fn cooldown_turns_after_quest(turns_to_complete: u32) -> u32 {
+    (turns_to_complete / 2).max(1)
+}
+
+#[test]
+fn cooldown_turns_after_quest_is_half_the_quest() {
+    assert_eq!(cooldown_turns_after_quest(10), 5);
+    assert_eq!(cooldown_turns_after_quest(5), 2); // Rounds down
+    assert_eq!(cooldown_turns_after_quest(1), 1); // Always at least one turn
+    assert_eq!(cooldown_turns_after_quest(0), 1);
+}
+
+#[test]
+fn complete_quest_apply_cooldown_keeps_hero_from_next_quest() {
+    let mut app = make_test_app();
+    app.add_systems(
+        Update,
+        (
+            complete_quest_apply_cooldown,
+            recover_from_cooldown,
+            start_quest,
+        )
+            .chain(),
+    );
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    // A 5 turn quest puts the hero on cooldown for 2 turns
+    send_injury_test_quest_complete(&mut app, vec![hero], vec![]);
+    app.update();
+    assert_eq!(
+        app.world()
+            .get::<HeroCooldown>(hero)
+            .unwrap()
+            .0
+            .turns_remaining,
+        2
+    );
+
+    let quest = spawn_quest(&mut app, 1, 5);
+    app.world_mut()
+        .resource_mut::<Events<StartQuestEvent>>()
+        .send(StartQuestEvent {
+            quest,
+            heroes: vec![hero],
+        });
+    app.update();
+    assert!(app.world().get::<ChildOf>(hero).is_none());
+    assert!(app.world().get::<QuestStatusAvailable>(quest).is_some());
+
+    // Once the cooldown is over, the hero can be sent out again
+    send_turns(&mut app, 2);
+    assert!(app.world().get::<HeroCooldown>(hero).is_none());
+    app.world_mut()
+        .resource_mut::<Events<StartQuestEvent>>()
+        .send(StartQuestEvent {
+            quest,
+            heroes: vec![hero],
+        });
+    app.update();
+    assert_eq!(app.world().get::<ChildOf>(hero).unwrap().parent(), quest);
+}
+
+// Count down each hero's cooldown, and let them be assigned to quests again once it's over.
+fn recover_from_cooldown(
+    mut commands: Commands,
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    mut query: Query<(Entity, &mut HeroCooldown), With<Hero>>,
+    names_query: Query<&HeroName>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let turn_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    if turn_delta == 0 {
+        return;
+    }
+    for (entity, mut cooldown) in query.iter_mut() {
+        cooldown.0.turns_remaining = cooldown.0.turns_remaining.saturating_sub(turn_delta);
+        if cooldown.0.turns_remaining == 0 {
+            commands.entity(entity).remove::<HeroCooldown>();
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} is ready to be assigned to a quest again",
+                hero_display_name(&names_query, entity)
+            )));
+        }
+    }
+}
+
+#[test]
+fn recover_from_cooldown_notifies_when_ready() {
+    let mut app = make_test_app();
+    app.add_systems(Update, recover_from_cooldown);
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.world_mut()
+        .entity_mut(hero)
+        .insert(HeroCooldown(TurnTimer::new(2)));
+
+    send_turns(&mut app, 1);
+    assert!(app.world().get::<HeroCooldown>(hero).is_some());
+
+    send_turns(&mut app, 1);
+    assert!(app.world().get::<HeroCooldown>(hero).is_none());
+    assert_eq!(
+        last_notification(&app),
+        format!("Hero {:?} is ready to be assigned to a quest again", hero)
+    );
+}
+
+// Heroes grow older as turns pass, and become elders once they pass the configured threshold.
+fn age_heroes(
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    mut query: Query<(Entity, &mut Age), With<Hero>>,
+    hero_config: Res<HeroConfig>,
+    names_query: Query<&HeroName>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let turn_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    if turn_delta == 0 {
+        return;
+    }
+    for (entity, mut age) in query.iter_mut() {
+        let was_elder = age.turns_alive > hero_config.elder_threshold;
+        age.turns_alive += turn_delta;
+        if !was_elder && age.turns_alive > hero_config.elder_threshold {
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} has become an elder",
+                hero_display_name(&names_query, entity)
+            )));
+        }
+    }
+}
+
+#[cfg(test)]
+fn make_age_test_app() -> App {
+    let mut app = make_test_app();
+    app.add_systems(Update, age_heroes);
+    app
+}
+
+#[test]
+fn age_heroes_accumulates_turns() {
+    let mut app = make_age_test_app();
+    let hero_entity = app.world_mut().spawn((Hero, Age::default())).id();
+
+    send_turns(&mut app, 3);
+    send_turns(&mut app, 4);
+
+    let age = app.world().get::<Age>(hero_entity).unwrap();
+    assert_eq!(age.turns_alive, 7);
+}
+
+#[test]
+fn age_heroes_notifies_once_when_hero_becomes_elder() {
+    let mut app = make_age_test_app();
+    let hero_entity = app.world_mut().spawn((Hero, Age { turns_alive: 499 })).id();
+
+    // Reaching the threshold exactly isn't enough, the hero must pass it
+    send_turns(&mut app, 1);
+    send_turns(&mut app, 1);
+    send_turns(&mut app, 1);
+
+    let notification_events = app.world().resource::<Events<NotificationEvent>>();
+    let mut reader = notification_events.get_cursor();
+    let notifications: Vec<&String> = reader
+        .read(notification_events)
+        .map(|notification| &notification.0)
+        .collect();
+    assert_eq!(
+        notifications,
+        vec![&format!("Hero {:?} has become an elder", hero_entity)]
+    );
+}
+
+// Keep count of each hero's successful and failed quests.
+fn complete_quest_update_hero_stats(
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    mut heroes_query: Query<(&mut QuestCount, Option<&mut Skills>), With<Hero>>,
+    mut ev_skill_level_up: EventWriter<SkillLevelUpEvent>,
+) {
+    let uses_per_level = 5; // Quests a skill must be used on to gain a level
+    for event in ev_quest_complete.read() {
+        let skill_type = event.quest_description.quest_type.skill();
+        for hero in event.heroes.iter() {
+            if let Ok((mut quest_count, skills)) = heroes_query.get_mut(*hero) {
+                if event.is_successful {
+                    quest_count.completed += 1;
+                } else {
+                    quest_count.failed += 1;
+                }
+                // Heroes practice a quest's skill whether they succeed or not
+                let Some(mut skills) = skills else {
+                    continue;
+                };
+                let Some(skill) = skills.get_mut(skill_type) else {
+                    continue;
+                };
+                skill.uses += 1;
+                if skill.uses >= uses_per_level {
+                    skill.level += 1;
+                    skill.uses = 0;
+                    ev_skill_level_up.write(SkillLevelUpEvent {
+                        hero: *hero,
+                        skill_type,
+                        new_level: skill.level,
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn complete_quest_update_hero_stats_counts_outcomes() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_update_hero_stats);
+
+    let hero = app.world_mut().spawn((Hero, QuestCount::default())).id();
+    let bystander = app.world_mut().spawn((Hero, QuestCount::default())).id();
+
+    send_quest_complete(&mut app, vec![hero], true);
+    send_quest_complete(&mut app, vec![hero], true);
+    send_quest_complete(&mut app, vec![hero], false);
+
+    let quest_count = app.world().get::<QuestCount>(hero).unwrap();
+    assert_eq!(quest_count.completed, 2);
+    assert_eq!(quest_count.failed, 1);
+    let quest_count = app.world().get::<QuestCount>(bystander).unwrap();
+    assert_eq!(quest_count.completed, 0);
+    assert_eq!(quest_count.failed, 0);
+}
+
+#[cfg(test)]
+fn skill_test_combat_skill(app: &App, hero: Entity) -> Skill {
+    app.world()
+        .get::<Skills>(hero)
+        .unwrap()
+        .0
+        .iter()
+        .find(|skill| skill.skill_type == SkillType::Combat)
+        .unwrap()
+        .clone()
+}
+
+#[test]
+fn complete_quest_update_hero_stats_levels_up_skills() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_update_hero_stats);
+    let hero = app
+        .world_mut()
+        .spawn((Hero, QuestCount::default(), Skills::default()))
+        .id();
+
+    // Bounties train Combat, whatever the outcome
+    for is_successful in [true, false, true, false] {
+        send_quest_complete(&mut app, vec![hero], is_successful);
+    }
+    assert_eq!(
+        skill_test_combat_skill(&app, hero),
+        Skill {
+            skill_type: SkillType::Combat,
+            level: 0,
+            uses: 4,
+        }
+    );
+    assert!(
+        app.world()
+            .resource::<Events<SkillLevelUpEvent>>()
+            .is_empty()
+    );
+    // Other skills aren't practiced
+    let skills = app.world().get::<Skills>(hero).unwrap();
+    assert!(
+        skills
+            .0
+            .iter()
+            .filter(|skill| skill.skill_type != SkillType::Combat)
+            .all(|skill| skill.uses == 0)
+    );
+
+    // The fifth use levels the skill up, and starts counting again
+    send_quest_complete(&mut app, vec![hero], true);
+    assert_eq!(
+        skill_test_combat_skill(&app, hero),
+        Skill {
+            skill_type: SkillType::Combat,
+            level: 1,
+            uses: 0,
+        }
+    );
+    let level_up_events = app.world().resource::<Events<SkillLevelUpEvent>>();
+    let mut reader = level_up_events.get_cursor();
+    let events: Vec<_> = reader
+        .read(level_up_events)
+        .map(|event| (event.hero, event.skill_type, event.new_level))
+        .collect();
+    assert_eq!(events, vec![(hero, SkillType::Combat, 1)]);
+}
+
+// Quests lift the spirits of heroes who succeed, and weigh on those who fail.
+fn complete_quest_update_morale(
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    mut heroes_query: Query<&mut Morale, With<Hero>>,
+    config: Res<MoraleConfig>,
+) {
+    for event in ev_quest_complete.read() {
+        let morale_change = if event.is_successful {
+            config.success_boost
+        } else {
+            -config.failure_penalty
+        };
+        for hero in event.heroes.iter() {
+            if let Ok(mut morale) = heroes_query.get_mut(*hero) {
+                morale.0 = (morale.0 + morale_change).clamp(0, 100);
+            }
+        }
+    }
+}
+
+// Each turn, every hero's morale drifts back toward 50.
+fn recover_hero_morale(
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    mut heroes_query: Query<
+        (
+            &mut Morale,
+            Option<&PermanentMoralePenalty>,
+            Has<HeroStatusResting>,
+        ),
+        With<Hero>,
+    >,
+    config: Res<MoraleConfig>,
+    tavern_config: Res<TavernConfig>,
+    synergies: Res<ActiveSynergies>,
+) {
+    let turn_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    if turn_delta == 0 {
+        return;
+    }
+    let recovery =
+        config.recovery_rate * turn_delta as i32 * (100 + synergies.morale_recovery_bonus().0)
+            / 100;
+    for (mut morale, penalty, is_resting) in heroes_query.iter_mut() {
+        let neutral_morale = Morale::default().0 - penalty.map_or(0, |penalty| penalty.amount);
+        let recovery = if is_resting {
+            recovery * tavern_config.rest_recovery_multiplier
+        } else {
+            recovery
+        };
+        morale.0 = neutral_morale + decay_toward_zero(morale.0 - neutral_morale, recovery);
+    }
+}
+
+#[test]
+fn complete_quest_update_morale_follows_outcome() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_update_morale);
+
+    let hero = app.world_mut().spawn((Hero, Morale(50))).id();
+    let miserable_hero = app.world_mut().spawn((Hero, Morale(5))).id();
+
+    send_quest_complete(&mut app, vec![hero, miserable_hero], true);
+    assert_eq!(app.world().get::<Morale>(hero).unwrap().0, 55);
+    assert_eq!(app.world().get::<Morale>(miserable_hero).unwrap().0, 10);
+
+    // Morale never drops below 0
+    send_quest_complete(&mut app, vec![hero, miserable_hero], false);
+    assert_eq!(app.world().get::<Morale>(hero).unwrap().0, 45);
+    assert_eq!(app.world().get::<Morale>(miserable_hero).unwrap().0, 0);
+}
+
+// A hero resents a successful quest where their higher level rival was along, since the rival gets the credit.
+fn complete_quest_apply_rival_resentment(
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    rivals_query: Query<(&Rival, &LevelState), With<Hero>>,
+    levels_query: Query<&LevelState, With<Hero>>,
+    mut morale_query: Query<&mut Morale, With<Hero>>,
+) {
+    let resentment = 3; // Morale lost by a hero outshone by their rival
+    for event in ev_quest_complete.read() {
+        if !event.is_successful {
+            continue;
+        }
+        for hero in event.heroes.iter() {
+            let Ok((rival, level)) = rivals_query.get(*hero) else {
+                continue;
+            };
+            let outshone = event.heroes.contains(&rival.0)
+                && levels_query
+                    .get(rival.0)
+                    .is_ok_and(|rival_level| rival_level.level > level.level);
+            if !outshone {
+                continue;
+            }
+            if let Ok(mut morale) = morale_query.get_mut(*hero) {
+                morale.0 = (morale.0 - resentment).clamp(0, 100);
+            }
+        }
+    }
+}
+
+#[test]
+fn complete_quest_apply_rival_resentment_needs_stronger_rival_and_success() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_apply_rival_resentment);
+    let spawn_rival_test_hero = |app: &mut App, level: u32| {
+        app.world_mut()
+            .spawn((Hero, LevelState { level, ..default() }, Morale(50)))
+            .id()
+    };
+    let stronger_rival = spawn_rival_test_hero(&mut app, 3);
+    let weaker_rival = spawn_rival_test_hero(&mut app, 1);
+    let resentful = spawn_rival_test_hero(&mut app, 2);
+    let confident = spawn_rival_test_hero(&mut app, 2);
+    app.world_mut()
+        .entity_mut(resentful)
+        .insert(Rival(stronger_rival));
+    app.world_mut()
+        .entity_mut(confident)
+        .insert(Rival(weaker_rival));
+
+    send_quest_complete(
+        &mut app,
+        vec![resentful, stronger_rival, confident, weaker_rival],
+        true,
+    );
+    assert_eq!(app.world().get::<Morale>(resentful).unwrap().0, 47);
+    assert_eq!(app.world().get::<Morale>(confident).unwrap().0, 50);
+    // The rival doesn't feel the same way
+    assert_eq!(app.world().get::<Morale>(stronger_rival).unwrap().0, 50);
+
+    // Failing together isn't the rival's glory
+    send_quest_complete(&mut app, vec![resentful, stronger_rival], false);
+    assert_eq!(app.world().get::<Morale>(resentful).unwrap().0, 47);
+
+    // Nor is a success the rival wasn't part of
+    send_quest_complete(&mut app, vec![resentful], true);
+    assert_eq!(app.world().get::<Morale>(resentful).unwrap().0, 47);
+}
+
+// Bonded heroes are happier for having quested together.
+fn complete_quest_apply_bonded_morale(
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    mut heroes_query: Query<(&Bonded, &mut Morale), With<Hero>>,
+) {
+    let together_boost = 5;
+    for event in ev_quest_complete.read() {
+        for hero in event.heroes.iter() {
+            let Ok((partner, mut morale)) = heroes_query.get_mut(*hero) else {
+                continue;
+            };
+            if event.heroes.contains(&partner.0) {
+                morale.0 = (morale.0 + together_boost).clamp(0, 100);
+            }
+        }
+    }
+}
+
+#[test]
+fn complete_quest_apply_bonded_morale_needs_partner_in_party() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_apply_bonded_morale);
+    let hero = app.world_mut().spawn((Hero, Morale(50))).id();
+    let partner = app.world_mut().spawn((Hero, Morale(50))).id();
+    let stranger = app.world_mut().spawn((Hero, Morale(50))).id();
+    app.world_mut().entity_mut(hero).insert(Bonded(partner));
+    app.world_mut().entity_mut(partner).insert(Bonded(hero));
+
+    send_quest_complete(&mut app, vec![hero, stranger], true);
+    assert_eq!(app.world().get::<Morale>(hero).unwrap().0, 50);
+
+    // Success or not, questing together lifts them both
+    send_quest_complete(&mut app, vec![hero, partner, stranger], false);
+    assert_eq!(app.world().get::<Morale>(hero).unwrap().0, 55);
+    assert_eq!(app.world().get::<Morale>(partner).unwrap().0, 55);
+    assert_eq!(app.world().get::<Morale>(stranger).unwrap().0, 50);
+}
+
+#[test]
+fn recover_hero_morale_converges_to_neutral() {
+    let mut app = make_test_app();
+    app.insert_resource(MoraleConfig {
+        recovery_rate: 2,
+        ..default()
+    });
+    app.add_systems(Update, recover_hero_morale);
+
+    let elated_hero = app.world_mut().spawn((Hero, Morale(95))).id();
+    let gloomy_hero = app.world_mut().spawn((Hero, Morale(10))).id();
+
+    send_turns(&mut app, 5);
+    assert_eq!(app.world().get::<Morale>(elated_hero).unwrap().0, 85);
+    assert_eq!(app.world().get::<Morale>(gloomy_hero).unwrap().0, 20);
+
+    // Given enough time, both settle at 50 without overshooting
+    for _ in 0..30 {
+        send_turns(&mut app, 1);
+    }
+    assert_eq!(app.world().get::<Morale>(elated_hero).unwrap().0, 50);
+    assert_eq!(app.world().get::<Morale>(gloomy_hero).unwrap().0, 50);
+}
+
+// Count down each fatigued hero's rest, and let them go on quests again once it's over.
+fn recover_from_fatigue(
+    mut commands: Commands,
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    mut query: Query<(Entity, &mut Fatigued), With<Hero>>,
+    names_query: Query<&HeroName>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let turn_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    if turn_delta == 0 {
+        return;
+    }
+    for (entity, mut fatigued) in query.iter_mut() {
+        fatigued.0.turns_remaining = fatigued.0.turns_remaining.saturating_sub(turn_delta);
+        if fatigued.0.turns_remaining == 0 {
+            commands.entity(entity).remove::<Fatigued>();
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} has rested and is ready for another quest",
+                hero_display_name(&names_query, entity)
+            )));
+        }
+    }
+}
+
+// Send an idle hero to rest at the Tavern, where their morale recovers faster. The guild needs an operational Tavern.
+fn rest_hero(
+    mut commands: Commands,
+    mut ev_rest_hero: EventReader<RestHeroEvent>,
+    idle_heroes_query: Query<
+        (),
+        (
+            With<Hero>,
+            With<HeroStatusIdle>,
+            Without<Injured>,
+            Without<HeroStatusAvailable>,
+        ),
+    >,
+    buildings_query: BuildingOperationalQuery,
+    names_query: Query<&HeroName>,
+    config: Res<TavernConfig>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let has_tavern = buildings_query
+        .iter()
+        .any(|building| building.building_type == BuildingType::Tavern);
+    for RestHeroEvent(hero) in ev_rest_hero.read() {
+        let name = hero_display_name(&names_query, *hero);
+        if !has_tavern {
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} can't rest without a Tavern",
+                name
+            )));
+            continue;
+        }
+        // Injured heroes are already recovering, on their own TurnTimer
+        if !idle_heroes_query.contains(*hero) {
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} is not idle, so can't rest",
+                name
+            )));
+            continue;
+        }
+        commands
+            .entity(*hero)
+            .remove::<HeroStatusIdle>()
+            .insert((HeroStatusResting, TurnTimer::new(config.rest_turns)));
+        ev_notify.write(NotificationEvent(format!(
+            "Hero {} is resting at the Tavern for {} turns",
+            name, config.rest_turns
+        )));
+    }
+}
+
+// When a resting hero's TurnTimer completes, they're idle again, and well-rested for a while.
+fn complete_rest(
+    mut commands: Commands,
+    mut ev_turn_timer_complete: EventReader<TurnTimerCompleteEvent>,
+    query: Query<(), (With<Hero>, With<HeroStatusResting>)>,
+    names_query: Query<&HeroName>,
+    config: Res<TavernConfig>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for TurnTimerCompleteEvent(entity) in ev_turn_timer_complete.read() {
+        if query.contains(*entity) {
+            commands
+                .entity(*entity)
+                .remove::<(HeroStatusResting, TurnTimer)>()
+                .insert((
+                    HeroStatusIdle,
+                    HeroRested(TurnTimer::new(config.rested_turns)),
+                ));
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} is back from the Tavern, well-rested",
+                hero_display_name(&names_query, *entity)
+            )));
+        }
+    }
+}
+
+// Heroes who don't go on a quest soon enough lose the benefit of their rest.
+fn wear_off_rested(
+    mut commands: Commands,
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    mut query: Query<(Entity, &mut HeroRested), With<Hero>>,
+) {
+    let turn_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    if turn_delta == 0 {
+        return;
+    }
+    for (entity, mut rested) in query.iter_mut() {
+        rested.0.turns_remaining = rested.0.turns_remaining.saturating_sub(turn_delta);
+        if rested.0.turns_remaining == 0 {
+            commands.entity(entity).remove::<HeroRested>();
+        }
+    }
+}
+
+#[test]
+fn rest_hero_rejects_without_tavern() {
+    let mut app = make_test_app();
+    app.add_systems(
+        Update,
+        (
+            rest_hero,
+            recover_hero_morale,
+            advance_turn_timer,
+            complete_rest,
+        )
+            .chain(),
+    );
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.world_mut().entity_mut(hero).insert(Morale(10));
+    app.world_mut()
+        .resource_mut::<Events<RestHeroEvent>>()
+        .send(RestHeroEvent(hero));
+    app.update();
+
+    assert!(app.world().get::<HeroStatusIdle>(hero).is_some());
+    assert!(app.world().get::<HeroStatusResting>(hero).is_none());
+    assert_eq!(
+        last_notification(&app),
+        format!("Hero {:?} can't rest without a Tavern", hero)
+    );
+}
+
+#[test]
+fn rest_hero_triples_morale_recovery() {
+    let mut app = make_test_app();
+    app.add_systems(
+        Update,
+        (
+            rest_hero,
+            recover_hero_morale,
+            advance_turn_timer,
+            complete_rest,
+        )
+            .chain(),
+    );
+    app.world_mut().spawn((
+        GuildBuilding {
+            building_type: BuildingType::Tavern,
+        },
+        Operational,
+    ));
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.world_mut().entity_mut(hero).insert(Morale(10));
+    app.world_mut()
+        .resource_mut::<Events<RestHeroEvent>>()
+        .send(RestHeroEvent(hero));
+    app.update();
+    assert!(app.world().get::<HeroStatusResting>(hero).is_some());
+    assert!(app.world().get::<HeroStatusIdle>(hero).is_none());
+
+    send_turns(&mut app, 1);
+
+    // 3 times the usual recovery for one turn
+    let recovery_rate = MoraleConfig::default().recovery_rate;
+    assert_eq!(
+        app.world().get::<Morale>(hero).unwrap().0,
+        10 + 3 * recovery_rate
+    );
+}
+
+#[test]
+fn complete_rest_leaves_hero_rested() {
+    let mut app = make_test_app();
+    app.add_systems(
+        Update,
+        (
+            rest_hero,
+            recover_hero_morale,
+            advance_turn_timer,
+            complete_rest,
+        )
+            .chain(),
+    );
+    app.world_mut().spawn((
+        GuildBuilding {
+            building_type: BuildingType::Tavern,
+        },
+        Operational,
+    ));
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.world_mut().entity_mut(hero).insert(Morale(10));
+    app.world_mut()
+        .resource_mut::<Events<RestHeroEvent>>()
+        .send(RestHeroEvent(hero));
+    app.update();
+
+    // Resting takes 5 turns
+    for _ in 0..4 {
+        send_turns(&mut app, 1);
+    }
+    assert!(app.world().get::<HeroStatusResting>(hero).is_some());
+
+    send_turns(&mut app, 1);
+    assert!(app.world().get::<HeroStatusResting>(hero).is_none());
+    assert!(app.world().get::<TurnTimer>(hero).is_none());
+    assert!(app.world().get::<HeroStatusIdle>(hero).is_some());
+    let rested = app.world().get::<HeroRested>(hero).unwrap();
+    assert_eq!(rested.0.turns_remaining, 10);
+}
+
+#[test]
+fn recover_from_fatigue_after_rest() {
+    let mut app = make_test_app();
+    app.add_systems(Update, recover_from_fatigue);
+
+    let hero_entity = app
+        .world_mut()
+        .spawn((Hero, Fatigued(TurnTimer::new(3))))
+        .id();
+
+    send_turns(&mut app, 2);
+    assert_eq!(
+        app.world()
+            .get::<Fatigued>(hero_entity)
+            .unwrap()
+            .0
+            .turns_remaining,
+        1
+    );
+
+    send_turns(&mut app, 1);
+    assert!(app.world().get::<Fatigued>(hero_entity).is_none());
+
+    assert_eq!(
+        last_notification(&app),
+        format!(
+            "Hero {:?} has rested and is ready for another quest",
+            hero_entity
+        )
+    );
+}
+
+fn complete_quest_send_notification(
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    names_query: Query<(Entity, &HeroName)>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for event in ev_quest_complete.read() {
+        let names: Vec<(Entity, &HeroName)> = event
+            .heroes
+            .iter()
+            .filter_map(|hero| names_query.get(*hero).ok())
+            .collect();
+        ev_notify.write(NotificationEvent(generate_quest_narrative(event, &names)));
+    }
+}
+
+// Tell the story of how a quest went, such as
+// "After a grueling battle, Aldric the Steadfast and Elena the Swift emerged victorious from The Dragon's Lair!"
+// Heroes without a name in names are called by their entity instead.
+fn generate_quest_narrative(event: &QuestCompleteEvent, names: &[(Entity, &HeroName)]) -> String {
+    let hero_names: Vec<String> = event
+        .heroes
+        .iter()
+        .map(|hero| {
+            names
+                .iter()
+                .find(|(entity, _)| entity == hero)
+                .map_or_else(|| format!("{:?}", hero), |(_, name)| name.0.clone())
+        })
+        .collect();
+    let party = match hero_names.split_last() {
+        None => "The party".to_string(),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+    };
+    let quest = &event.quest_description.name;
+    if event.is_successful {
+        format!(
+            "After a grueling battle, {} emerged victorious from {}!",
+            party, quest
+        )
+    } else {
+        let verb = if hero_names.len() == 1 { "was" } else { "were" };
+        format!(
+            "Despite their best efforts, {} {} driven back from {}.",
+            party, verb, quest
+        )
+    }
+}
+
+#[test]
+fn generate_quest_narrative_names_every_hero() {
+    let mut world = World::new();
+    let aldric = world.spawn_empty().id();
+    let elena = world.spawn_empty().id();
+    let brenna = world.spawn_empty().id();
+    let aldric_name = HeroName("Aldric the Steadfast".to_string());
+    let elena_name = HeroName("Elena the Swift".to_string());
+    let brenna_name = HeroName("Brenna the Bold".to_string());
+    let names = [
+        (aldric, &aldric_name),
+        (elena, &elena_name),
+        (brenna, &brenna_name),
+    ];
+
+    assert_eq!(
+        generate_quest_narrative(&quest_complete_event(vec![aldric, elena], true), &names),
+        "After a grueling battle, Aldric the Steadfast and Elena the Swift emerged victorious from The Dragon's Lair!"
+    );
+    assert_eq!(
+        generate_quest_narrative(
+            &quest_complete_event(vec![aldric, elena, brenna], true),
+            &names
+        ),
+        "After a grueling battle, Aldric the Steadfast, Elena the Swift and Brenna the Bold emerged victorious from The Dragon's Lair!"
+    );
+    // Heroes without a name are still mentioned
+    let nameless = world.spawn_empty().id();
+    assert_eq!(
+        generate_quest_narrative(&quest_complete_event(vec![nameless], true), &names),
+        format!(
+            "After a grueling battle, {:?} emerged victorious from The Dragon's Lair!",
+            nameless
+        )
+    );
+}
+
+#[test]
+fn generate_quest_narrative_varies_with_outcome() {
+    let mut world = World::new();
+    let aldric = world.spawn_empty().id();
+    let elena = world.spawn_empty().id();
+    let aldric_name = HeroName("Aldric the Steadfast".to_string());
+    let elena_name = HeroName("Elena the Swift".to_string());
+    let names = [(aldric, &aldric_name), (elena, &elena_name)];
+
+    assert_eq!(
+        generate_quest_narrative(&quest_complete_event(vec![aldric], false), &names),
+        "Despite their best efforts, Aldric the Steadfast was driven back from The Dragon's Lair."
+    );
+    assert_eq!(
+        generate_quest_narrative(&quest_complete_event(vec![aldric, elena], false), &names),
+        "Despite their best efforts, Aldric the Steadfast and Elena the Swift were driven back from The Dragon's Lair."
+    );
+}
+
+#[test]
+fn complete_quest_updates_guild_caps_gold_at_vault_size() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 950 });
+    app.add_systems(
+        Update,
+        (complete_quest_updates_guild, apply_gold_cap).chain(),
+    );
+    send_quest_complete(&mut app, vec![], true);
+
+    assert_eq!(app.world().resource::<Guild>().gold, 1000);
+    // The gold was still earned, even if the vault couldn't hold it
+    assert_eq!(app.world().resource::<GuildStats>().total_gold_earned, 100);
+    assert_eq!(last_notification(&app), "Vault is full — 50 gold lost!");
+}
+
+// The most gold the guild's vault can hold, raised by each operational Treasury.
+fn guild_gold_cap(config: &GuildConfig, treasury_count: u64) -> u64 {
+    config
+        .base_gold_cap
+        .saturating_add(config.gold_cap_per_treasury.saturating_mul(treasury_count))
+}
+
+// Discard any gold that doesn't fit in the guild's vault. Runs after every system that changes the guild's gold.
+// Gold already held when the vault shrinks, such as when a Treasury is lost, is kept, but no more can be added.
+fn apply_gold_cap(
+    mut gold_held: Local<Option<u64>>,
+    mut guild: ResMut<Guild>,
+    config: Res<GuildConfig>,
+    buildings_query: Query<&GuildBuilding, With<Operational>>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let treasury_count = buildings_query
+        .iter()
+        .filter(|building| building.building_type == BuildingType::Treasury)
+        .count();
+    let gold_cap = guild_gold_cap(&config, treasury_count as u64).max(gold_held.unwrap_or(0));
+    if guild.gold > gold_cap {
+        let gold_lost = guild.gold - gold_cap;
+        guild.gold = gold_cap;
+        ev_notify.write(NotificationEvent(format!(
+            "Vault is full — {} gold lost!",
+            gold_lost
+        )));
+    }
+    *gold_held = Some(guild.gold);
+}
+
+#[test]
+fn apply_gold_cap_rises_with_each_treasury() {
+    let mut app = make_test_app();
+    app.add_systems(Update, apply_gold_cap);
+    let run_with_gold = |app: &mut App, gold: u64| {
+        app.insert_resource(Guild { gold });
+        app.update();
+        app.world().resource::<Guild>().gold
+    };
+
+    assert_eq!(run_with_gold(&mut app, 20_000), 1000);
+    // A Treasury still under construction doesn't count
+    app.world_mut().spawn(GuildBuilding {
+        building_type: BuildingType::Treasury,
+    });
+    assert_eq!(run_with_gold(&mut app, 20_000), 1000);
+    app.world_mut().spawn((
+        GuildBuilding {
+            building_type: BuildingType::Treasury,
+        },
+        Operational,
+    ));
+    assert_eq!(run_with_gold(&mut app, 20_000), 6000);
+    app.world_mut().spawn((
+        GuildBuilding {
+            building_type: BuildingType::Treasury,
+        },
+        Operational,
+    ));
+    assert_eq!(run_with_gold(&mut app, 20_000), 11_000);
+    // Other buildings don't enlarge the vault
+    app.world_mut().spawn((
+        GuildBuilding {
+            building_type: BuildingType::Barracks,
+        },
+        Operational,
+    ));
+    assert_eq!(run_with_gold(&mut app, 20_000), 11_000);
+}
+
+#[test]
+fn apply_gold_cap_keeps_gold_held_when_the_vault_shrinks() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 6000 });
+    app.add_systems(Update, apply_gold_cap);
+    let treasury = app
+        .world_mut()
+        .spawn((
+            GuildBuilding {
+                building_type: BuildingType::Treasury,
+            },
+            Operational,
+        ))
+        .id();
+    app.update();
+
+    // Losing the Treasury doesn't take the gold with it
+    app.world_mut().entity_mut(treasury).despawn();
+    app.update();
+    assert_eq!(app.world().resource::<Guild>().gold, 6000);
+    assert!(
+        app.world()
+            .resource::<Events<NotificationEvent>>()
+            .is_empty()
+    );
+
+    // But the overfull vault can't take any more
+    app.world_mut().resource_mut::<Guild>().gold += 100;
+    app.update();
+    assert_eq!(app.world().resource::<Guild>().gold, 6000);
+    assert_eq!(last_notification(&app), "Vault is full — 100 gold lost!");
+
+    // Once spent below the cap, the vault holds no more than the cap
+    app.world_mut().resource_mut::<Guild>().gold = 500;
+    app.update();
+    app.world_mut().resource_mut::<Guild>().gold = 2000;
+    app.update();
+    assert_eq!(app.world().resource::<Guild>().gold, 1000);
+}
+
+#[test]
+fn apply_gold_cap_leaves_gold_within_cap() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 1000 });
+    app.add_systems(Update, apply_gold_cap);
+    app.update();
+
+    assert_eq!(app.world().resource::<Guild>().gold, 1000);
+    assert!(
+        app.world()
+            .resource::<Events<NotificationEvent>>()
+            .is_empty()
+    );
+}
+
+#[test]
+fn complete_quest_updates_guild_gold_only_on_success() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_updates_guild);
+    // Add a QuestCompleteEvent with gold reward
+    send_quest_complete(&mut app, vec![], true);
+    // Check that the guild's gold was incremented
+    let guild = app.world().resource::<Guild>();
+    assert_eq!(guild.gold, 100);
+
+    // Add a QuestCompleteEvent which failed
+    let mut failed_quest = quest_complete_event(vec![], false);
+    failed_quest.gold_reward = 50;
+    app.world_mut()
+        .resource_mut::<Events<QuestCompleteEvent>>()
+        .send(failed_quest);
+    // Run the system again
+    app.update();
+    // Check that the guild's gold was not incremented
+    let guild = app.world().resource::<Guild>();
+    assert_eq!(guild.gold, 100); // Still 100, since the quest failed
+
+    // Check that both quests were counted, but only the successful one's gold
+    let stats = app.world().resource::<GuildStats>();
+    assert_eq!(stats.total_quests_completed, 2);
+    assert_eq!(stats.total_quests_failed, 1);
+    assert_eq!(stats.total_gold_earned, 100);
+}
+
+#[test]
+fn complete_quest_updates_guild_pays_down_debt_first() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_updates_guild);
+    app.insert_resource(GuildDebt {
+        amount: 150,
+        turns_in_debt: 3,
+    });
+
+    // The whole reward goes to the debt
+    send_quest_complete(&mut app, vec![], true);
+    assert_eq!(app.world().resource::<Guild>().gold, 0);
+    assert_eq!(app.world().resource::<GuildDebt>().amount, 50);
+    assert_eq!(app.world().resource::<GuildDebt>().turns_in_debt, 3);
+
+    // Once the debt is paid off, the rest of the reward is kept
+    send_quest_complete(&mut app, vec![], true);
+    assert_eq!(app.world().resource::<Guild>().gold, 50);
+    assert_eq!(app.world().resource::<GuildDebt>().amount, 0);
+    assert_eq!(app.world().resource::<GuildDebt>().turns_in_debt, 0);
+    // The gold was still earned, even though it paid off debt
+    assert_eq!(app.world().resource::<GuildStats>().total_gold_earned, 200);
+}
+
+#[test]
+fn complete_quest_updates_guild_scales_gold_with_guild_morale() {
+    for (morale, expected_gold) in [(25, 50), (50, 100), (75, 150)] {
+        let mut app = make_test_app();
+        app.add_systems(Update, complete_quest_updates_guild);
+        app.insert_resource(GuildMorale(morale));
+
+        send_quest_complete(&mut app, vec![], true);
+
+        assert_eq!(app.world().resource::<Guild>().gold, expected_gold);
+    }
+}
+
+#[test]
+fn complete_quest_sends_notification() {
+    let mut app = make_test_app();
+    app.add_systems(Update, complete_quest_send_notification);
+
+    // Create a Hero
+    let hero_entity = app
+        .world_mut()
+        .spawn(HeroBundle {
+            name: HeroName("Cedric the Wise".to_string()),
+            ..default()
+        })
+        .id();
+
+    // Add a QuestCompleteEvent
+    send_quest_complete(&mut app, vec![hero_entity], true);
+    // Check that a notification was sent
+    assert_eq!(
+        last_notification(&app),
+        "After a grueling battle, Cedric the Wise emerged victorious from The Dragon's Lair!"
+    );
+}
+
+// Exp a hero of this level needs to reach the next level.
+fn exp_required_for_level(config: &LevelConfig, level: u32) -> u32 {
+    (config.base_exp as f32 * (level as f32).powf(config.scale_factor)).round() as u32
+}
+
+#[test]
+fn exp_required_for_level_follows_config() {
+    let config = LevelConfig {
+        base_exp: 100,
+        scale_factor: 1.5,
+    };
+    assert_eq!(exp_required_for_level(&config, 1), 100);
+    assert_eq!(exp_required_for_level(&config, 3), 520);
+    assert_eq!(exp_required_for_level(&config, 5), 1118);
+    assert_eq!(exp_required_for_level(&config, 10), 3162);
+
+    // A linear curve
+    let config = LevelConfig {
+        base_exp: 50,
+        scale_factor: 1.0,
+    };
+    assert_eq!(exp_required_for_level(&config, 1), 50);
+    assert_eq!(exp_required_for_level(&config, 3), 150);
+    assert_eq!(exp_required_for_level(&config, 5), 250);
+    assert_eq!(exp_required_for_level(&config, 10), 500);
+}
+
+// Heroes level up when their exp reaches exp_to_next. Leftover exp carries over, and a large enough reward can grant several levels at once.
+fn level_up_heroes(
+    mut heroes_query: Query<
+        (
+            Entity,
+            &mut LevelState,
+            Option<&HeroClass>,
+            Option<&mut Biography>,
+        ),
+        With<Hero>,
+    >,
+    names_query: Query<&HeroName>,
+    buildings_query: BuildingOperationalQuery,
+    hero_config: Res<HeroConfig>,
+    level_config: Res<LevelConfig>,
+    turn: Res<Turn>,
+    mut ev_level_up: EventWriter<LevelUpEvent>,
+    mut ev_specialization_unlock: EventWriter<SpecializationUnlockEvent>,
+    mut ev_retirement: EventWriter<HeroRetirementEvent>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let specialization_level = 5; // Heroes choose a specialization on reaching this level
+    let has_training_ground = buildings_query
+        .iter()
+        .any(|building| building.building_type == BuildingType::TrainingGround);
+    for (entity, mut level_state, class, mut biography) in heroes_query.iter_mut() {
+        // Guard against a zero threshold, which would otherwise loop forever.
+        while level_state.exp_to_next > 0
+            && level_state.exp
+                >= effective_exp_to_next(level_state.exp_to_next, has_training_ground)
+        {
+            // Heroes who have nothing left to learn retire instead
+            if level_state.level >= hero_config.max_level {
+                if let Some(biography) = biography.as_mut() {
+                    biography.record(
+                        turn.0,
+                        BiographyEventType::Retired,
+                        format!("Retired at level {}", level_state.level),
+                    );
+                }
+                ev_retirement.write(HeroRetirementEvent {
+                    hero: entity,
+                    name: hero_display_name(&names_query, entity),
+                    final_level: level_state.level,
+                });
+                break;
+            }
+            level_state.exp -= effective_exp_to_next(level_state.exp_to_next, has_training_ground);
+            level_state.level += 1;
+            level_state.exp_to_next = exp_required_for_level(&level_config, level_state.level);
+            ev_level_up.write(LevelUpEvent {
+                hero: entity,
+                new_level: level_state.level,
+            });
+            if let Some(biography) = biography.as_mut() {
+                biography.record(
+                    turn.0,
+                    BiographyEventType::LeveledUp,
+                    format!("Reached level {}", level_state.level),
+                );
+            }
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} reached level {}",
+                hero_display_name(&names_query, entity),
+                level_state.level
+            )));
+            let options = class.map_or(vec![], Specialization::options_for);
+            if level_state.level == specialization_level && !options.is_empty() {
+                ev_specialization_unlock.write(SpecializationUnlockEvent {
+                    hero: entity,
+                    options,
+                });
+            }
+        }
+    }
+}
+
+// The exp a hero actually needs for their next level. An operational Training Ground cuts it by 15%.
+fn effective_exp_to_next(exp_to_next: u32, has_training_ground: bool) -> u32 {
+    if has_training_ground {
+        (exp_to_next as f32 * 0.85).ceil() as u32
+    } else {
+        exp_to_next
+    }
+}
+
+#[test]
+fn effective_exp_to_next_rounds_up() {
+    assert_eq!(effective_exp_to_next(100, false), 100);
+    assert_eq!(effective_exp_to_next(100, true), 85);
+    assert_eq!(effective_exp_to_next(101, true), 86);
+    assert_eq!(effective_exp_to_next(283, true), 241);
+}
+
+#[test]
+fn level_up_heroes_levels_sooner_with_training_ground() {
+    let mut app = make_test_app();
+    app.add_systems(Update, level_up_heroes);
+    let spawn_hero_with_exp = |app: &mut App, exp: u32| {
+        app.world_mut()
+            .spawn((Hero, LevelState { exp, ..default() }))
+            .id()
+    };
+    let training_ground = app
+        .world_mut()
+        .spawn((
+            GuildBuilding {
+                building_type: BuildingType::TrainingGround,
+            },
+            Operational,
+        ))
+        .id();
+
+    // 85 of the usual 100 exp is enough, and the hero's own threshold is left alone
+    let trained_hero = spawn_hero_with_exp(&mut app, 90);
+    app.update();
+    let level_state = app.world().get::<LevelState>(trained_hero).unwrap();
+    assert_eq!(level_state.level, 2);
+    assert_eq!(level_state.exp, 5);
+    assert_eq!(level_state.exp_to_next, 283);
+
+    // Without the Training Ground, heroes need the full amount again
+    app.world_mut()
+        .entity_mut(training_ground)
+        .remove::<Operational>();
+    let untrained_hero = spawn_hero_with_exp(&mut app, 90);
+    app.update();
+    let level_state = app.world().get::<LevelState>(untrained_hero).unwrap();
+    assert_eq!(level_state.level, 1);
+    assert_eq!(level_state.exp, 90);
+}
+
+#[test]
+fn level_up_heroes_records_biography_entries() {
+    let mut app = make_test_app();
+    app.insert_resource(HeroConfig {
+        max_level: 2,
+        ..default()
+    });
+    app.add_systems(Update, level_up_heroes);
+    app.world_mut().resource_mut::<Turn>().0 = 8;
+    // Enough exp for level 2, and then for level 3, which is past the max
+    let hero = app
+        .world_mut()
+        .spawn((
+            Hero,
+            LevelState {
+                exp: 383,
+                ..default()
+            },
+            Biography::default(),
+        ))
+        .id();
+
+    app.update();
+
+    let entries: Vec<(u32, BiographyEventType, String)> = app
+        .world()
+        .get::<Biography>(hero)
+        .unwrap()
+        .entries
+        .iter()
+        .map(|entry| (entry.turn, entry.event_type, entry.description.clone()))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            (
+                8,
+                BiographyEventType::LeveledUp,
+                "Reached level 2".to_string()
+            ),
+            (
+                8,
+                BiographyEventType::Retired,
+                "Retired at level 2".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn level_up_heroes_promotes_hero_once() {
+    let mut app = make_test_app();
+    app.add_systems(Update, level_up_heroes);
+
+    let hero_entity = app
+        .world_mut()
+        .spawn((
+            Hero,
+            LevelState {
+                exp: 150,
+                ..default()
+            },
+        ))
+        .id();
+
+    app.update();
+
+    // Check that the hero gained a level and the remainder carried over
+    let level_state = app.world().get::<LevelState>(hero_entity).unwrap();
+    assert_eq!(level_state.level, 2);
+    assert_eq!(level_state.exp, 50);
+    assert_eq!(level_state.exp_to_next, 283);
+
+    // Check that a LevelUpEvent was emitted
+    let level_up_events = app.world().resource::<Events<LevelUpEvent>>();
+    let mut reader = level_up_events.get_cursor();
+    let event = reader.read(level_up_events).next().unwrap();
+    assert_eq!(event.hero, hero_entity);
+    assert_eq!(event.new_level, 2);
+
+    // Check that a notification was sent
+    assert_eq!(
+        last_notification(&app),
+        format!("Hero {:?} reached level 2", hero_entity)
+    );
+}
+
+#[test]
+fn level_up_heroes_promotes_hero_twice_in_one_update() {
+    let mut app = make_test_app();
+    app.add_systems(Update, level_up_heroes);
+
+    // 100 exp for level 2, then 283 for level 3, leaving 17 over
+    let hero_entity = app
+        .world_mut()
+        .spawn((
+            Hero,
+            LevelState {
+                exp: 400,
+                ..default()
+            },
+        ))
+        .id();
+
+    app.update();
+
+    let level_state = app.world().get::<LevelState>(hero_entity).unwrap();
+    assert_eq!(level_state.level, 3);
+    assert_eq!(level_state.exp, 17);
+    assert_eq!(level_state.exp_to_next, 520);
+
+    // Check that one LevelUpEvent was emitted per level gained
+    let level_up_events = app.world().resource::<Events<LevelUpEvent>>();
+    let mut reader = level_up_events.get_cursor();
+    let new_levels: Vec<u32> = reader
+        .read(level_up_events)
+        .map(|event| event.new_level)
+        .collect();
+    assert_eq!(new_levels, vec![2, 3]);
+}
+
+#[test]
+fn level_up_heroes_levels_up_when_exp_exactly_reaches_threshold() {
+    let mut app = make_test_app();
+    app.add_systems(Update, level_up_heroes);
+
+    let hero_entity = app
+        .world_mut()
+        .spawn((
+            Hero,
+            LevelState {
+                exp: 100,
+                ..default()
+            },
+        ))
+        .id();
+
+    app.update();
+
+    let level_state = app.world().get::<LevelState>(hero_entity).unwrap();
+    assert_eq!(level_state.level, 2);
+    assert_eq!(level_state.exp, 0);
+    assert_eq!(level_state.exp_to_next, 283);
+}
+
+#[test]
+fn level_up_heroes_ignores_hero_below_threshold() {
+    let mut app = make_test_app();
+    app.add_systems(Update, level_up_heroes);
+
+    let hero_entity = app
+        .world_mut()
+        .spawn((
+            Hero,
+            LevelState {
+                exp: 99,
+                ..default()
+            },
+        ))
+        .id();
+
+    app.update();
+
+    let level_state = app.world().get::<LevelState>(hero_entity).unwrap();
+    assert_eq!(level_state.level, 1);
+    assert_eq!(level_state.exp, 99);
+
+    let level_up_events = app.world().resource::<Events<LevelUpEvent>>();
+    assert!(level_up_events.is_empty());
+}
+
+#[cfg(test)]
+fn make_retirement_test_app() -> App {
+    let mut app = make_test_app();
+    app.insert_resource(HeroConfig {
+        max_level: 5,
+        ..default()
+    });
+    app.add_systems(
+        Update,
+        (
+            level_up_heroes,
+            handle_hero_retirement,
+            compute_retirement_bonus,
+        )
+            .chain(),
+    );
+    app
+}
+
+#[cfg(test)]
+fn spawn_retirement_test_hero(app: &mut App, level: u32) -> Entity {
+    app.world_mut()
+        .spawn((
+            Hero,
+            LevelState {
+                level,
+                exp: 100,
+                exp_to_next: 100,
+            },
+        ))
+        .id()
+}
+
+#[test]
+fn level_up_heroes_does_not_retire_hero_reaching_max_level() {
+    let mut app = make_retirement_test_app();
+    let hero_entity = spawn_retirement_test_hero(&mut app, 4);
+
+    app.update();
+
+    // Reaching the max level is fine, it's only levelling past it that retires a hero
+    let level_state = app.world().get::<LevelState>(hero_entity).unwrap();
+    assert_eq!(level_state.level, 5);
+    assert!(app.world().resource::<RetiredHeroes>().0.is_empty());
+    assert_eq!(app.world().resource::<GuildReputation>().0, 0);
+}
+
+#[test]
+fn level_up_heroes_retires_hero_at_max_level() {
+    let mut app = make_retirement_test_app();
+    let hero_entity = spawn_retirement_test_hero(&mut app, 5);
+
+    app.update();
+
+    let retirement_events = app.world().resource::<Events<HeroRetirementEvent>>();
+    let mut reader = retirement_events.get_cursor();
+    let event = reader.read(retirement_events).next().unwrap();
+    assert_eq!(event.hero, hero_entity);
+    assert_eq!(event.final_level, 5);
+
+    // Check that no level up happened along the way
+    assert!(app.world().resource::<Events<LevelUpEvent>>().is_empty());
+
+    assert!(app.world().get_entity(hero_entity).is_err());
+    let retired = app.world().resource::<RetiredHeroes>();
+    assert_eq!(retired.0.len(), 1);
+    assert_eq!(retired.0[0].name, format!("{:?}", hero_entity));
+    assert_eq!(retired.0[0].final_level, 5);
+    assert_eq!(app.world().resource::<GuildReputation>().0, 1);
+}
+
+#[cfg(test)]
+fn spawn_specialization_test_hero(app: &mut App, class: HeroClass, level: u32) -> Entity {
+    app.world_mut()
+        .spawn((
+            Hero,
+            class,
+            LevelState {
+                level,
+                exp: 10,
+                exp_to_next: 10,
+            },
+        ))
+        .id()
+}
+
+#[cfg(test)]
+fn read_specialization_unlock_events(app: &App) -> Vec<(Entity, Vec<Specialization>)> {
+    let events = app.world().resource::<Events<SpecializationUnlockEvent>>();
+    let mut reader = events.get_cursor();
+    reader
+        .read(events)
+        .map(|event| (event.hero, event.options.clone()))
+        .collect()
+}
+
+#[test]
+fn level_up_heroes_unlocks_specialization_at_level_5() {
+    let mut app = make_test_app();
+    app.add_systems(Update, level_up_heroes);
+    spawn_specialization_test_hero(&mut app, HeroClass::Warrior, 3);
+    let reaching_5 = spawn_specialization_test_hero(&mut app, HeroClass::Warrior, 4);
+    spawn_specialization_test_hero(&mut app, HeroClass::Warrior, 5);
+
+    app.update();
+
+    // Only the hero who reached level 5 gets to specialize, not the ones reaching 4 or 6
+    assert_eq!(
+        read_specialization_unlock_events(&app),
+        vec![(
+            reaching_5,
+            vec![Specialization::Berserker, Specialization::Guardian]
+        )]
+    );
+}
+
+#[test]
+fn level_up_heroes_offers_specializations_for_class() {
+    for (class, options) in [
+        (
+            HeroClass::Warrior,
+            vec![Specialization::Berserker, Specialization::Guardian],
+        ),
+        (
+            HeroClass::Tank,
+            vec![Specialization::Juggernaut, Specialization::Sentinel],
+        ),
+        (
+            HeroClass::Support,
+            vec![Specialization::Healer, Specialization::Bard],
+        ),
+    ] {
+        let mut app = make_test_app();
+        app.add_systems(Update, level_up_heroes);
+        let hero = spawn_specialization_test_hero(&mut app, class, 4);
+
+        app.update();
+
+        assert_eq!(
+            read_specialization_unlock_events(&app),
+            vec![(hero, options)]
+        );
+    }
+
+    // Classes without any specializations don't get offered an empty choice
+    let mut app = make_test_app();
+    app.add_systems(Update, level_up_heroes);
+    spawn_specialization_test_hero(&mut app, HeroClass::Rogue, 4);
+    app.update();
+    assert!(read_specialization_unlock_events(&app).is_empty());
+}
+
+// Heroes take the specialization the player chooses for them, as long as it's one of their class's.
+// When quests are auto-assigned, heroes choose for themselves as soon as they unlock one.
+fn choose_specialization(
+    mut commands: Commands,
+    mut ev_specialization_unlock: EventReader<SpecializationUnlockEvent>,
+    mut ev_choose_specialization: EventReader<ChooseSpecializationEvent>,
+    heroes_query: Query<(&LevelState, &HeroClass, Has<Specialization>), With<Hero>>,
+    names_query: Query<&HeroName>,
+    auto_assign: Res<QuestAutoAssign>,
+    mut random_src: ResMut<RandomSource>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let specialization_level = 5; // Heroes can't specialize before this level
+    let mut choices: Vec<(Entity, Specialization)> = ev_choose_specialization
+        .read()
+        .map(|event| (event.hero, event.specialization))
+        .collect();
+    for event in ev_specialization_unlock.read() {
+        if auto_assign.enabled {
+            let rng = &mut random_src.0;
+            choices.push((event.hero, *event.options.choose(rng).unwrap()));
+        }
+    }
+    for (hero, specialization) in choices {
+        let Ok((level_state, class, has_specialization)) = heroes_query.get(hero) else {
+            continue;
+        };
+        if has_specialization
+            || level_state.level < specialization_level
+            || !Specialization::options_for(class).contains(&specialization)
+        {
+            continue;
+        }
+        commands.entity(hero).insert(specialization);
+        ev_notify.write(NotificationEvent(format!(
+            "Hero {} specialized as a {:?}",
+            hero_display_name(&names_query, hero),
+            specialization
+        )));
+    }
+}
+
+#[test]
+fn choose_specialization_inserts_chosen_specialization() {
+    let mut app = make_test_app();
+    app.add_systems(Update, choose_specialization);
+    let hero = spawn_specialization_test_hero(&mut app, HeroClass::Tank, 5);
+    let low_level_hero = spawn_specialization_test_hero(&mut app, HeroClass::Tank, 4);
+    let wrong_class_hero = spawn_specialization_test_hero(&mut app, HeroClass::Support, 5);
+
+    let mut events = app
+        .world_mut()
+        .resource_mut::<Events<ChooseSpecializationEvent>>();
+    for hero in [hero, low_level_hero, wrong_class_hero] {
+        events.send(ChooseSpecializationEvent {
+            hero,
+            specialization: Specialization::Sentinel,
+        });
+    }
+    app.update();
+
+    assert_eq!(
+        app.world().get::<Specialization>(hero),
+        Some(&Specialization::Sentinel)
+    );
+    assert!(app.world().get::<Specialization>(low_level_hero).is_none());
+    assert!(
+        app.world()
+            .get::<Specialization>(wrong_class_hero)
+            .is_none()
+    );
+
+    // A hero can't change their specialization once chosen
+    app.world_mut()
+        .resource_mut::<Events<ChooseSpecializationEvent>>()
+        .send(ChooseSpecializationEvent {
+            hero,
+            specialization: Specialization::Juggernaut,
+        });
+    app.update();
+    assert_eq!(
+        app.world().get::<Specialization>(hero),
+        Some(&Specialization::Sentinel)
+    );
+}
+
+#[test]
+fn choose_specialization_auto_selects_when_auto_assigning() {
+    let mut app = make_test_app();
+    app.add_systems(Update, (level_up_heroes, choose_specialization).chain());
+    let hero = spawn_specialization_test_hero(&mut app, HeroClass::Support, 4);
+    app.update();
+    // Without auto-assignment, the choice is left to the player
+    assert!(app.world().get::<Specialization>(hero).is_none());
+
+    let mut app = make_test_app();
+    app.add_systems(Update, (level_up_heroes, choose_specialization).chain());
+    app.world_mut().resource_mut::<QuestAutoAssign>().enabled = true;
+    let hero = spawn_specialization_test_hero(&mut app, HeroClass::Support, 4);
+    app.update();
+    let specialization = app.world().get::<Specialization>(hero).unwrap();
+    assert!(Specialization::options_for(&HeroClass::Support).contains(specialization));
+}
+
+#[test]
+fn probability_of_quest_success_applies_specialization_bonus() {
+    let config = HeroConfig::default();
+    let guild_config = GuildConfig::default();
+    let probability_with = |specialization: Option<Specialization>| {
+        let hero = || QuestHeroFixture {
+            specialization,
+            ..QuestHeroFixture::new(HeroClass::Warrior, 3)
+        };
+        probability_of_quest_success(
+            3,
+            QuestType::Bounty,
+            None,
+            None,
+            &quest_hero_items(&[hero(), hero()]),
+            &config,
+            &guild_config,
+        )
+    };
+
+    assert_eq!(probability_with(None), Percent(70));
+    assert_eq!(
+        probability_with(Some(Specialization::Berserker)),
+        Percent(85)
+    );
+    assert_eq!(
+        probability_with(Some(Specialization::Guardian)),
+        Percent(75)
+    );
+}
+
+#[test]
+fn handle_hero_retirement_adds_reputation_per_retired_hero() {
+    let mut app = make_retirement_test_app();
+    app.insert_resource(GuildReputation(3));
+    spawn_retirement_test_hero(&mut app, 5);
+    spawn_retirement_test_hero(&mut app, 6);
+    spawn_retirement_test_hero(&mut app, 3);
+
+    app.update();
+
+    assert_eq!(app.world().resource::<RetiredHeroes>().0.len(), 2);
+    assert_eq!(app.world().resource::<GuildReputation>().0, 5);
+    assert_eq!(app.world().resource::<GuildStats>().total_heroes_lost, 2);
+}
+
+// Record a retiring hero in RetiredHeroes and send them off.
+fn handle_hero_retirement(
+    mut commands: Commands,
+    mut ev_retirement: EventReader<HeroRetirementEvent>,
+    heroes_query: Query<(), With<Hero>>,
+    mut retired_heroes: ResMut<RetiredHeroes>,
+    mut stats: ResMut<GuildStats>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for event in ev_retirement.read() {
+        if heroes_query.contains(event.hero) {
+            retired_heroes.0.push(RetiredHeroRecord {
+                name: event.name.clone(),
+                final_level: event.final_level,
+            });
+            stats.total_heroes_lost += 1;
+            commands.entity(event.hero).remove::<ChildOf>().despawn();
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} retired at level {}",
+                event.name, event.final_level
+            )));
+        }
+    }
+}
+
+// Every retired hero adds to the guild's reputation. Only the bonus not yet applied is added, so it's never counted twice.
+fn compute_retirement_bonus(
+    retired_heroes: Res<RetiredHeroes>,
+    mut bonus: ResMut<RetirementBonus>,
+    mut reputation: ResMut<GuildReputation>,
+) {
+    let reputation_per_retirement = 1; // Passive reputation gained for each retired hero
+    let total_bonus = retired_heroes.0.len() as i32 * reputation_per_retirement;
+    reputation.0 += total_bonus - bonus.0;
+    bonus.0 = total_bonus;
+}
+
+#[test]
+fn compute_retirement_bonus_counts_each_retiree_once() {
+    let mut app = make_test_app();
+    app.insert_resource(GuildReputation(3));
+    app.add_systems(Update, compute_retirement_bonus);
+    let retire = |app: &mut App| {
+        app.world_mut()
+            .resource_mut::<RetiredHeroes>()
+            .0
+            .push(RetiredHeroRecord {
+                name: "Aldric the Steadfast".to_string(),
+                final_level: 10,
+            });
+    };
+
+    retire(&mut app);
+    app.update();
+    assert_eq!(app.world().resource::<GuildReputation>().0, 4);
+
+    // No new retirements, so the reputation stays put
+    app.update();
+    app.update();
+    assert_eq!(app.world().resource::<GuildReputation>().0, 4);
+
+    // Reputation earned elsewhere isn't disturbed by the bonus
+    app.world_mut().resource_mut::<GuildReputation>().0 += 5;
+    retire(&mut app);
+    retire(&mut app);
+    app.update();
+    assert_eq!(app.world().resource::<GuildReputation>().0, 11);
+    app.update();
+    assert_eq!(app.world().resource::<GuildReputation>().0, 11);
+}
+
+// Update hero opinions of their party members when a quest ends, according to each hero's personality.
+fn update_relationships_after_quest(
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    mut persons_query: Query<&mut Person, With<Hero>>,
+    levels_query: Query<&LevelState, With<Hero>>,
+    config: Res<RelationshipConfig>,
+) {
+    for event in ev_quest_complete.read() {
+        // Work out every (hero, other, delta) before mutating anything, since one hero's update may depend on another hero's Person.
+        let mut deltas: Vec<(Entity, Entity, i32)> = Vec::new();
+        for hero in event.heroes.iter() {
+            if let Ok(person) = persons_query.get(*hero) {
+                for other in event.heroes.iter().filter(|other| *other != hero) {
+                    let delta = match person.personality {
+                        // Only the outcome matters to these, so apply_personality_delta handles them
+                        Personality::Friendly
+                        | Personality::ResultOriented
+                        | Personality::Brave
+                        | Personality::Cowardly
+                        | Personality::Legacy { .. } => 0,
+                        Personality::Mirror => {
+                            // Step toward the other hero's opinion of this hero, as it was before this quest
+                            let own_opinion = person.relationships.get(other).copied().unwrap_or(0);
+                            let their_opinion = persons_query
+                                .get(*other)
+                                .ok()
+                                .and_then(|other_person| {
+                                    other_person.relationships.get(hero).copied()
+                                })
+                                .unwrap_or(0);
+                            (their_opinion - own_opinion).signum()
+                        }
+                        Personality::Judgmental => {
+                            if event.injured_heroes.contains(other) {
+                                -2
+                            } else {
+                                1
+                            }
+                        }
+                        Personality::Learner | Personality::Teacher => {
+                            // Positive if the other hero is stronger
+                            let level_diff =
+                                match (levels_query.get(*hero), levels_query.get(*other)) {
+                                    (Ok(own_level), Ok(other_level)) => {
+                                        (other_level.level as i32 - own_level.level as i32).signum()
+                                    }
+                                    _ => 0,
+                                };
+                            if let Personality::Learner = person.personality {
+                                level_diff
+                            } else {
+                                -level_diff
+                            }
+                        }
+                    };
+                    deltas.push((*hero, *other, delta));
+                }
+            }
+        }
+        for (hero, other, delta) in deltas {
+            if let Ok(person) = persons_query.get_mut(hero) {
+                let person = person.into_inner();
+                apply_personality_delta(
+                    &person.personality,
+                    event.is_successful,
+                    other,
+                    &mut person.relationships,
+                );
+                let value = person.relationships.entry(other).or_insert(0);
+                *value = (*value + delta).clamp(config.min_value, config.max_value);
+            }
+        }
+    }
+}
+
+// Apply the change in opinion of another party member for personalities that only care about the quest's outcome.
+fn apply_personality_delta(
+    personality: &Personality,
+    is_successful: bool,
+    other: Entity,
+    relationships: &mut HashMap<Entity, i32>,
+) {
+    *relationships.entry(other).or_insert(0) += outcome_delta(personality, is_successful);
+}
+
+// The change in opinion of party members that a personality has from the quest's outcome alone.
+fn outcome_delta(personality: &Personality, is_successful: bool) -> i32 {
+    match personality {
+        Personality::Friendly | Personality::Brave => 1,
+        Personality::ResultOriented => {
+            if is_successful {
+                1
+            } else {
+                -1
+            }
+        }
+        Personality::Cowardly => {
+            if is_successful {
+                0
+            } else {
+                -1
+            }
+        }
+        Personality::Legacy {
+            on_success,
+            on_failure,
+        } => {
+            if is_successful {
+                *on_success
+            } else {
+                *on_failure
+            }
+        }
+        _ => 0,
+    }
+}
+
+// A legacy hero reacts to a quest's outcome as both parents would have together, within -2 to +2.
+// Parents whose reactions depend on more than the outcome, such as a Mirror, pass nothing on.
+fn legacy_personality(parent_a: &Personality, parent_b: &Personality) -> Personality {
+    let max_delta = 2; // Strongest reaction a legacy hero can inherit
+    let inherit = |is_successful| {
+        (outcome_delta(parent_a, is_successful) + outcome_delta(parent_b, is_successful))
+            .clamp(-max_delta, max_delta)
+    };
+    Personality::Legacy {
+        on_success: inherit(true),
+        on_failure: inherit(false),
+    }
+}
+
+#[test]
+fn apply_personality_delta_friendly() {
+    let other = Entity::from_raw(1);
+    let mut relationships = HashMap::new();
+
+    apply_personality_delta(&Personality::Friendly, true, other, &mut relationships);
+    assert_eq!(relationships.get(&other), Some(&1));
+
+    // A second quest with the same party, which fails
+    apply_personality_delta(&Personality::Friendly, false, other, &mut relationships);
+    assert_eq!(relationships.get(&other), Some(&2));
+}
+
+#[test]
+fn apply_personality_delta_brave() {
+    let other = Entity::from_raw(1);
+    let mut relationships = HashMap::new();
+
+    apply_personality_delta(&Personality::Brave, true, other, &mut relationships);
+    assert_eq!(relationships.get(&other), Some(&1));
+
+    apply_personality_delta(&Personality::Brave, false, other, &mut relationships);
+    assert_eq!(relationships.get(&other), Some(&2));
+}
+
+#[test]
+fn apply_personality_delta_cowardly() {
+    let other = Entity::from_raw(1);
+    let mut relationships = HashMap::new();
+
+    // Success doesn't change a Cowardly hero's opinion
+    apply_personality_delta(&Personality::Cowardly, true, other, &mut relationships);
+    assert_eq!(relationships.get(&other), Some(&0));
+
+    // But they blame the group for a failure
+    apply_personality_delta(&Personality::Cowardly, false, other, &mut relationships);
+    assert_eq!(relationships.get(&other), Some(&-1));
+}
+
+#[test]
+fn apply_personality_delta_legacy() {
+    let other = Entity::from_raw(1);
+    let mut relationships = HashMap::new();
+    let legacy = Personality::Legacy {
+        on_success: 2,
+        on_failure: -1,
+    };
+
+    apply_personality_delta(&legacy, true, other, &mut relationships);
+    assert_eq!(relationships.get(&other), Some(&2));
+
+    apply_personality_delta(&legacy, false, other, &mut relationships);
+    assert_eq!(relationships.get(&other), Some(&1));
+}
+
+#[test]
+fn legacy_personality_combines_parents_reactions() {
+    let reactions = |personality: Personality| match personality {
+        Personality::Legacy {
+            on_success,
+            on_failure,
+        } => (on_success, on_failure),
+        _ => panic!("Not a legacy personality"),
+    };
+
+    // Friendly and Result Oriented parents agree on success, and cancel out on failure
+    assert_eq!(
+        reactions(legacy_personality(
+            &Personality::Friendly,
+            &Personality::ResultOriented
+        )),
+        (2, 0)
+    );
+    // Result Oriented and Cowardly parents both blame the party for a failure
+    assert_eq!(
+        reactions(legacy_personality(
+            &Personality::ResultOriented,
+            &Personality::Cowardly
+        )),
+        (1, -2)
+    );
+    // A Mirror parent passes nothing on
+    assert_eq!(
+        reactions(legacy_personality(
+            &Personality::Mirror,
+            &Personality::Brave
+        )),
+        (1, 1)
+    );
+    // Reactions inherited from legacy parents stay within bounds
+    let legacy = legacy_personality(&Personality::Friendly, &Personality::Brave);
+    assert_eq!(reactions(legacy_personality(&legacy, &legacy)), (2, 2));
+}
+
+#[test]
+fn apply_personality_delta_result_oriented() {
+    let other = Entity::from_raw(1);
+    let mut relationships = HashMap::new();
+
+    apply_personality_delta(
+        &Personality::ResultOriented,
+        true,
+        other,
+        &mut relationships,
+    );
+    assert_eq!(relationships.get(&other), Some(&1));
+
+    apply_personality_delta(
+        &Personality::ResultOriented,
+        true,
+        other,
+        &mut relationships,
+    );
+    assert_eq!(relationships.get(&other), Some(&2));
+
+    apply_personality_delta(
+        &Personality::ResultOriented,
+        false,
+        other,
+        &mut relationships,
+    );
+    assert_eq!(relationships.get(&other), Some(&1));
+
+    // A failure with someone new starts off badly
+    let stranger = Entity::from_raw(2);
+    apply_personality_delta(
+        &Personality::ResultOriented,
+        false,
+        stranger,
+        &mut relationships,
+    );
+    assert_eq!(relationships.get(&stranger), Some(&-1));
+}
+
+#[test]
+fn update_relationships_after_quest_friendly_always_improves() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_relationships_after_quest);
+
+    let friendly = app.world_mut().spawn((Hero, Person::default())).id();
+    let other = app.world_mut().spawn((Hero, Person::default())).id();
+
+    // A failed quest still improves a Friendly hero's opinion
+    send_quest_complete(&mut app, vec![friendly, other], false);
+    let person = app.world().get::<Person>(friendly).unwrap();
+    assert_eq!(person.relationships.get(&other), Some(&1));
+    let person = app.world().get::<Person>(other).unwrap();
+    assert_eq!(person.relationships.get(&friendly), Some(&1));
+
+    // Questing together again stacks
+    send_quest_complete(&mut app, vec![friendly, other], true);
+    let person = app.world().get::<Person>(friendly).unwrap();
+    assert_eq!(person.relationships.get(&other), Some(&2));
+}
+
+#[test]
+fn update_relationships_after_quest_result_oriented_follows_outcome() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_relationships_after_quest);
+
+    let result_oriented = app
+        .world_mut()
+        .spawn((
+            Hero,
+            Person {
+                personality: Personality::ResultOriented,
+                ..default()
+            },
+        ))
+        .id();
+    let other = app.world_mut().spawn((Hero, Person::default())).id();
+
+    send_quest_complete(&mut app, vec![result_oriented, other], true);
+    let person = app.world().get::<Person>(result_oriented).unwrap();
+    assert_eq!(person.relationships.get(&other), Some(&1));
+
+    // Same pair again, but the quest fails
+    send_quest_complete(&mut app, vec![result_oriented, other], false);
+    let person = app.world().get::<Person>(result_oriented).unwrap();
+    assert_eq!(person.relationships.get(&other), Some(&0));
+
+    send_quest_complete(&mut app, vec![result_oriented, other], false);
+    let person = app.world().get::<Person>(result_oriented).unwrap();
+    assert_eq!(person.relationships.get(&other), Some(&-1));
+}
+
+#[cfg(test)]
+fn spawn_relationship_test_hero(
+    app: &mut App,
+    personality: Personality,
+    relationships: HashMap<Entity, i32>,
+) -> Entity {
+    app.world_mut()
+        .spawn((
+            Hero,
+            Person {
+                personality,
+                relationships,
+            },
+        ))
+        .id()
+}
+
+#[test]
+fn update_relationships_after_quest_mirror_steps_toward_other_opinion() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_relationships_after_quest);
+
+    let mirror = spawn_relationship_test_hero(&mut app, Personality::Mirror, HashMap::new());
+    let friendly = spawn_relationship_test_hero(
+        &mut app,
+        Personality::Friendly,
+        HashMap::from([(mirror, 3)]),
+    );
+
+    send_quest_complete(&mut app, vec![mirror, friendly], true);
+
+    // The Mirror moves one step toward 3, and the Friendly hero's own +1 doesn't count until next time
+    let person = app.world().get::<Person>(mirror).unwrap();
+    assert_eq!(person.relationships.get(&friendly), Some(&1));
+    let person = app.world().get::<Person>(friendly).unwrap();
+    assert_eq!(person.relationships.get(&mirror), Some(&4));
+}
+
+#[test]
+fn update_relationships_after_quest_mirrors_converge() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_relationships_after_quest);
+
+    let mirror_a = spawn_relationship_test_hero(&mut app, Personality::Mirror, HashMap::new());
+    let mirror_b = spawn_relationship_test_hero(
+        &mut app,
+        Personality::Mirror,
+        HashMap::from([(mirror_a, 4)]),
+    );
+
+    // Each quest, both Mirrors step toward each other, until they meet in the middle and stay there
+    for (expected_a, expected_b) in [(1, 3), (2, 2), (2, 2), (2, 2)] {
+        send_quest_complete(&mut app, vec![mirror_a, mirror_b], true);
+        let person = app.world().get::<Person>(mirror_a).unwrap();
+        assert_eq!(person.relationships.get(&mirror_b), Some(&expected_a));
+        let person = app.world().get::<Person>(mirror_b).unwrap();
+        assert_eq!(person.relationships.get(&mirror_a), Some(&expected_b));
+    }
+}
+
+#[test]
+fn update_relationships_after_quest_mirror_unchanged_when_opinions_equal() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_relationships_after_quest);
+
+    let mirror_a = spawn_relationship_test_hero(&mut app, Personality::Mirror, HashMap::new());
+    let mirror_b = spawn_relationship_test_hero(
+        &mut app,
+        Personality::Mirror,
+        HashMap::from([(mirror_a, -2)]),
+    );
+    app.world_mut()
+        .get_mut::<Person>(mirror_a)
+        .unwrap()
+        .relationships
+        .insert(mirror_b, -2);
+
+    send_quest_complete(&mut app, vec![mirror_a, mirror_b], false);
+
+    let person = app.world().get::<Person>(mirror_a).unwrap();
+    assert_eq!(person.relationships.get(&mirror_b), Some(&-2));
+    let person = app.world().get::<Person>(mirror_b).unwrap();
+    assert_eq!(person.relationships.get(&mirror_a), Some(&-2));
+}
+
+#[test]
+fn update_relationships_after_quest_judgmental_follows_injuries() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_relationships_after_quest);
+
+    let judgmental =
+        spawn_relationship_test_hero(&mut app, Personality::Judgmental, HashMap::new());
+    let healthy_a = spawn_relationship_test_hero(&mut app, Personality::Friendly, HashMap::new());
+    let healthy_b = spawn_relationship_test_hero(&mut app, Personality::Friendly, HashMap::new());
+
+    let party = vec![judgmental, healthy_a, healthy_b];
+
+    // Nobody injured
+    send_injury_test_quest_complete(&mut app, party.clone(), vec![]);
+    app.update();
+    let person = app.world().get::<Person>(judgmental).unwrap();
+    assert_eq!(person.relationships.get(&healthy_a), Some(&1));
+    assert_eq!(person.relationships.get(&healthy_b), Some(&1));
+
+    // Mixed party: one injured
+    send_injury_test_quest_complete(&mut app, party.clone(), vec![healthy_a]);
+    app.update();
+    let person = app.world().get::<Person>(judgmental).unwrap();
+    assert_eq!(person.relationships.get(&healthy_a), Some(&-1));
+    assert_eq!(person.relationships.get(&healthy_b), Some(&2));
+
+    // Everyone injured, including the Judgmental hero, who doesn't judge themself
+    send_injury_test_quest_complete(&mut app, party.clone(), party.clone());
+    app.update();
+    let person = app.world().get::<Person>(judgmental).unwrap();
+    assert_eq!(person.relationships.get(&healthy_a), Some(&-3));
+    assert_eq!(person.relationships.get(&healthy_b), Some(&0));
+    assert_eq!(person.relationships.get(&judgmental), None);
+}
+
+#[cfg(test)]
+fn spawn_leveled_relationship_test_hero(
+    app: &mut App,
+    personality: Personality,
+    level: u32,
+) -> Entity {
+    app.world_mut()
+        .spawn((
+            Hero,
+            Person {
+                personality,
+                relationships: HashMap::new(),
+            },
+            LevelState { level, ..default() },
+        ))
+        .id()
+}
+
+#[test]
+fn update_relationships_after_quest_learner_prefers_stronger_heroes() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_relationships_after_quest);
+
+    let learner = spawn_leveled_relationship_test_hero(&mut app, Personality::Learner, 3);
+    let stronger = spawn_leveled_relationship_test_hero(&mut app, Personality::Friendly, 4);
+    let equal = spawn_leveled_relationship_test_hero(&mut app, Personality::Friendly, 3);
+    let weaker = spawn_leveled_relationship_test_hero(&mut app, Personality::Friendly, 1);
+
+    send_quest_complete(&mut app, vec![learner, stronger, equal, weaker], true);
+
+    let person = app.world().get::<Person>(learner).unwrap();
+    assert_eq!(person.relationships.get(&stronger), Some(&1));
+    assert_eq!(person.relationships.get(&equal), Some(&0));
+    assert_eq!(person.relationships.get(&weaker), Some(&-1));
+}
+
+#[test]
+fn update_relationships_after_quest_teacher_prefers_weaker_heroes() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_relationships_after_quest);
+
+    let teacher = spawn_leveled_relationship_test_hero(&mut app, Personality::Teacher, 3);
+    let stronger = spawn_leveled_relationship_test_hero(&mut app, Personality::Friendly, 4);
+    let equal = spawn_leveled_relationship_test_hero(&mut app, Personality::Friendly, 3);
+    let weaker = spawn_leveled_relationship_test_hero(&mut app, Personality::Friendly, 1);
+
+    send_quest_complete(&mut app, vec![teacher, stronger, equal, weaker], false);
+
+    let person = app.world().get::<Person>(teacher).unwrap();
+    assert_eq!(person.relationships.get(&stronger), Some(&-1));
+    assert_eq!(person.relationships.get(&equal), Some(&0));
+    assert_eq!(person.relationships.get(&weaker), Some(&1));
+}
+
+#[test]
+fn update_relationships_after_quest_learner_and_teacher_like_each_other() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_relationships_after_quest);
+
+    let learner = spawn_leveled_relationship_test_hero(&mut app, Personality::Learner, 2);
+    let teacher = spawn_leveled_relationship_test_hero(&mut app, Personality::Teacher, 5);
+
+    send_quest_complete(&mut app, vec![learner, teacher], true);
+
+    let person = app.world().get::<Person>(learner).unwrap();
+    assert_eq!(person.relationships.get(&teacher), Some(&1));
+    let person = app.world().get::<Person>(teacher).unwrap();
+    assert_eq!(person.relationships.get(&learner), Some(&1));
+}
+
+#[test]
+fn update_relationships_after_quest_multiple_learners() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_relationships_after_quest);
+
+    let weak_learner = spawn_leveled_relationship_test_hero(&mut app, Personality::Learner, 1);
+    let strong_learner = spawn_leveled_relationship_test_hero(&mut app, Personality::Learner, 4);
+    let middle_learner = spawn_leveled_relationship_test_hero(&mut app, Personality::Learner, 2);
+
+    send_quest_complete(
+        &mut app,
+        vec![weak_learner, strong_learner, middle_learner],
+        true,
+    );
+
+    let person = app.world().get::<Person>(weak_learner).unwrap();
+    assert_eq!(person.relationships.get(&strong_learner), Some(&1));
+    assert_eq!(person.relationships.get(&middle_learner), Some(&1));
+    let person = app.world().get::<Person>(strong_learner).unwrap();
+    assert_eq!(person.relationships.get(&weak_learner), Some(&-1));
+    assert_eq!(person.relationships.get(&middle_learner), Some(&-1));
+    let person = app.world().get::<Person>(middle_learner).unwrap();
+    assert_eq!(person.relationships.get(&weak_learner), Some(&-1));
+    assert_eq!(person.relationships.get(&strong_learner), Some(&1));
+}
+
+// Each time the RelationshipDecayTimer completes, every hero's opinions drift toward neutral.
+// Opinions of heroes from an opposing faction drift twice as fast.
+fn apply_relationship_decay(
+    mut ev_turn_timer_complete: EventReader<TurnTimerCompleteEvent>,
+    timers_query: Query<(), With<RelationshipDecayTimer>>,
+    mut persons_query: Query<(&mut Person, Option<&HeroFaction>, Option<&Alignment>), With<Hero>>,
+    factions_query: Query<&HeroFaction>,
+    alignments_query: Query<&Alignment>,
+    config: Res<RelationshipConfig>,
+) {
+    for TurnTimerCompleteEvent(entity) in ev_turn_timer_complete.read() {
+        if timers_query.contains(*entity) {
+            for (mut person, own_faction, own_alignment) in persons_query.iter_mut() {
+                for (other, value) in person.relationships.iter_mut() {
+                    let opposed = match (own_faction, factions_query.get(*other)) {
+                        (Some(own_faction), Ok(other_faction)) => {
+                            own_faction.0.opposes(&other_faction.0)
+                        }
+                        _ => false,
+                    };
+                    // Law and Chaos grate on each other, on top of any faction rivalry
+                    let opposed_alignment = match (own_alignment, alignments_query.get(*other)) {
+                        (Some(own_alignment), Ok(other_alignment)) => {
+                            own_alignment.opposes(other_alignment)
+                        }
+                        _ => false,
+                    };
+                    let mut decay_rate = config.decay_rate;
+                    if opposed {
+                        decay_rate *= 2;
+                    }
+                    if opposed_alignment {
+                        decay_rate *= 2;
+                    }
+                    *value = decay_toward_zero(*value, decay_rate)
+                        .clamp(config.min_value, config.max_value);
+                }
+            }
+        }
+    }
+}
+
+// Move a relationship value toward zero by up to decay_rate, without overshooting past zero.
+fn decay_toward_zero(value: i32, decay_rate: i32) -> i32 {
+    if value > 0 {
+        (value - decay_rate).max(0)
+    } else {
+        (value + decay_rate).min(0)
+    }
+}
+
+#[test]
+fn decay_toward_zero_doesnt_overshoot() {
+    assert_eq!(decay_toward_zero(5, 1), 4);
+    assert_eq!(decay_toward_zero(-5, 1), -4);
+    assert_eq!(decay_toward_zero(1, 1), 0);
+    assert_eq!(decay_toward_zero(1, 3), 0);
+    assert_eq!(decay_toward_zero(-2, 3), 0);
+    assert_eq!(decay_toward_zero(0, 1), 0);
+}
+
+#[test]
+fn apply_relationship_decay_moves_opinions_toward_neutral() {
+    let mut app = make_test_app();
+    app.insert_resource(RelationshipConfig {
+        decay_interval_turns: 2,
+        decay_rate: 1,
+        ..default()
+    });
+    app.add_systems(
+        Update,
+        (advance_turn_timer, apply_relationship_decay).chain(),
+    );
+
+    app.world_mut()
+        .spawn((RelationshipDecayTimer, TurnTimer::repeating(2)));
+    let fan = Entity::from_raw(100);
+    let rival = Entity::from_raw(101);
+    let stranger = Entity::from_raw(102);
+    let hero = app
+        .world_mut()
+        .spawn((
+            Hero,
+            Person {
+                personality: Personality::Friendly,
+                relationships: HashMap::from([(fan, 5), (rival, -1), (stranger, 0)]),
+            },
+        ))
+        .id();
+
+    // Each decay cycle takes 2 turns
+    for expected_fan in [4, 3, 2, 1, 0] {
+        for _ in 0..2 {
+            send_turns(&mut app, 1);
+        }
+        let person = app.world().get::<Person>(hero).unwrap();
+        assert_eq!(person.relationships.get(&fan), Some(&expected_fan));
+        // Values that reach neutral stay there, rather than overshooting
+        assert_eq!(person.relationships.get(&rival), Some(&0));
+        assert_eq!(person.relationships.get(&stranger), Some(&0));
+    }
+}
+
+#[test]
+fn apply_relationship_decay_is_doubled_between_opposing_factions() {
+    let mut app = make_test_app();
+    app.add_systems(Update, apply_relationship_decay);
+    let timer = app
+        .world_mut()
+        .spawn((RelationshipDecayTimer, TurnTimer::repeating(20)))
+        .id();
+    let chaotic = app
+        .world_mut()
+        .spawn((Hero, HeroFaction(Faction::Chaos)))
+        .id();
+    let orderly = app
+        .world_mut()
+        .spawn((Hero, HeroFaction(Faction::Order)))
+        .id();
+    let natural = app
+        .world_mut()
+        .spawn((Hero, HeroFaction(Faction::Nature)))
+        .id();
+    let unaligned = app.world_mut().spawn(Hero).id();
+    let hero = app
+        .world_mut()
+        .spawn((
+            Hero,
+            HeroFaction(Faction::Order),
+            Person {
+                relationships: HashMap::from([
+                    (chaotic, 5),
+                    (orderly, 5),
+                    (natural, -5),
+                    (unaligned, 5),
+                ]),
+                ..default()
+            },
+        ))
+        .id();
+
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(timer));
+    app.update();
+
+    let person = app.world().get::<Person>(hero).unwrap();
+    assert_eq!(person.relationships.get(&chaotic), Some(&3));
+    assert_eq!(person.relationships.get(&orderly), Some(&4));
+    assert_eq!(person.relationships.get(&natural), Some(&-4));
+    assert_eq!(person.relationships.get(&unaligned), Some(&4));
+}
+
+#[test]
+fn apply_relationship_decay_is_doubled_between_law_and_chaos() {
+    let mut app = make_test_app();
+    app.add_systems(Update, apply_relationship_decay);
+    let timer = app
+        .world_mut()
+        .spawn((RelationshipDecayTimer, TurnTimer::repeating(20)))
+        .id();
+    let chaotic = app.world_mut().spawn((Hero, Alignment::Chaos)).id();
+    let lawful = app.world_mut().spawn((Hero, Alignment::Law)).id();
+    let neutral = app.world_mut().spawn((Hero, Alignment::Neutral)).id();
+    let opposing_faction = app
+        .world_mut()
+        .spawn((Hero, Alignment::Chaos, HeroFaction(Faction::Chaos)))
+        .id();
+    let hero = app
+        .world_mut()
+        .spawn((
+            Hero,
+            Alignment::Law,
+            HeroFaction(Faction::Order),
+            Person {
+                relationships: HashMap::from([
+                    (chaotic, 5),
+                    (lawful, 5),
+                    (neutral, -5),
+                    (opposing_faction, 5),
+                ]),
+                ..default()
+            },
+        ))
+        .id();
+
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(timer));
+    app.update();
+
+    let person = app.world().get::<Person>(hero).unwrap();
+    assert_eq!(person.relationships.get(&chaotic), Some(&3));
+    assert_eq!(person.relationships.get(&lawful), Some(&4));
+    assert_eq!(person.relationships.get(&neutral), Some(&-4));
+    // Opposing factions and alignments both double the decay
+    assert_eq!(person.relationships.get(&opposing_faction), Some(&1));
+}
+
+#[test]
+fn update_relationships_after_quest_stays_within_bounds() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_relationships_after_quest);
+
+    let result_oriented =
+        spawn_relationship_test_hero(&mut app, Personality::ResultOriented, HashMap::new());
+    let other = spawn_relationship_test_hero(&mut app, Personality::ResultOriented, HashMap::new());
+
+    for _ in 0..20 {
+        send_quest_complete(&mut app, vec![result_oriented, other], true);
+        let person = app.world().get::<Person>(result_oriented).unwrap();
+        assert!(*person.relationships.get(&other).unwrap() <= 10);
+    }
+    let person = app.world().get::<Person>(result_oriented).unwrap();
+    assert_eq!(person.relationships.get(&other), Some(&10));
+
+    // The excess wasn't stored, so a single failure brings the opinion down from the max
+    send_quest_complete(&mut app, vec![result_oriented, other], false);
+    let person = app.world().get::<Person>(result_oriented).unwrap();
+    assert_eq!(person.relationships.get(&other), Some(&9));
+}
+
+// Clamp every relationship to the configured bounds, in case the bounds changed since the values were set.
+fn enforce_relationship_bounds(
+    mut persons_query: Query<&mut Person, With<Hero>>,
+    config: Res<RelationshipConfig>,
+) {
+    for mut person in persons_query.iter_mut() {
+        for value in person.relationships.values_mut() {
+            *value = (*value).clamp(config.min_value, config.max_value);
+        }
+    }
+}
+
+#[test]
+fn enforce_relationship_bounds_clamps_existing_values() {
+    let mut app = make_test_app();
+    app.insert_resource(RelationshipConfig {
+        min_value: -3,
+        max_value: 4,
+        ..default()
+    });
+    app.add_systems(Update, enforce_relationship_bounds);
+
+    let fan = Entity::from_raw(100);
+    let rival = Entity::from_raw(101);
+    let friend = Entity::from_raw(102);
+    let hero = spawn_relationship_test_hero(
+        &mut app,
+        Personality::Friendly,
+        HashMap::from([(fan, 9), (rival, -7), (friend, 2)]),
+    );
+
+    app.update();
+
+    let person = app.world().get::<Person>(hero).unwrap();
+    assert_eq!(person.relationships.get(&fan), Some(&4));
+    assert_eq!(person.relationships.get(&rival), Some(&-3));
+    assert_eq!(person.relationships.get(&friend), Some(&2));
+}
+
+// Keep GuildStats up to date with the current roster.
+fn update_guild_stats(
+    heroes_query: Query<&LevelState, (With<Hero>, Without<HeroStatusAvailable>)>,
+    mut stats: ResMut<GuildStats>,
+) {
+    let hero_count = heroes_query.iter().count() as u32;
+    let total_level: u32 = heroes_query.iter().map(|level| level.level).sum();
+    stats.average_hero_level = if hero_count > 0 {
+        total_level / hero_count
+    } else {
+        1
+    };
+}
+
+#[test]
+fn update_guild_stats_finds_average_hero_level() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_guild_stats);
+
+    for level in [2, 3, 5] {
+        app.world_mut()
+            .spawn((Hero, LevelState { level, ..default() }));
+    }
+    app.update();
+
+    assert_eq!(app.world().resource::<GuildStats>().average_hero_level, 3);
+}
+
+// Each time the GuildReportTimer completes, summarise the guild's lifetime stats.
+fn emit_guild_report(
+    mut ev_turn_timer_complete: EventReader<TurnTimerCompleteEvent>,
+    timers_query: Query<(), With<GuildReportTimer>>,
+    stats: Res<GuildStats>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for TurnTimerCompleteEvent(entity) in ev_turn_timer_complete.read() {
+        if timers_query.contains(*entity) {
+            ev_notify.write(NotificationEvent(format!(
+                "Guild report:\nQuests completed: {}\nQuests failed: {}\nGold earned: {}\nExp awarded: {}\nHeroes hired: {}\nHeroes lost: {}",
+                stats.total_quests_completed,
+                stats.total_quests_failed,
+                stats.total_gold_earned,
+                stats.total_exp_awarded,
+                stats.total_heroes_hired,
+                stats.total_heroes_lost
+            )));
+        }
+    }
+}
+
+// Alongside each guild report, list the most rewarding quests on the board.
+fn announce_best_quests(
+    mut ev_turn_timer_complete: EventReader<TurnTimerCompleteEvent>,
+    timers_query: Query<(), With<GuildReportTimer>>,
+    quests_query: Query<(Entity, &QuestDescription), (With<Quest>, With<QuestStatusAvailable>)>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let quests_to_show = 3;
+    for TurnTimerCompleteEvent(entity) in ev_turn_timer_complete.read() {
+        if !timers_query.contains(*entity) {
+            continue;
+        }
+        let mut quests: Vec<(Entity, &QuestDescription)> = quests_query.iter().collect();
+        if quests.is_empty() {
+            continue;
+        }
+        // Sort in entity order first, so quests worth the same are listed in the order they were posted
+        quests.sort_by_key(|(quest, _)| *quest);
+        sort_quests_by_reward(&mut quests);
+        let lines: Vec<String> = quests
+            .iter()
+            .take(quests_to_show)
+            .map(|(_, description)| {
+                format!(
+                    "{}: {} gold, {} exp",
+                    description.name, description.gold_reward, description.exp_reward
+                )
+            })
+            .collect();
+        ev_notify.write(NotificationEvent(format!(
+            "Best available quests:\n{}",
+            lines.join("\n")
+        )));
+    }
+}
+
+#[test]
+fn announce_best_quests_lists_most_rewarding_quests() {
+    let mut app = make_test_app();
+    app.add_systems(Update, announce_best_quests);
+    let timer = app
+        .world_mut()
+        .spawn((GuildReportTimer, TurnTimer::repeating(20)))
+        .id();
+    for (name, gold, exp) in [
+        ("Rat Catching", 20, 10),
+        ("The Dragon's Lair", 500, 200),
+        ("Bandit Camp", 150, 50),
+        ("Lost Caravan", 100, 100),
+    ] {
+        let description = QuestDescriptionBuilder::new()
+            .name(name.to_string())
+            .gold(gold)
+            .exp(exp)
+            .build();
+        app.world_mut().spawn(QuestBundle {
+            marker: Quest,
+            progress: TurnTimer::new(description.turns_to_expiry),
+            description,
+            status: QuestStatusAvailable,
+        });
+    }
+
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(timer));
+    app.update();
+
+    assert_eq!(
+        last_notification(&app),
+        "Best available quests:\nThe Dragon's Lair: 500 gold, 200 exp\nBandit Camp: 150 gold, 50 exp\nLost Caravan: 100 gold, 100 exp"
+    );
+}
+
+#[test]
+fn emit_guild_report_summarises_stats_on_schedule() {
+    let mut app = make_test_app();
+    app.insert_resource(GuildStats {
+        total_quests_completed: 7,
+        total_quests_failed: 2,
+        total_gold_earned: 1500,
+        total_exp_awarded: 900,
+        total_heroes_hired: 4,
+        total_heroes_lost: 1,
+        ..default()
+    });
+    app.add_systems(Update, (advance_turn_timer, emit_guild_report).chain());
+    let timer = app
+        .world_mut()
+        .spawn((GuildReportTimer, TurnTimer::repeating(20)))
+        .id();
+
+    send_turns(&mut app, 19);
+    assert!(
+        app.world()
+            .resource::<Events<NotificationEvent>>()
+            .is_empty()
+    );
+
+    send_turns(&mut app, 1);
+    assert_eq!(
+        last_notification(&app),
+        "Guild report:\nQuests completed: 7\nQuests failed: 2\nGold earned: 1500\nExp awarded: 900\nHeroes hired: 4\nHeroes lost: 1"
+    );
+    assert_eq!(
+        app.world().get::<TurnTimer>(timer).unwrap().turns_remaining,
+        20
+    );
+}
+
+// Count the guild's successful quests, and promote the guild each time it reaches the next rank's threshold.
+fn advance_guild_rank(
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    thresholds: Res<GuildRankThresholds>,
+    config: Res<GuildConfig>,
+    mut stats: ResMut<GuildStats>,
+    mut rank: ResMut<GuildRank>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for event in ev_quest_complete.read() {
+        if !event.is_successful {
+            continue;
+        }
+        stats.total_successful_quests += 1;
+        while let Some(threshold) = next_rank_threshold(&thresholds, rank.0) {
+            if stats.total_successful_quests < threshold || rank.0 >= config.max_rank {
+                break;
+            }
+            rank.0 += 1;
+            ev_notify.write(NotificationEvent(format!(
+                "The guild has advanced to rank {}",
+                rank.0
+            )));
+        }
+    }
+}
+
+// Each turn, every rival guild might claim each available quest before the guild can take it.
+// Rivals are less of a threat to a guild of higher rank.
+fn rival_guilds_claim_quests(
+    mut commands: Commands,
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    quests_query: Query<(Entity, &QuestDescription), (With<Quest>, With<QuestStatusAvailable>)>,
+    rivals: Res<RivalGuilds>,
+    rank: Res<GuildRank>,
+    mut random_src: ResMut<RandomSource>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let turn_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    if turn_delta == 0 {
+        return;
+    }
+    let rng = &mut random_src.0;
+    let mut claimed: Vec<Entity> = Vec::new();
+    for _ in 0..turn_delta {
+        for (quest, description) in quests_query.iter() {
+            if claimed.contains(&quest) {
+                continue;
+            }
+            let claimant = rivals.0.iter().find(|rival| {
+                let probability =
+                    rival.claim_probability_per_turn * rival.strength as f32 / rank.0.max(1) as f32;
+                rng.random_bool(f64::from(probability.clamp(0.0, 1.0)))
+            });
+            if let Some(rival) = claimant {
+                claimed.push(quest);
+                commands.entity(quest).despawn();
+                ev_notify.write(NotificationEvent(format!(
+                    "{} claimed {}!",
+                    rival.name, description.name
+                )));
+            }
+        }
+    }
+}
+
+#[test]
+fn rival_guilds_claim_quests_despawns_claimed_quest() {
+    let mut app = make_test_app();
+    app.insert_resource(RivalGuilds(vec![RivalGuild {
+        name: "The Iron Wolves".to_string(),
+        claim_probability_per_turn: 1.0,
+        strength: 1,
+    }]));
+    app.add_systems(
+        Update,
+        (grow_rival_guilds, rival_guilds_claim_quests).chain(),
+    );
+    let quest = spawn_quest(&mut app, 1, 10);
+
+    send_turns(&mut app, 1);
+
+    assert!(app.world().get_entity(quest).is_err());
+    assert_eq!(
+        last_notification(&app),
+        "The Iron Wolves claimed The Dragon's Lair!"
+    );
+}
+
+#[test]
+fn rival_guilds_claim_quests_leaves_unclaimed_quests() {
+    let mut app = make_test_app();
+    app.insert_resource(RivalGuilds(vec![RivalGuild {
+        name: "The Iron Wolves".to_string(),
+        claim_probability_per_turn: 0.5,
+        strength: 1,
+    }]));
+    app.add_systems(
+        Update,
+        (grow_rival_guilds, rival_guilds_claim_quests).chain(),
+    );
+    // The rival claims one quest and misses the other
+    install_mock_rng(&mut app, vec![true, false]);
+    spawn_quest(&mut app, 1, 10);
+    spawn_quest(&mut app, 1, 10);
+    // Quests in progress can't be claimed
+    let in_progress = spawn_quest(&mut app, 1, 10);
+    app.world_mut()
+        .entity_mut(in_progress)
+        .remove::<QuestStatusAvailable>()
+        .insert(QuestStatusInProgress);
+
+    send_turns(&mut app, 1);
+
+    assert_eq!(count_available_quests(&mut app), 1);
+    assert!(app.world().get_entity(in_progress).is_ok());
+}
+
+#[test]
+fn rival_guilds_claim_quests_rolls_once_per_turn() {
+    let mut app = make_test_app();
+    app.insert_resource(RivalGuilds(vec![RivalGuild {
+        name: "The Iron Wolves".to_string(),
+        claim_probability_per_turn: 0.5,
+        strength: 1,
+    }]));
+    app.add_systems(
+        Update,
+        (grow_rival_guilds, rival_guilds_claim_quests).chain(),
+    );
+    // The rival misses the quest for two turns, then claims it on the third
+    install_mock_rng(&mut app, vec![false, false, true]);
+    let quest = spawn_quest(&mut app, 1, 10);
+
+    send_turns(&mut app, 3);
+
+    assert!(app.world().get_entity(quest).is_err());
+}
+
+// Every so many turns the guild goes without advancing its rank, the rival guilds grow stronger.
+fn grow_rival_guilds(
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    rank: Res<GuildRank>,
+    mut growth: ResMut<RivalGrowth>,
+    mut rivals: ResMut<RivalGuilds>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let growth_interval = 50; // Turns without advancing rank before the rivals grow stronger
+    let turn_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    if turn_delta == 0 {
+        return;
+    }
+    if rank.0 != growth.last_rank {
+        growth.last_rank = rank.0;
+        growth.turns_without_advance = 0;
+    }
+    growth.turns_without_advance += turn_delta;
+    while growth.turns_without_advance >= growth_interval {
+        growth.turns_without_advance -= growth_interval;
+        for rival in rivals.0.iter_mut() {
+            rival.strength += 1;
+        }
+        ev_notify.write(NotificationEvent(
+            "The rival guilds have grown stronger".to_string(),
+        ));
+    }
+}
+
+#[test]
+fn grow_rival_guilds_grows_while_rank_stalls() {
+    // No chance of claims, so only growth is tested
+    let mut app = make_test_app();
+    app.insert_resource(RivalGuilds(vec![RivalGuild {
+        name: "The Iron Wolves".to_string(),
+        claim_probability_per_turn: 0.0,
+        strength: 1,
+    }]));
+    app.add_systems(
+        Update,
+        (grow_rival_guilds, rival_guilds_claim_quests).chain(),
+    );
+    let advance_turns = |app: &mut App, turns: u32| {
+        for _ in 0..turns {
+            send_turns(app, 1);
+        }
+        app.world().resource::<RivalGuilds>().0[0].strength
+    };
+
+    assert_eq!(advance_turns(&mut app, 49), 1);
+    assert_eq!(advance_turns(&mut app, 1), 2);
+
+    // Advancing the guild's rank starts the count again
+    advance_turns(&mut app, 10);
+    app.insert_resource(GuildRank(2));
+    assert_eq!(advance_turns(&mut app, 49), 2);
+    assert_eq!(advance_turns(&mut app, 1), 3);
+}
+
+// The guild's reputation grows with each successful quest, and suffers more from each failure.
+fn update_guild_reputation(
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    mut reputation: ResMut<GuildReputation>,
+) {
+    let success_reputation = 5; // Reputation gained for a successful quest
+    let failure_reputation = -10; // Reputation lost for a failed quest
+    for event in ev_quest_complete.read() {
+        reputation.0 += if event.is_successful {
+            success_reputation
+        } else {
+            failure_reputation
+        };
+    }
+}
+
+#[test]
+fn update_guild_reputation_changes_with_quest_outcome() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_guild_reputation);
+
+    send_quest_complete(&mut app, vec![], true);
+    assert_eq!(app.world().resource::<GuildReputation>().0, 5);
+
+    send_quest_complete(&mut app, vec![], false);
+    assert_eq!(app.world().resource::<GuildReputation>().0, -5);
+
+    send_quest_complete(&mut app, vec![], true);
+    send_quest_complete(&mut app, vec![], true);
+    assert_eq!(app.world().resource::<GuildReputation>().0, 5);
+}
+
+// The guild's spirits rise with each successful quest, and fall further with each failure.
+// The guild hears about it each time its spirits sink low or soar high, and when they return to normal.
+fn update_guild_morale(
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    mut morale: ResMut<GuildMorale>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let success_morale = 5; // Morale gained for a successful quest
+    let failure_morale = -10; // Morale lost for a failed quest
+    let low_morale = 25; // Morale below this is low
+    let high_morale = 75; // Morale above this is high
+    for event in ev_quest_complete.read() {
+        let old_morale = morale.0;
+        let change = if event.is_successful {
+            success_morale
+        } else {
+            failure_morale
+        };
+        morale.0 = (old_morale + change).clamp(0, 100);
+        let message = if old_morale >= low_morale && morale.0 < low_morale {
+            "Guild morale has sunk low, and quests will pay less"
+        } else if old_morale < low_morale && morale.0 >= low_morale {
+            "Guild morale has recovered"
+        } else if old_morale <= high_morale && morale.0 > high_morale {
+            "The guild is celebrating its successes, and quests will pay more"
+        } else if old_morale > high_morale && morale.0 <= high_morale {
+            "The guild's celebrations have died down"
+        } else {
+            continue;
+        };
+        ev_notify.write(NotificationEvent(message.to_string()));
+    }
+}
+
+#[cfg(test)]
+fn read_guild_morale_test_notifications(app: &mut App, outcomes: &[bool]) -> Vec<String> {
+    for is_successful in outcomes {
+        send_quest_complete(app, vec![], *is_successful);
+    }
+    let notification_events = app.world().resource::<Events<NotificationEvent>>();
+    let mut reader = notification_events.get_cursor();
+    reader
+        .read(notification_events)
+        .map(|notification| notification.0.clone())
+        .collect()
+}
+
+#[test]
+fn update_guild_morale_notifies_once_when_crossing_low_morale() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_guild_morale);
+    app.insert_resource(GuildMorale(35));
+
+    // 35 -> 25 stays at the threshold, and 25 -> 15 crosses below it, then 15 -> 5 stays below
+    let notifications = read_guild_morale_test_notifications(&mut app, &[false, false, false]);
+    assert_eq!(
+        notifications,
+        vec!["Guild morale has sunk low, and quests will pay less"]
+    );
+    assert_eq!(app.world().resource::<GuildMorale>().0, 5);
+
+    // 5 -> 0 is clamped, then climbing back crosses 25 once
+    app.world_mut()
+        .resource_mut::<Events<NotificationEvent>>()
+        .clear();
+    let notifications = read_guild_morale_test_notifications(
+        &mut app,
+        &[false, true, true, true, true, true, true],
+    );
+    assert_eq!(notifications, vec!["Guild morale has recovered"]);
+    assert_eq!(app.world().resource::<GuildMorale>().0, 30);
+}
+
+#[test]
+fn update_guild_morale_notifies_once_when_crossing_high_morale() {
+    let mut app = make_test_app();
+    app.add_systems(Update, update_guild_morale);
+    app.insert_resource(GuildMorale(70));
+
+    // 70 -> 75 stays at the threshold, and 75 -> 80 crosses above it, then 80 -> 85 stays above
+    let notifications = read_guild_morale_test_notifications(&mut app, &[true, true, true]);
+    assert_eq!(
+        notifications,
+        vec!["The guild is celebrating its successes, and quests will pay more"]
+    );
+
+    // 85 -> 75 crosses back, and 75 -> 65 stays below
+    app.world_mut()
+        .resource_mut::<Events<NotificationEvent>>()
+        .clear();
+    let notifications = read_guild_morale_test_notifications(&mut app, &[false, false]);
+    assert_eq!(
+        notifications,
+        vec!["The guild's celebrations have died down"]
+    );
+    assert_eq!(app.world().resource::<GuildMorale>().0, 65);
+}
+
+// Successful quests needed to advance past the given rank, or None if it's the highest rank.
+fn next_rank_threshold(thresholds: &GuildRankThresholds, rank: u32) -> Option<u32> {
+    rank.checked_sub(1)
+        .and_then(|index| thresholds.0.get(index as usize))
+        .copied()
+}
+
+#[cfg(test)]
+fn make_guild_rank_test_app(thresholds: Vec<u32>) -> App {
+    let mut app = make_test_app();
+    app.insert_resource(GuildRankThresholds(thresholds));
+    app.add_systems(Update, advance_guild_rank);
+    app
+}
+
+#[test]
+fn advance_guild_rank_advances_at_threshold() {
+    let mut app = make_guild_rank_test_app(vec![2, 4]);
+
+    send_quest_complete(&mut app, vec![], true);
+    assert_eq!(app.world().resource::<GuildRank>().0, 1);
+
+    send_quest_complete(&mut app, vec![], true);
+    assert_eq!(app.world().resource::<GuildRank>().0, 2);
+    assert_eq!(
+        app.world().resource::<GuildStats>().total_successful_quests,
+        2
+    );
+
+    send_quest_complete(&mut app, vec![], true);
+    assert_eq!(app.world().resource::<GuildRank>().0, 2);
+    send_quest_complete(&mut app, vec![], true);
+    assert_eq!(app.world().resource::<GuildRank>().0, 3);
+
+    assert_eq!(last_notification(&app), "The guild has advanced to rank 3");
+}
+
+#[test]
+fn advance_guild_rank_ignores_failed_quests() {
+    let mut app = make_guild_rank_test_app(vec![1]);
+
+    send_quest_complete(&mut app, vec![], false);
+    send_quest_complete(&mut app, vec![], false);
+
+    assert_eq!(app.world().resource::<GuildRank>().0, 1);
+    assert_eq!(
+        app.world().resource::<GuildStats>().total_successful_quests,
+        0
+    );
+}
+
+#[test]
+fn advance_guild_rank_stops_at_end_of_thresholds() {
+    let mut app = make_guild_rank_test_app(vec![1]);
+
+    for _ in 0..5 {
+        send_quest_complete(&mut app, vec![], true);
+    }
+
+    // There is no threshold for leaving rank 2, so the guild stays there
+    assert_eq!(app.world().resource::<GuildRank>().0, 2);
+    assert_eq!(
+        app.world().resource::<GuildStats>().total_successful_quests,
+        5
+    );
+
+    // The guild can't pass the configured max rank, even with thresholds to spare
+    let mut app = make_guild_rank_test_app(vec![1, 2, 3]);
+    app.world_mut().resource_mut::<GuildConfig>().max_rank = 2;
+    for _ in 0..3 {
+        send_quest_complete(&mut app, vec![], true);
+    }
+    assert_eq!(app.world().resource::<GuildRank>().0, 2);
+
+    // An empty or nonsensical rank is handled too
+    let mut app = make_guild_rank_test_app(vec![]);
+    app.insert_resource(GuildRank(0));
+    send_quest_complete(&mut app, vec![], true);
+    assert_eq!(app.world().resource::<GuildRank>().0, 0);
+}
+
+// Once a quest is complete, tell the story of each milestone the guild has newly reached, and pay out its reward.
+fn check_milestones(
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    stats: Res<GuildStats>,
+    mut chapters: ResMut<ChapterConfig>,
+    mut guild: ResMut<Guild>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    if ev_quest_complete.read().count() == 0 {
+        return;
+    }
+    let ChapterConfig {
+        milestones,
+        completed_milestones,
+    } = &mut *chapters;
+    for milestone in milestones.iter() {
+        if stats.total_successful_quests < milestone.quests_required
+            || !completed_milestones.insert(milestone.quests_required)
+        {
+            continue;
+        }
+        guild.gold = guild.gold.saturating_add(u64::from(milestone.gold_reward));
+        ev_notify.write(NotificationEvent(format!(
+            "{}: {} The guild is rewarded with {} gold.",
+            milestone.title, milestone.narrative, milestone.gold_reward
+        )));
+    }
+}
+
+#[test]
+fn check_milestones_fires_at_quest_count() {
+    // The default chapter's first milestones come at 1 and 10 quests, paying 50 and 200 gold
+    let mut app = make_test_app();
+    app.add_systems(Update, check_milestones);
+
+    send_quest_complete(&mut app, vec![], true);
+    assert_eq!(app.world().resource::<Guild>().gold, 0);
+    assert!(
+        app.world()
+            .resource::<ChapterConfig>()
+            .completed_milestones
+            .is_empty()
+    );
+
+    app.world_mut()
+        .resource_mut::<GuildStats>()
+        .total_successful_quests = 1;
+    send_quest_complete(&mut app, vec![], false);
+    assert_eq!(app.world().resource::<Guild>().gold, 50);
+
+    assert_eq!(
+        last_notification(&app),
+        "A Humble Beginning: Word spreads that a new guild has finished its first job. The guild is rewarded with 50 gold."
+    );
+}
+
+#[test]
+fn check_milestones_counts_only_successful_quests() {
+    let mut app = make_test_app();
+    app.add_systems(Update, (advance_guild_rank, check_milestones).chain());
+
+    send_quest_complete(&mut app, vec![], false);
+    assert_eq!(app.world().resource::<Guild>().gold, 0);
+
+    send_quest_complete(&mut app, vec![], true);
+    assert_eq!(app.world().resource::<Guild>().gold, 50);
+}
+
+#[test]
+fn check_milestones_fires_each_milestone_once() {
+    // The default chapter's first milestones come at 1 and 10 quests, paying 50 and 200 gold
+    let mut app = make_test_app();
+    app.add_systems(Update, check_milestones);
+
+    // Crossing several thresholds at once rewards each of them
+    app.world_mut()
+        .resource_mut::<GuildStats>()
+        .total_successful_quests = 10;
+    send_quest_complete(&mut app, vec![], true);
+    assert_eq!(app.world().resource::<Guild>().gold, 250);
+
+    // Checking again at the same count doesn't pay out twice
+    send_quest_complete(&mut app, vec![], true);
+    send_quest_complete(&mut app, vec![], true);
+    assert_eq!(app.world().resource::<Guild>().gold, 250);
+    assert_eq!(
+        app.world().resource::<ChapterConfig>().completed_milestones,
+        HashSet::from([1, 10])
+    );
+
+    // Nothing happens without a completed quest
+    app.world_mut()
+        .resource_mut::<ChapterConfig>()
+        .completed_milestones
+        .clear();
+    app.update();
+    assert_eq!(app.world().resource::<Guild>().gold, 250);
+}
+
+// Each time the QuestGenerator's timer completes, spawn a batch of new quests around the guild's average hero level.
+// Higher guild ranks allow harder quests. The batch is cut short if it would overfill the quest board.
+fn generate_quests(
+    mut commands: Commands,
+    mut ev_turn_timer_complete: EventReader<TurnTimerCompleteEvent>,
+    generators_query: Query<(), With<QuestGenerator>>,
+    available_quests_query: Query<(), (With<Quest>, With<QuestStatusAvailable>)>,
+    config: Res<QuestGenerationConfig>,
+    board_config: Res<QuestBoardConfig>,
+    guild_hall: Res<GuildHallLevel>,
+    stats: Res<GuildStats>,
+    rank: Res<GuildRank>,
+    tutorial: Res<TutorialMode>,
+    mut random_src: ResMut<RandomSource>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for TurnTimerCompleteEvent(entity) in ev_turn_timer_complete.read() {
+        // The tutorial posts its own quest
+        if generators_query.contains(*entity) && !tutorial.enabled {
+            let board_space = (board_config.max_available_quests + guild_hall.quest_board_bonus())
+                .saturating_sub(available_quests_query.iter().count());
+            if board_space == 0 {
+                ev_notify.write(NotificationEvent(
+                    "Quest board is full, so no new quests were posted".to_string(),
+                ));
+                continue;
+            }
+
+            let rng = &mut random_src.0;
+            let batch_size = rng
+                .random_range(config.min_per_batch..=config.max_per_batch)
+                .min(board_space as u32);
+            // Each rank above the first unlocks quests one level harder
+            let difficulty_offset_range = *config.difficulty_offset_range.start()
+                ..=*config.difficulty_offset_range.end() + rank.0.saturating_sub(1) as i32;
+            for _ in 0..batch_size {
+                let offset = rng.random_range(difficulty_offset_range.clone());
+                let difficulty_level =
+                    (stats.average_hero_level as i32 + offset).clamp(1, 10) as u32;
+                let turns_to_expiry = 10;
+                let quest_type = *[
+                    QuestType::Dungeon,
+                    QuestType::Escort,
+                    QuestType::Investigation,
+                    QuestType::Bounty,
+                    QuestType::TradeRoute,
+                ]
+                .choose(rng)
+                .unwrap();
+                // Some quests aren't for any faction
+                let faction = *[
+                    None,
+                    Some(Faction::Order),
+                    Some(Faction::Chaos),
+                    Some(Faction::Nature),
+                    Some(Faction::Arcane),
+                ]
+                .choose(rng)
+                .unwrap();
+                let alignment_affinity = *[
+                    None,
+                    Some(Alignment::Law),
+                    Some(Alignment::Neutral),
+                    Some(Alignment::Chaos),
+                ]
+                .choose(rng)
+                .unwrap();
+                let mut description = QuestDescriptionBuilder::new()
+                    .name(generate_quest_name(rng))
+                    .difficulty(difficulty_level)
+                    .exp(50 * difficulty_level)
+                    .expiry(turns_to_expiry)
+                    .quest_type(quest_type)
+                    .build();
+                let gold_per_level =
+                    rng.random_range(description.difficulty_category().gold_per_level_range());
+                description.gold_reward = gold_per_level * difficulty_level;
+                description.faction = faction;
+                description.alignment_affinity = alignment_affinity;
+                if config.item_reward_chance.distribution().sample(rng) {
+                    let class = *ALL_CLASSES.choose(rng).unwrap();
+                    description.item_reward = Some(Item {
+                        class,
+                        rarity: random_item_rarity(rng),
+                    });
+                }
+                let is_boss = guild_hall.boss_quests_enabled()
+                    && config.boss_quest_chance.distribution().sample(rng);
+                if is_boss {
+                    let boss_difficulty_bonus = 2; // Boss quests are this many levels harder
+                    let boss_reward_multiplier = 2; // Boss quests pay this many times the usual rewards
+                    description.difficulty_level =
+                        (description.difficulty_level + boss_difficulty_bonus).min(10);
+                    description.exp_reward *= boss_reward_multiplier;
+                    description.gold_reward *= boss_reward_multiplier;
+                }
+                let mut quest = commands.spawn(QuestBundle {
+                    marker: Quest,
+                    description,
+                    progress: TurnTimer::new(turns_to_expiry),
+                    status: QuestStatusAvailable,
+                });
+                if is_boss {
+                    quest.insert(BossQuest);
+                }
+            }
+            ev_notify.write(NotificationEvent(format!(
+                "{} new quests are available",
+                batch_size
+            )));
+        }
+    }
+}
+
+#[test]
+fn generate_quests_spawns_batches_within_bounds() {
+    let mut app = make_test_app();
+    app.insert_resource(GuildStats {
+        average_hero_level: 5,
+        ..default()
+    });
+    app.insert_resource(QuestGenerationConfig {
+        cadence_turns: 2,
+        min_per_batch: 1,
+        max_per_batch: 3,
+        difficulty_offset_range: -2..=1,
+        ..default()
+    });
+    app.add_systems(Update, (advance_turn_timer, generate_quests).chain());
+
+    let generator = app
+        .world_mut()
+        .spawn((QuestGenerator, TurnTimer::repeating(2)))
+        .id();
+
+    // Advance 6 turns, one at a time, for 3 generation cycles
+    for _ in 0..6 {
+        send_turns(&mut app, 1);
+    }
+
+    let mut quests_query = app
+        .world_mut()
+        .query_filtered::<&QuestDescription, (With<Quest>, With<QuestStatusAvailable>)>();
+    let descriptions: Vec<QuestDescription> = quests_query.iter(app.world()).cloned().collect();
+    assert!(descriptions.len() >= 3);
+    assert!(descriptions.len() <= 9);
+    for description in descriptions.iter() {
+        assert!((3..=6).contains(&description.difficulty_level));
+        let gold_per_level_range = description.difficulty_category().gold_per_level_range();
+        assert!(
+            gold_per_level_range
+                .contains(&(description.gold_reward / description.difficulty_level))
+        );
+        assert_eq!(description.gold_reward % description.difficulty_level, 0);
+    }
+
+    // Check that the generator's timer was restarted for the next cycle
+    let timer = app.world().get::<TurnTimer>(generator).unwrap();
+    assert_eq!(timer.turns_remaining, 2);
+}
+
+// Set up an app with a quest board holding max_available_quests of 5, already holding some quests, and run one generation tick which always rolls 3 quests.
+#[cfg(test)]
+fn run_quest_board_test_tick(existing_quests: usize) -> App {
+    let mut app = make_test_app();
+    app.insert_resource(QuestGenerationConfig {
+        min_per_batch: 3,
+        max_per_batch: 3,
+        ..default()
+    });
+    app.insert_resource(QuestBoardConfig {
+        max_available_quests: 5,
+    });
+    app.add_systems(Update, generate_quests);
+
+    for _ in 0..existing_quests {
+        app.world_mut().spawn((Quest, QuestStatusAvailable));
+    }
+    let generator = app
+        .world_mut()
+        .spawn((
+            QuestGenerator,
+            TurnTimer {
+                initial_value: 10,
+                turns_remaining: 0,
+                timer_mode: TimerMode::Once,
+            },
+        ))
+        .id();
+
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(generator));
+    app.update();
+    app
+}
+
+#[cfg(test)]
+fn count_available_quests(app: &mut App) -> usize {
+    app.world_mut()
+        .query_filtered::<(), (With<Quest>, With<QuestStatusAvailable>)>()
+        .iter(app.world())
+        .count()
+}
+
+#[test]
+fn generate_quests_skips_batch_when_board_is_full() {
+    let mut app = run_quest_board_test_tick(5);
+    assert_eq!(count_available_quests(&mut app), 5);
+
+    assert_eq!(
+        last_notification(&app),
+        "Quest board is full, so no new quests were posted"
+    );
+}
+
+#[test]
+fn generate_quests_fills_remaining_board_space() {
+    let mut app = run_quest_board_test_tick(4);
+    assert_eq!(count_available_quests(&mut app), 5);
+}
+
+#[test]
+fn generate_quests_grows_board_with_guild_hall_level() {
+    let mut app = make_test_app();
+    app.insert_resource(GuildHallLevel(2));
+    app.insert_resource(QuestGenerationConfig {
+        min_per_batch: 3,
+        max_per_batch: 3,
+        ..default()
+    });
+    app.insert_resource(QuestBoardConfig {
+        max_available_quests: 5,
+    });
+    app.add_systems(Update, generate_quests);
+    for _ in 0..4 {
+        app.world_mut().spawn((Quest, QuestStatusAvailable));
+    }
+    let generator = app
+        .world_mut()
+        .spawn((QuestGenerator, TurnTimer::new(10)))
+        .id();
+
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(generator));
+    app.update();
+
+    assert_eq!(count_available_quests(&mut app), 6);
+}
+
+#[test]
+fn generate_quests_posts_boss_quests_once_unlocked() {
+    let count_boss_quests = |guild_hall_level: u32| {
+        let mut app = make_test_app();
+        app.insert_resource(GuildHallLevel(guild_hall_level));
+        app.insert_resource(QuestGenerationConfig {
+            min_per_batch: 3,
+            max_per_batch: 3,
+            boss_quest_chance: Percent(100),
+            ..default()
+        });
+        app.add_systems(Update, generate_quests);
+        let generator = app
+            .world_mut()
+            .spawn((QuestGenerator, TurnTimer::new(10)))
+            .id();
+
+        app.world_mut()
+            .resource_mut::<Events<TurnTimerCompleteEvent>>()
+            .send(TurnTimerCompleteEvent(generator));
+        app.update();
+
+        app.world_mut()
+            .query_filtered::<(), (With<Quest>, With<BossQuest>)>()
+            .iter(app.world())
+            .count()
+    };
+
+    assert_eq!(count_boss_quests(4), 0);
+    assert_eq!(count_boss_quests(5), 3);
+}
+
+#[test]
+fn generate_quests_posts_full_batch_on_empty_board() {
+    let mut app = run_quest_board_test_tick(0);
+    assert_eq!(count_available_quests(&mut app), 3);
+}
+
+#[test]
+fn generate_quests_unlocks_harder_quests_at_higher_rank() {
+    let mut app = make_test_app();
+    app.insert_resource(GuildRank(4));
+    app.insert_resource(QuestGenerationConfig {
+        min_per_batch: 10,
+        max_per_batch: 10,
+        difficulty_offset_range: 0..=0,
+        ..default()
+    });
+    app.add_systems(Update, generate_quests);
+
+    let generator = app
+        .world_mut()
+        .spawn((
+            QuestGenerator,
+            TurnTimer {
+                initial_value: 10,
+                turns_remaining: 0,
+                timer_mode: TimerMode::Once,
+            },
+        ))
+        .id();
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(generator));
+    app.update();
+
+    // At rank 4, quests can be up to 3 levels above the average hero level of 1
+    let mut quests_query = app
+        .world_mut()
+        .query_filtered::<&QuestDescription, (With<Quest>, With<QuestStatusAvailable>)>();
+    let difficulties: Vec<u32> = quests_query
+        .iter(app.world())
+        .map(|description| description.difficulty_level)
+        .collect();
+    assert_eq!(difficulties.len(), 10);
+    assert!(difficulties.iter().all(|level| (1..=4).contains(level)));
+    assert!(difficulties.iter().any(|level| *level > 1));
+}
+
+// Each time the RecruitGenerator's timer completes, spawn a random recruit who waits to be hired.
+// A more reputable guild attracts stronger recruits.
+fn generate_recruits(
+    mut commands: Commands,
+    mut ev_turn_timer_complete: EventReader<TurnTimerCompleteEvent>,
+    generators_query: Query<(), With<RecruitGenerator>>,
+    config: Res<RecruitmentConfig>,
+    level_config: Res<LevelConfig>,
+    reputation: Res<GuildReputation>,
+    tutorial: Res<TutorialMode>,
+    mut random_src: ResMut<RandomSource>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for TurnTimerCompleteEvent(entity) in ev_turn_timer_complete.read() {
+        // The tutorial provides its own hero
+        if generators_query.contains(*entity) && !tutorial.enabled {
+            let rng = &mut random_src.0;
+            let class = *ALL_CLASSES.choose(rng).unwrap();
+            let personality = *[
+                Personality::Friendly,
+                Personality::ResultOriented,
+                Personality::Mirror,
+                Personality::Judgmental,
+                Personality::Learner,
+                Personality::Teacher,
+                Personality::Brave,
+                Personality::Cowardly,
+            ]
+            .choose(rng)
+            .unwrap();
+            let faction = *[
+                Faction::Order,
+                Faction::Chaos,
+                Faction::Nature,
+                Faction::Arcane,
+            ]
+            .choose(rng)
+            .unwrap();
+            let alignment = *[Alignment::Law, Alignment::Neutral, Alignment::Chaos]
+                .choose(rng)
+                .unwrap();
+            let level = rng.random_range(recruit_level_range(reputation.0));
+            let name = generate_hero_name(rng);
+            // Rogues always come as their archetype, whatever personality was rolled
+            let archetype = match class {
+                HeroClass::Rogue => HeroBundle::rogue_archetype(name.clone()),
+                _ => HeroBundle {
+                    name: HeroName(name.clone()),
+                    class,
+                    person: Person {
+                        personality,
+                        relationships: HashMap::new(),
+                    },
+                    ..default()
+                },
+            };
+            let recruit = commands
+                .spawn((
+                    HeroBundle {
+                        level: LevelState {
+                            level,
+                            exp: 0,
+                            exp_to_next: exp_required_for_level(&level_config, level),
+                        },
+                        alignment,
+                        portrait: HeroPortrait::random_for(&class, rng),
+                        ..archetype
+                    },
+                    HeroFaction(faction),
+                    HeroStatusAvailable,
+                    TurnTimer::new(config.turns_to_expiry),
+                ))
+                .id();
+            ev_notify.write(NotificationEvent(format!(
+                "A level {} {}, {}, wants to join the guild: entity {:?}",
+                level, class, name, recruit
+            )));
+        }
+    }
+}
+
+#[test]
+fn generate_recruits_spawns_available_hero() {
+    let mut app = make_test_app();
+    app.add_systems(Update, generate_recruits);
+
+    let generator = app
+        .world_mut()
+        .spawn((
+            RecruitGenerator,
+            TurnTimer {
+                initial_value: 15,
+                turns_remaining: 0,
+                timer_mode: TimerMode::Once,
+            },
+        ))
+        .id();
+
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(generator));
+    app.update();
+
+    let mut recruits_query = app
+        .world_mut()
+        .query_filtered::<(&LevelState, &TurnTimer), (With<Hero>, With<HeroStatusAvailable>)>();
+    let recruits: Vec<(&LevelState, &TurnTimer)> = recruits_query.iter(app.world()).collect();
+    assert_eq!(recruits.len(), 1);
+    let (level, timer) = recruits[0];
+    assert!((1..=3).contains(&level.level));
+    assert_eq!(timer.turns_remaining, 10);
+}
+
+#[test]
+fn generate_recruits_spawns_rogues_as_their_archetype() {
+    let mut app = make_test_app();
+    app.add_systems(Update, generate_recruits);
+    let generator = app
+        .world_mut()
+        .spawn((RecruitGenerator, TurnTimer::new(15)))
+        .id();
+
+    for _ in 0..50 {
+        app.world_mut()
+            .resource_mut::<Events<TurnTimerCompleteEvent>>()
+            .send(TurnTimerCompleteEvent(generator));
+    }
+    app.update();
+
+    let mut recruits_query = app
+        .world_mut()
+        .query_filtered::<(&HeroClass, &Person), (With<Hero>, With<HeroStatusAvailable>)>();
+    let rogues: Vec<&Person> = recruits_query
+        .iter(app.world())
+        .filter(|(class, _)| **class == HeroClass::Rogue)
+        .map(|(_, person)| person)
+        .collect();
+    assert!(!rogues.is_empty());
+    assert!(
+        rogues
+            .iter()
+            .all(|person| matches!(person.personality, Personality::Judgmental))
+    );
+}
+
+// The achievements a guild can earn, with what it takes to earn each one.
+fn achievement_definitions() -> Vec<AchievementDefinition> {
+    let achievement =
+        |id, description: &str, condition: fn(&World) -> bool, reward| AchievementDefinition {
+            id,
+            description: description.to_string(),
+            condition,
+            reward,
+        };
+    vec![
+        achievement(
+            AchievementId::FirstQuestCompleted,
+            "Complete a quest successfully",
+            |world| world.resource::<GuildStats>().total_successful_quests >= 1,
+            AchievementReward::Gold(100),
+        ),
+        achievement(
+            AchievementId::FirstHeroDeath,
+            "Lose a hero in the line of duty",
+            |world| !world.resource::<FallenHeroes>().0.is_empty(),
+            AchievementReward::MoraleBoost(10),
+        ),
+        achievement(
+            AchievementId::FirstLevelFive,
+            "Have a hero reach level 5",
+            |world| {
+                world.iter_entities().any(|entity| {
+                    entity.contains::<Hero>()
+                        && !entity.contains::<HeroStatusAvailable>()
+                        && entity
+                            .get::<LevelState>()
+                            .is_some_and(|level| level.level >= 5)
+                })
+            },
+            AchievementReward::Reputation(5),
+        ),
+        achievement(
+            AchievementId::GoldHoard,
+            "Earn 1000 gold from quests",
+            |world| world.resource::<GuildStats>().total_gold_earned >= 1000,
+            AchievementReward::Reputation(5),
+        ),
+        achievement(
+            AchievementId::FiveHeroesHired,
+            "Hire 5 heroes",
+            |world| world.resource::<GuildStats>().total_heroes_hired >= 5,
+            AchievementReward::Gold(200),
+        ),
+        achievement(
+            AchievementId::TenQuestsSucceeded,
+            "Succeed at 10 quests",
+            |world| world.resource::<GuildStats>().total_successful_quests >= 10,
+            AchievementReward::Reputation(10),
+        ),
+        achievement(
+            AchievementId::FirstRetirement,
+            "See a hero retire",
+            |world| !world.resource::<RetiredHeroes>().0.is_empty(),
+            AchievementReward::MoraleBoost(10),
+        ),
+        achievement(
+            AchievementId::FirstBuilding,
+            "Finish constructing a building",
+            |world| {
+                world.iter_entities().any(|entity| {
+                    entity.contains::<GuildBuilding>() && entity.contains::<Operational>()
+                })
+            },
+            AchievementReward::Gold(100),
+        ),
+        achievement(
+            AchievementId::FirstTradeRoute,
+            "Establish a trade route",
+            |world| {
+                world
+                    .iter_entities()
+                    .any(|entity| entity.contains::<TradeRouteActive>())
+            },
+            AchievementReward::Gold(100),
+        ),
+        achievement(
+            AchievementId::RankFive,
+            "Reach guild rank 5",
+            |world| world.resource::<GuildRank>().0 >= 5,
+            AchievementReward::MoraleBoost(20),
+        ),
+    ]
+}
+
+// Each turn, complete any achievement whose condition is now met, and grant its reward.
+// An exclusive system, so each condition can look at whatever it needs in the World.
+fn check_achievements(world: &mut World) {
+    let achievements = world.resource::<Achievements>();
+    let newly_completed: Vec<AchievementDefinition> = achievement_definitions()
+        .into_iter()
+        .filter(|achievement| {
+            !achievements
+                .0
+                .get(&achievement.id)
+                .copied()
+                .unwrap_or(false)
+        })
+        .filter(|achievement| (achievement.condition)(world))
+        .collect();
+    for achievement in newly_completed {
+        world
+            .resource_mut::<Achievements>()
+            .0
+            .insert(achievement.id, true);
+        let reward = match achievement.reward {
+            AchievementReward::Gold(gold) => {
+                let mut guild = world.resource_mut::<Guild>();
+                guild.gold = guild.gold.saturating_add(gold);
+                format!("{} gold", gold)
+            }
+            AchievementReward::Reputation(reputation) => {
+                world.resource_mut::<GuildReputation>().0 += reputation;
+                format!("{} reputation", reputation)
+            }
+            AchievementReward::MoraleBoost(boost) => {
+                let mut heroes_query = world
+                    .query_filtered::<&mut Morale, (With<Hero>, Without<HeroStatusAvailable>)>();
+                for mut morale in heroes_query.iter_mut(world) {
+                    morale.0 = (morale.0 + boost).clamp(0, 100);
+                }
+                format!("{} morale for every hero", boost)
+            }
+        };
+        world.send_event(NotificationEvent(format!(
+            "Achievement unlocked: {}! Reward: {}",
+            achievement.description, reward
+        )));
+    }
+}
+
+#[cfg(test)]
+fn completed_achievements(app: &App) -> Vec<AchievementId> {
+    app.world()
+        .resource::<Achievements>()
+        .0
+        .iter()
+        .filter(|(_, completed)| **completed)
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+#[test]
+fn check_achievements_triggers_each_achievement_once() {
+    let setups: [(AchievementId, fn(&mut World)); 10] = [
+        (AchievementId::FirstQuestCompleted, |world| {
+            world.resource_mut::<GuildStats>().total_successful_quests = 1;
+        }),
+        (AchievementId::FirstHeroDeath, |world| {
+            world
+                .resource_mut::<FallenHeroes>()
+                .0
+                .push(FallenHeroRecord {
+                    name: "Aldric the Steadfast".to_string(),
+                    level: 3,
+                    class: HeroClass::Warrior,
+                    quest_count: QuestCount::default(),
+                    biography: Biography::default(),
+                });
+        }),
+        (AchievementId::FirstLevelFive, |world| {
+            world.spawn((
+                Hero,
+                LevelState {
+                    level: 5,
+                    ..default()
+                },
+            ));
+        }),
+        (AchievementId::GoldHoard, |world| {
+            world.resource_mut::<GuildStats>().total_gold_earned = 1000;
+        }),
+        (AchievementId::FiveHeroesHired, |world| {
+            world.resource_mut::<GuildStats>().total_heroes_hired = 5;
+        }),
+        (AchievementId::TenQuestsSucceeded, |world| {
+            world.resource_mut::<GuildStats>().total_successful_quests = 10;
+        }),
+        (AchievementId::FirstRetirement, |world| {
+            world
+                .resource_mut::<RetiredHeroes>()
+                .0
+                .push(RetiredHeroRecord {
+                    name: "Aldric the Steadfast".to_string(),
+                    final_level: 10,
+                });
+        }),
+        (AchievementId::FirstBuilding, |world| {
+            world.spawn((
+                GuildBuilding {
+                    building_type: BuildingType::Treasury,
+                },
+                Operational,
+            ));
+        }),
+        (AchievementId::FirstTradeRoute, |world| {
+            world.spawn(TradeRouteActive { gold_per_turn: 10 });
+        }),
+        (AchievementId::RankFive, |world| {
+            world.insert_resource(GuildRank(5));
+        }),
+    ];
+    assert_eq!(setups.len(), achievement_definitions().len());
+
+    for (id, setup) in setups {
+        let mut app = make_test_app();
+        app.add_systems(
+            Update,
+            check_achievements.run_if(on_event::<TurnDeltaEvent>),
+        );
+        let mut reader = app
+            .world()
+            .resource::<Events<NotificationEvent>>()
+            .get_cursor();
+
+        // A new guild hasn't earned anything
+        send_turns(&mut app, 1);
+        assert!(completed_achievements(&app).is_empty());
+
+        setup(app.world_mut());
+        send_turns(&mut app, 1);
+        send_turns(&mut app, 1);
+        assert_eq!(completed_achievements(&app), vec![id]);
+        let notification_events = app.world().resource::<Events<NotificationEvent>>();
+        assert_eq!(reader.read(notification_events).count(), 1);
+    }
+}
+
+#[test]
+fn check_achievements_grants_rewards() {
+    let mut app = make_test_app();
+    app.add_systems(
+        Update,
+        check_achievements.run_if(on_event::<TurnDeltaEvent>),
+    );
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    let recruit = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.world_mut()
+        .entity_mut(recruit)
+        .insert(HeroStatusAvailable);
+
+    app.world_mut()
+        .resource_mut::<GuildStats>()
+        .total_successful_quests = 1;
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().resource::<Guild>().gold, 100);
+    assert_eq!(
+        last_notification(&app),
+        "Achievement unlocked: Complete a quest successfully! Reward: 100 gold"
+    );
+
+    app.world_mut()
+        .resource_mut::<GuildStats>()
+        .total_gold_earned = 1000;
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().resource::<GuildReputation>().0, 5);
+
+    // Only heroes in the guild are cheered up
+    app.insert_resource(GuildRank(5));
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().get::<Morale>(hero).unwrap().0, 70);
+    assert_eq!(app.world().get::<Morale>(recruit).unwrap().0, 50);
+
+    // Nothing is checked without a turn passing
+    app.world_mut().resource_mut::<Achievements>().0.clear();
+    app.update();
+    assert_eq!(app.world().resource::<Guild>().gold, 100);
+}
+
+// Move the tutorial along as the player posts, starts and finishes their first quest, with a hint for each step.
+fn run_tutorial_step(
+    mut commands: Commands,
+    mut tutorial: ResMut<TutorialMode>,
+    mut ev_quest_complete: EventReader<QuestCompleteEvent>,
+    in_progress_query: Query<(), (With<Quest>, With<QuestStatusInProgress>)>,
+    level_config: Res<LevelConfig>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let quest_completed = ev_quest_complete.read().count() > 0;
+    if !tutorial.enabled {
+        return;
+    }
+    let (next_step, hint) = match tutorial.step {
+        0 => {
+            let description = QuestDescriptionBuilder::new()
+                .name("Rats in the Cellar".to_string())
+                .difficulty(1)
+                .turns(2)
+                .exp(50)
+                .gold(50)
+                .expiry(100) // Plenty of time to learn
+                .build();
+            commands.spawn(QuestBundle {
+                marker: Quest,
+                progress: TurnTimer::new(description.turns_to_expiry),
+                description,
+                status: QuestStatusAvailable,
+            });
+            commands.spawn(HeroBundle {
+                name: HeroName("Pip the Apprentice".to_string()),
+                level: LevelState {
+                    level: 1,
+                    exp: 0,
+                    exp_to_next: exp_required_for_level(&level_config, 1),
+                },
+                ..default()
+            });
+            (
+                1,
+                "You have an available quest! Send heroes to complete it.",
+            )
+        }
+        _ if quest_completed => {
+            tutorial.enabled = false;
+            (
+                3,
+                "Your first quest is complete! From now on, new quests and recruits will arrive on their own.",
+            )
+        }
+        1 if !in_progress_query.is_empty() => (2, "Advance a turn to make progress."),
+        _ => return,
+    };
+    tutorial.step = next_step;
+    ev_notify.write(NotificationEvent(hint.to_string()));
+}
+
+#[test]
+fn run_tutorial_step_spawns_scripted_quest_and_hero() {
+    let mut app = make_test_app();
+    app.add_systems(Update, run_tutorial_step);
+    app.update();
+    // Nothing happens unless the tutorial is on
+    assert_eq!(app.world().resource::<TutorialMode>().step, 0);
+    assert_eq!(count_available_quests(&mut app), 0);
+
+    app.insert_resource(TutorialMode {
+        enabled: true,
+        step: 0,
+    });
+    app.update();
+
+    let mut quests_query = app
+        .world_mut()
+        .query_filtered::<&QuestDescription, (With<Quest>, With<QuestStatusAvailable>)>();
+    let quests: Vec<&QuestDescription> = quests_query.iter(app.world()).collect();
+    assert_eq!(quests.len(), 1);
+    assert_eq!(quests[0].name, "Rats in the Cellar");
+    assert_eq!(quests[0].difficulty_level, 1);
+
+    let mut heroes_query = app.world_mut().query_filtered::<&HeroName, With<Hero>>();
+    let heroes: Vec<&HeroName> = heroes_query.iter(app.world()).collect();
+    assert_eq!(heroes.len(), 1);
+    assert_eq!(heroes[0].0, "Pip the Apprentice");
+
+    assert_eq!(app.world().resource::<TutorialMode>().step, 1);
+    assert_eq!(
+        last_notification(&app),
+        "You have an available quest! Send heroes to complete it."
+    );
+}
+
+#[test]
+fn run_tutorial_step_progresses_through_first_quest() {
+    let mut app = make_test_app();
+    app.insert_resource(TutorialMode {
+        enabled: true,
+        step: 0,
+    });
+    app.add_systems(Update, run_tutorial_step);
+    app.update();
+
+    // Waiting around doesn't move the tutorial on until heroes are sent out
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().resource::<TutorialMode>().step, 1);
+
+    let quest = app
+        .world_mut()
+        .query_filtered::<Entity, With<Quest>>()
+        .single(app.world())
+        .unwrap();
+    app.world_mut()
+        .entity_mut(quest)
+        .remove::<QuestStatusAvailable>()
+        .insert(QuestStatusInProgress);
+    app.update();
+    assert_eq!(app.world().resource::<TutorialMode>().step, 2);
+    assert_eq!(last_notification(&app), "Advance a turn to make progress.");
+
+    send_turns(&mut app, 1);
+    // The tutorial waits for the quest to finish
+    assert_eq!(app.world().resource::<TutorialMode>().step, 2);
+
+    // Finishing the quest ends the tutorial
+    send_quest_complete(&mut app, vec![], true);
+    let tutorial = app.world().resource::<TutorialMode>();
+    assert_eq!(tutorial.step, 3);
+    assert!(!tutorial.enabled);
+    assert_eq!(
+        last_notification(&app),
+        "Your first quest is complete! From now on, new quests and recruits will arrive on their own."
+    );
+}
+
+#[test]
+fn generate_quests_and_recruits_are_bypassed_during_tutorial() {
+    let mut app = make_test_app();
+    app.insert_resource(TutorialMode {
+        enabled: true,
+        step: 1,
+    });
+    app.add_systems(Update, (generate_quests, generate_recruits));
+    let quest_generator = app
+        .world_mut()
+        .spawn((QuestGenerator, TurnTimer::repeating(1)))
+        .id();
+    let recruit_generator = app
+        .world_mut()
+        .spawn((RecruitGenerator, TurnTimer::repeating(1)))
+        .id();
+
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(quest_generator));
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(recruit_generator));
+    app.update();
+
+    assert_eq!(count_available_quests(&mut app), 0);
+    let recruits = app
+        .world_mut()
+        .query_filtered::<(), With<Hero>>()
+        .iter(app.world())
+        .count();
+    assert_eq!(recruits, 0);
+}
+
+#[test]
+fn generate_recruits_assigns_portraits_for_class() {
+    let mut app = make_test_app();
+    app.add_systems(Update, generate_recruits);
+    let generator = app
+        .world_mut()
+        .spawn((RecruitGenerator, TurnTimer::repeating(1)))
+        .id();
+
+    for _ in 0..40 {
+        app.world_mut()
+            .resource_mut::<Events<TurnTimerCompleteEvent>>()
+            .send(TurnTimerCompleteEvent(generator));
+        app.update();
+    }
+
+    let mut recruits_query = app
+        .world_mut()
+        .query_filtered::<(&HeroClass, &HeroPortrait), (With<Hero>, With<HeroStatusAvailable>)>();
+    let portraits: Vec<(HeroClass, String)> = recruits_query
+        .iter(app.world())
+        .map(|(class, portrait)| (*class, portrait.asset_path.clone()))
+        .collect();
+    assert_eq!(portraits.len(), 40);
+    for (class, path) in portraits.iter() {
+        assert!(!path.is_empty());
+        assert!(path.contains(&class.to_string().to_lowercase()));
+    }
+    // There are only a few portraits per class, so heroes end up sharing them
+    let unique_paths: HashSet<&String> = portraits.iter().map(|(_, path)| path).collect();
+    assert!(unique_paths.len() < portraits.len());
+}
+
+#[test]
+fn generate_recruits_includes_rogues() {
+    let mut app = make_test_app();
+    app.add_systems(Update, generate_recruits);
+    let generator = app
+        .world_mut()
+        .spawn((RecruitGenerator, TurnTimer::repeating(1)))
+        .id();
+
+    for _ in 0..40 {
+        app.world_mut()
+            .resource_mut::<Events<TurnTimerCompleteEvent>>()
+            .send(TurnTimerCompleteEvent(generator));
+        app.update();
+    }
+
+    let mut recruits_query = app
+        .world_mut()
+        .query_filtered::<&HeroClass, (With<Hero>, With<HeroStatusAvailable>)>();
+    let classes: Vec<HeroClass> = recruits_query.iter(app.world()).copied().collect();
+    assert_eq!(classes.len(), 40);
+    assert!(classes.contains(&HeroClass::Rogue));
+}
+
+#[test]
+fn generate_recruits_assigns_random_alignments() {
+    let mut app = make_test_app();
+    app.add_systems(Update, generate_recruits);
+    let generator = app
+        .world_mut()
+        .spawn((RecruitGenerator, TurnTimer::repeating(1)))
+        .id();
+
+    for _ in 0..30 {
+        app.world_mut()
+            .resource_mut::<Events<TurnTimerCompleteEvent>>()
+            .send(TurnTimerCompleteEvent(generator));
+        app.update();
+    }
+
+    let mut recruits_query = app
+        .world_mut()
+        .query_filtered::<&Alignment, (With<Hero>, With<HeroStatusAvailable>)>();
+    let alignments: Vec<Alignment> = recruits_query.iter(app.world()).copied().collect();
+    assert_eq!(alignments.len(), 30);
+    for alignment in [Alignment::Law, Alignment::Neutral, Alignment::Chaos] {
+        assert!(alignments.contains(&alignment));
+    }
+}
+
+// Levels a new recruit may have. The lowest rises by one for every 20 reputation, up to the max level of 10.
+fn recruit_level_range(reputation: i32) -> RangeInclusive<u32> {
+    let level_spread = 2; // How many levels above the lowest a recruit can be
+    let lowest_level = (1 + reputation / 20).clamp(1, 10) as u32;
+    lowest_level..=(lowest_level + level_spread).min(10)
+}
+
+#[test]
+fn recruit_level_range_rises_with_reputation() {
+    assert_eq!(recruit_level_range(0), 1..=3);
+    assert_eq!(recruit_level_range(-100), 1..=3);
+    assert_eq!(recruit_level_range(19), 1..=3);
+    assert_eq!(recruit_level_range(20), 2..=4);
+    assert_eq!(recruit_level_range(100), 6..=8);
+    assert_eq!(recruit_level_range(160), 9..=10);
+    assert_eq!(recruit_level_range(1000), 10..=10);
+}
+
+// When turn timer completes for a recruit nobody hired, they leave: despawn and notify.
+fn expire_recruit(
+    mut commands: Commands,
+    mut ev_turn_timer_complete: EventReader<TurnTimerCompleteEvent>,
+    query: Query<Entity, (With<Hero>, With<HeroStatusAvailable>)>,
+    names_query: Query<&HeroName>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for TurnTimerCompleteEvent(entity) in ev_turn_timer_complete.read() {
+        if query.get(*entity).is_ok() {
+            commands.entity(*entity).despawn();
+            ev_notify.write(NotificationEvent(format!(
+                "A recruit gave up waiting and left: {}",
+                hero_display_name(&names_query, *entity)
+            )));
+        }
+    }
+}
+
+#[test]
+fn expire_recruit_despawns_available_hero() {
+    let mut app = make_test_app();
+    app.add_systems(Update, expire_recruit);
+
+    let recruit = app
+        .world_mut()
+        .spawn((
+            Hero,
+            HeroStatusAvailable,
+            TurnTimer {
+                initial_value: 10,
+                turns_remaining: 0,
+                timer_mode: TimerMode::Once,
+            },
+        ))
+        .id();
+    // A hired hero with a completed timer (e.g. from an injury) must not be despawned
+    let hired_hero = app
+        .world_mut()
+        .spawn((
+            Hero,
+            TurnTimer {
+                initial_value: 10,
+                turns_remaining: 0,
+                timer_mode: TimerMode::Once,
+            },
+        ))
+        .id();
+
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(recruit));
+    app.world_mut()
+        .resource_mut::<Events<TurnTimerCompleteEvent>>()
+        .send(TurnTimerCompleteEvent(hired_hero));
+    app.update();
+
+    assert!(app.world().get_entity(recruit).is_err());
+    assert!(app.world().get::<Hero>(hired_hero).is_some());
+}
+
+// The most heroes the guild can hold. Each operational Barracks, up to a limit, makes room for more.
+fn hero_capacity(
+    config: &GuildConfig,
+    barracks_count: usize,
+    guild_hall: &GuildHallLevel,
+) -> usize {
+    let max_barracks = 3; // Barracks past this many don't add any more room
+    let slots_per_barracks = 5; // Heroes each Barracks makes room for
+    config.base_hero_capacity
+        + barracks_count.min(max_barracks) * slots_per_barracks
+        + guild_hall.hero_capacity_bonus()
+}
+
+#[test]
+fn hero_capacity_grows_with_each_barracks() {
+    let config = GuildConfig::default();
+    let guild_hall = GuildHallLevel::default();
+    assert_eq!(hero_capacity(&config, 0, &guild_hall), 5);
+    assert_eq!(hero_capacity(&config, 1, &guild_hall), 10);
+    assert_eq!(hero_capacity(&config, 3, &guild_hall), 20);
+    assert_eq!(hero_capacity(&config, 4, &guild_hall), 20);
+    assert_eq!(hero_capacity(&config, 1, &GuildHallLevel(4)), 15);
+}
+
+// Hire an available recruit, paying their fee from the guild's gold. The guild needs room for them.
+fn hire_hero(
+    mut commands: Commands,
+    mut ev_hire_hero: EventReader<HireHeroEvent>,
+    recruits_query: Query<&LevelState, (With<Hero>, With<HeroStatusAvailable>)>,
+    members_query: Query<(), (With<Hero>, Without<HeroStatusAvailable>)>,
+    buildings_query: BuildingOperationalQuery,
+    names_query: Query<&HeroName>,
+    config: Res<RecruitmentConfig>,
+    guild_config: Res<GuildConfig>,
+    guild_hall: Res<GuildHallLevel>,
+    mut guild: ResMut<Guild>,
+    mut stats: ResMut<GuildStats>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let barracks_count = buildings_query
+        .iter()
+        .filter(|building| building.building_type == BuildingType::Barracks)
+        .count();
+    let capacity = hero_capacity(&guild_config, barracks_count, &guild_hall);
+    // Heroes hired this update aren't members until the commands are applied, so count them here
+    let mut member_count = members_query.iter().count();
+    for HireHeroEvent(entity) in ev_hire_hero.read() {
+        let name = hero_display_name(&names_query, *entity);
+        if let Ok(level) = recruits_query.get(*entity) {
+            if member_count >= capacity {
+                ev_notify.write(NotificationEvent(format!(
+                    "No room to hire hero {}: the guild can only hold {} heroes",
+                    name, capacity
+                )));
+                continue;
+            }
+            let fee = u64::from(level.level) * u64::from(config.hiring_fee_per_level);
+            if guild.gold < fee {
+                ev_notify.write(NotificationEvent(format!(
+                    "Not enough gold to hire hero {}: costs {}, guild has {}",
+                    name, fee, guild.gold
+                )));
+                continue;
+            }
+            guild.gold = guild.gold.saturating_sub(fee);
+            stats.total_heroes_hired += 1;
+            member_count += 1;
+            // The recruit's expiry timer no longer applies once they've joined
+            commands
+                .entity(*entity)
+                .remove::<(HeroStatusAvailable, TurnTimer)>();
+            ev_notify.write(NotificationEvent(format!(
+                "Hired hero {} for {} gold",
+                name, fee
+            )));
+        } else {
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} is not available for hire",
+                name
+            )));
+        }
+    }
+}
+
+#[test]
+fn hire_hero_deducts_fee_and_removes_marker() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 250 });
+    app.add_systems(Update, hire_hero);
+
+    let recruit = app
+        .world_mut()
+        .spawn((
+            Hero,
+            HeroStatusAvailable,
+            LevelState {
+                level: 2,
+                exp_to_next: 200,
+                ..default()
+            },
+            TurnTimer {
+                initial_value: 10,
+                turns_remaining: 7,
+                timer_mode: TimerMode::Once,
+            },
+        ))
+        .id();
+
+    app.world_mut()
+        .resource_mut::<Events<HireHeroEvent>>()
+        .send(HireHeroEvent(recruit));
+    app.update();
+
+    assert_eq!(app.world().resource::<Guild>().gold, 150);
+    assert_eq!(app.world().resource::<GuildStats>().total_heroes_hired, 1);
+    assert!(app.world().get::<HeroStatusAvailable>(recruit).is_none());
+    assert!(app.world().get::<TurnTimer>(recruit).is_none());
+    assert!(app.world().get::<Hero>(recruit).is_some());
+
+    assert_eq!(
+        last_notification(&app),
+        format!("Hired hero {:?} for 100 gold", recruit)
+    );
+}
+
+#[test]
+fn hire_hero_rejects_when_guild_is_full() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 1000 });
+    app.add_systems(Update, hire_hero);
+    for _ in 0..5 {
+        spawn_hero(&mut app, HeroClass::Warrior, 1);
+    }
+    let recruit = app
+        .world_mut()
+        .spawn((Hero, HeroStatusAvailable, LevelState::default()))
+        .id();
+    app.world_mut()
+        .resource_mut::<Events<HireHeroEvent>>()
+        .send(HireHeroEvent(recruit));
+    app.update();
+
+    assert_eq!(app.world().resource::<Guild>().gold, 1000);
+    assert!(app.world().get::<HeroStatusAvailable>(recruit).is_some());
+    assert_eq!(
+        last_notification(&app),
+        format!(
+            "No room to hire hero {:?}: the guild can only hold 5 heroes",
+            recruit
+        )
+    );
+}
+
+#[test]
+fn hire_hero_counts_heroes_hired_in_the_same_update() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 1000 });
+    app.add_systems(Update, hire_hero);
+    for _ in 0..4 {
+        spawn_hero(&mut app, HeroClass::Warrior, 1);
+    }
+    let recruit = app
+        .world_mut()
+        .spawn((Hero, HeroStatusAvailable, LevelState::default()))
+        .id();
+    let second_recruit = app
+        .world_mut()
+        .spawn((Hero, HeroStatusAvailable, LevelState::default()))
+        .id();
+    let mut events = app.world_mut().resource_mut::<Events<HireHeroEvent>>();
+    events.send(HireHeroEvent(recruit));
+    events.send(HireHeroEvent(second_recruit));
+    app.update();
+
+    assert!(app.world().get::<HeroStatusAvailable>(recruit).is_none());
+    assert!(
+        app.world()
+            .get::<HeroStatusAvailable>(second_recruit)
+            .is_some()
+    );
+}
+
+#[test]
+fn hire_hero_has_room_once_barracks_is_built() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 1000 });
+    app.add_systems(Update, hire_hero);
+    for _ in 0..5 {
+        spawn_hero(&mut app, HeroClass::Warrior, 1);
+    }
+    let recruit = app
+        .world_mut()
+        .spawn((Hero, HeroStatusAvailable, LevelState::default()))
+        .id();
+    app.world_mut().spawn((
+        GuildBuilding {
+            building_type: BuildingType::Barracks,
+        },
+        Operational,
+    ));
+    app.world_mut()
+        .resource_mut::<Events<HireHeroEvent>>()
+        .send(HireHeroEvent(recruit));
+    app.update();
+
+    assert!(app.world().get::<HeroStatusAvailable>(recruit).is_none());
+    assert_eq!(app.world().resource::<GuildStats>().total_heroes_hired, 1);
+}
+
+#[test]
+fn hire_hero_rejects_when_gold_is_insufficient() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 99 });
+    app.add_systems(Update, hire_hero);
+
+    let recruit = app
+        .world_mut()
+        .spawn((
+            Hero,
+            HeroStatusAvailable,
+            LevelState {
+                level: 2,
+                exp_to_next: 200,
+                ..default()
+            },
+        ))
+        .id();
+
+    app.world_mut()
+        .resource_mut::<Events<HireHeroEvent>>()
+        .send(HireHeroEvent(recruit));
+    app.update();
+
+    // Check that nothing changed, and the player was told why
+    assert_eq!(app.world().resource::<Guild>().gold, 99);
+    assert_eq!(app.world().resource::<GuildStats>().total_heroes_hired, 0);
+    assert!(app.world().get::<HeroStatusAvailable>(recruit).is_some());
+
+    assert_eq!(
+        last_notification(&app),
+        format!(
+            "Not enough gold to hire hero {:?}: costs 100, guild has 99",
+            recruit
+        )
+    );
+}
+
+// Send a hero away from the guild, refunding part of what they cost. A hero on a quest leaves their party,
+// and the quest carries on without them. Anyone who has shared a quest with them is saddened to see them go.
+fn dismiss_hero(
+    mut commands: Commands,
+    mut ev_dismiss_hero: EventReader<DismissHeroEvent>,
+    members_query: Query<
+        (&LevelState, Option<&ChildOf>, Has<Injured>, Has<Fatigued>),
+        (With<Hero>, Without<HeroStatusAvailable>),
+    >,
+    mut others_query: Query<(Entity, &mut Morale, &Person, Option<&ChildOf>), With<Hero>>,
+    names_query: Query<&HeroName>,
+    mut guild: ResMut<Guild>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let refund_per_level: u64 = 25; // Gold refunded for each of the dismissed hero's levels
+    let morale_penalty = 5; // Morale lost by each hero who shared a quest with the dismissed hero
+    for DismissHeroEvent(hero) in ev_dismiss_hero.read() {
+        let name = hero_display_name(&names_query, *hero);
+        let Ok((level, child_of, is_injured, is_fatigued)) = members_query.get(*hero) else {
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} is not in the guild, and can't be dismissed",
+                name
+            )));
+            continue;
+        };
+        if is_injured {
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} was dismissed while injured",
+                name
+            )));
+        } else if is_fatigued {
+            ev_notify.write(NotificationEvent(format!(
+                "Hero {} was dismissed while fatigued",
+                name
+            )));
+        }
+        let quest = child_of.map(|child_of| child_of.parent());
+        for (other, mut morale, person, other_child_of) in others_query.iter_mut() {
+            if other == *hero {
+                continue;
+            }
+            let on_same_quest =
+                quest.is_some() && other_child_of.map(|child_of| child_of.parent()) == quest;
+            if on_same_quest || person.relationships.contains_key(hero) {
+                morale.0 = (morale.0 - morale_penalty).max(0);
+            }
+        }
+        let refund = u64::from(level.level) * refund_per_level;
+        guild.gold = guild.gold.saturating_add(refund);
+        commands.entity(*hero).remove::<ChildOf>().despawn();
+        ev_notify.write(NotificationEvent(format!(
+            "Dismissed hero {}, refunding {} gold",
+            name, refund
+        )));
+    }
+}
+
+#[cfg(test)]
+fn send_dismiss_hero(app: &mut App, hero: Entity) {
+    app.world_mut()
+        .resource_mut::<Events<DismissHeroEvent>>()
+        .send(DismissHeroEvent(hero));
+    app.update();
+}
+
+#[test]
+fn dismiss_hero_refunds_gold_and_saddens_past_party_members() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 100 });
+    app.add_systems(Update, dismiss_hero);
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 2);
+    let old_party_member = spawn_hero(&mut app, HeroClass::Mage, 1);
+    app.world_mut()
+        .get_mut::<Person>(old_party_member)
+        .unwrap()
+        .relationships
+        .insert(hero, 10);
+    let stranger = spawn_hero(&mut app, HeroClass::Rogue, 1);
+
+    send_dismiss_hero(&mut app, hero);
+
+    assert!(app.world().get_entity(hero).is_err());
+    assert_eq!(app.world().resource::<Guild>().gold, 150);
+    assert_eq!(app.world().get::<Morale>(old_party_member).unwrap().0, 45);
+    assert_eq!(app.world().get::<Morale>(stranger).unwrap().0, 50);
+    assert_eq!(
+        last_notification(&app),
+        format!("Dismissed hero {:?}, refunding 50 gold", hero)
+    );
+}
+
+#[test]
+fn dismiss_hero_on_quest_leaves_quest_to_continue() {
+    let mut app = make_test_app();
+    app.add_systems(Update, dismiss_hero);
+    let quest = spawn_quest(&mut app, 1, 10);
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    let party_member = spawn_hero(&mut app, HeroClass::Mage, 1);
+    link_hero_to_quest(&mut app, hero, quest);
+    link_hero_to_quest(&mut app, party_member, quest);
+
+    send_dismiss_hero(&mut app, hero);
+
+    assert!(app.world().get_entity(hero).is_err());
+    assert!(app.world().get_entity(quest).is_ok());
+    assert_eq!(
+        app.world().get::<ChildOf>(party_member).unwrap().parent(),
+        quest
+    );
+    assert_eq!(app.world().get::<Morale>(party_member).unwrap().0, 45);
+    assert_eq!(app.world().resource::<Guild>().gold, 25);
+}
+
+#[test]
+fn dismiss_hero_warns_about_injured_or_fatigued_heroes() {
+    let mut app = make_test_app();
+    app.add_systems(Update, dismiss_hero);
+    let injured_hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.world_mut().entity_mut(injured_hero).insert(Injured);
+    let fatigued_hero = spawn_hero(&mut app, HeroClass::Mage, 1);
+    app.world_mut()
+        .entity_mut(fatigued_hero)
+        .insert(Fatigued(TurnTimer::new(3)));
+
+    send_dismiss_hero(&mut app, injured_hero);
+    send_dismiss_hero(&mut app, fatigued_hero);
+
+    assert!(app.world().get_entity(injured_hero).is_err());
+    assert!(app.world().get_entity(fatigued_hero).is_err());
+    let notification_events = app.world().resource::<Events<NotificationEvent>>();
+    let mut reader = notification_events.get_cursor();
+    let notifications: Vec<_> = reader
+        .read(notification_events)
+        .map(|notification| notification.0.clone())
+        .collect();
+    assert!(notifications.contains(&format!(
+        "Hero {:?} was dismissed while injured",
+        injured_hero
+    )));
+    assert!(notifications.contains(&format!(
+        "Hero {:?} was dismissed while fatigued",
+        fatigued_hero
+    )));
+}
+
+#[test]
+fn dismiss_hero_ignores_missing_hero() {
+    let mut app = make_test_app();
+    app.add_systems(Update, dismiss_hero);
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    app.world_mut().despawn(hero);
+
+    send_dismiss_hero(&mut app, hero);
+
+    assert_eq!(app.world().resource::<Guild>().gold, 0);
+    assert_eq!(
+        last_notification(&app),
+        format!(
+            "Hero {:?} is not in the guild, and can't be dismissed",
+            hero
+        )
+    );
+}
+
+// Heroes salary removed from guild gold every turn. Any debt is paid off first, from whatever gold the guild
+// has come by since, and whatever payroll the guild can't cover is added to its debt.
+fn deduct_hero_salaries(
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    heroes_query: Query<&LevelState, (With<Hero>, Without<HeroStatusAvailable>)>,
+    config: Res<GuildConfig>,
+    mut guild: ResMut<Guild>,
+    mut debt: ResMut<GuildDebt>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let turn_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    if turn_delta == 0 {
+        return;
+    }
+    let salary_per_turn: u64 = heroes_query
+        .iter()
+        .map(|level| u64::from(level.level) * u64::from(config.gold_per_level_per_turn))
+        .sum();
+    let total_salary = salary_per_turn.saturating_mul(u64::from(turn_delta));
+    guild.gold = debt.pay_down(guild.gold);
+    if guild.gold < total_salary {
+        ev_notify.write(NotificationEvent(format!(
+            "The guild couldn't cover payroll: owed {} gold, but only had {}",
+            total_salary, guild.gold
+        )));
+        debt.amount = debt.amount.saturating_add(total_salary - guild.gold);
+    }
+    guild.gold = guild.gold.saturating_sub(total_salary);
+    if debt.amount > 0 {
+        debt.turns_in_debt += turn_delta;
+    }
+}
+
+#[test]
+fn deduct_hero_salaries_deducts_salary_for_each_turn() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 1000 });
+    app.add_systems(Update, deduct_hero_salaries);
+
+    for level in [1, 3] {
+        app.world_mut()
+            .spawn((Hero, LevelState { level, ..default() }));
+    }
+    // Recruits who haven't been hired aren't paid
+    app.world_mut().spawn((
+        Hero,
+        HeroStatusAvailable,
+        LevelState {
+            level: 5,
+            ..default()
+        },
+    ));
+
+    send_turns(&mut app, 3);
+
+    // (1 * 10 + 3 * 10) gold per turn, for 3 turns
+    assert_eq!(app.world().resource::<Guild>().gold, 880);
+    assert_eq!(app.world().resource::<GuildDebt>().amount, 0);
+    assert_eq!(app.world().resource::<GuildDebt>().turns_in_debt, 0);
+    let notification_events = app.world().resource::<Events<NotificationEvent>>();
+    assert!(notification_events.is_empty());
+}
+
+#[test]
+fn deduct_hero_salaries_handles_huge_salaries() {
+    let mut app = make_test_app();
+    app.insert_resource(GuildConfig {
+        gold_per_level_per_turn: u32::MAX,
+        ..default()
+    });
+    app.add_systems(Update, deduct_hero_salaries);
+    spawn_hero(&mut app, HeroClass::Warrior, 2);
+
+    send_turns(&mut app, 1);
+
+    assert_eq!(
+        app.world().resource::<GuildDebt>().amount,
+        2 * u64::from(u32::MAX)
+    );
+}
+
+#[test]
+fn deduct_hero_salaries_follows_config_changes() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 1000 });
+    app.add_systems(Update, deduct_hero_salaries);
+    app.world_mut().spawn((
+        Hero,
+        LevelState {
+            level: 2,
+            ..default()
+        },
+    ));
+
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().resource::<Guild>().gold, 980);
+
+    // A pay rise applies from the next turn
+    app.world_mut()
+        .resource_mut::<GuildConfig>()
+        .gold_per_level_per_turn = 25;
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().resource::<Guild>().gold, 930);
+}
+
+#[test]
+fn deduct_hero_salaries_doesnt_go_below_zero() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 50 });
+    app.add_systems(Update, deduct_hero_salaries);
+
+    app.world_mut().spawn((
+        Hero,
+        LevelState {
+            level: 2,
+            ..default()
+        },
+    ));
+
+    send_turns(&mut app, 5);
+
+    assert_eq!(app.world().resource::<Guild>().gold, 0);
+    // The 50 gold the guild was short is owed
+    assert_eq!(app.world().resource::<GuildDebt>().amount, 50);
+    assert_eq!(app.world().resource::<GuildDebt>().turns_in_debt, 5);
+
+    // Check that the guild was warned about the shortfall
+    assert_eq!(
+        last_notification(&app),
+        "The guild couldn't cover payroll: owed 100 gold, but only had 50"
+    );
+}
+
+#[test]
+fn deduct_hero_salaries_pays_off_debt_from_the_treasury() {
+    let mut app = make_test_app();
+    app.insert_resource(GuildConfig {
+        bankruptcy_threshold: 2,
+        ..default()
+    });
+    // Gold earned outside of quests, such as from sales, leaves the treasury full despite the debt
+    app.insert_resource(Guild { gold: 500 });
+    app.insert_resource(GuildDebt {
+        amount: 50,
+        turns_in_debt: 3,
+    });
+    app.add_systems(
+        Update,
+        (deduct_hero_salaries, check_victory_conditions).chain(),
+    );
+    spawn_hero(&mut app, HeroClass::Warrior, 1);
+
+    assert_eq!(read_victory_test_game_over(&mut app), None);
+    // The debt is paid, then the 10 gold payroll
+    assert_eq!(app.world().resource::<Guild>().gold, 440);
+    assert_eq!(app.world().resource::<GuildDebt>().amount, 0);
+    assert_eq!(app.world().resource::<GuildDebt>().turns_in_debt, 0);
+}
+
+#[test]
+fn deduct_hero_salaries_bankrupts_guild_in_debt_past_threshold() {
+    let mut app = make_test_app();
+    app.insert_resource(GuildConfig {
+        bankruptcy_threshold: 2,
+        ..default()
+    });
+    app.add_systems(
+        Update,
+        (deduct_hero_salaries, check_victory_conditions).chain(),
+    );
+    spawn_hero(&mut app, HeroClass::Warrior, 1);
+
+    // The guild has no gold, so falls further into debt every turn
+    assert_eq!(read_victory_test_game_over(&mut app), None);
+    assert_eq!(read_victory_test_game_over(&mut app), None);
+    assert_eq!(
+        read_victory_test_game_over(&mut app),
+        Some(GameOverReason::Bankruptcy)
+    );
+    assert_eq!(app.world().resource::<GuildDebt>().amount, 30);
+}
+
+// The guild's heroes eat through its supplies every turn, and their morale suffers on every turn that starts with
+// the supplies run out, until the guild resupplies.
+fn consume_supplies(
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    mut morale_query: Query<&mut Morale, (With<Hero>, Without<HeroStatusAvailable>)>,
+    config: Res<ConsumptionConfig>,
+    mut supplies: ResMut<Supplies>,
+) {
+    let hunger_penalty = 5; // Morale lost by each hero on each turn without supplies
+    let turn_delta: u32 = ev_turn_delta.read().map(|e| e.0).sum();
+    let hero_count = morale_query.iter().count() as u32;
+    let supplies_per_turn = hero_count * config.supplies_per_hero_per_turn;
+    for _ in 0..turn_delta {
+        if supplies.0 == 0 {
+            for mut morale in morale_query.iter_mut() {
+                morale.0 = (morale.0 - hunger_penalty).max(0);
+            }
+        }
+        supplies.0 = supplies.0.saturating_sub(supplies_per_turn);
+    }
+}
+
+#[test]
+fn consume_supplies_deducts_supplies_for_each_hero_and_turn() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 100 });
+    app.insert_resource(Supplies(50));
+    app.insert_resource(ConsumptionConfig {
+        supplies_per_hero_per_turn: 2,
+        gold_per_supply: 5,
+    });
+    app.add_systems(Update, (buy_supplies, consume_supplies).chain());
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+    spawn_hero(&mut app, HeroClass::Mage, 1);
+    // Recruits who haven't been hired don't eat the guild's supplies
+    let recruit = spawn_hero(&mut app, HeroClass::Rogue, 1);
+    app.world_mut()
+        .entity_mut(recruit)
+        .insert(HeroStatusAvailable);
+
+    send_turns(&mut app, 3);
+
+    // 2 heroes eating 2 supplies each, for 3 turns
+    assert_eq!(app.world().resource::<Supplies>().0, 38);
+    assert_eq!(app.world().get::<Morale>(hero).unwrap().0, 50);
+}
+
+#[test]
+fn consume_supplies_lowers_morale_until_resupplied() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 100 });
+    app.insert_resource(Supplies(2));
+    app.insert_resource(ConsumptionConfig {
+        supplies_per_hero_per_turn: 2,
+        gold_per_supply: 5,
+    });
+    app.add_systems(Update, (buy_supplies, consume_supplies).chain());
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+
+    // The first turn uses the last of the supplies, and the heroes go hungry on the next two
+    send_turns(&mut app, 3);
+    assert_eq!(app.world().resource::<Supplies>().0, 0);
+    assert_eq!(app.world().get::<Morale>(hero).unwrap().0, 40);
+
+    app.world_mut()
+        .resource_mut::<Events<BuySuppliesEvent>>()
+        .send(BuySuppliesEvent { amount: 10 });
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().resource::<Guild>().gold, 50);
+    assert_eq!(app.world().resource::<Supplies>().0, 8);
+    assert_eq!(app.world().get::<Morale>(hero).unwrap().0, 40);
+}
+
+// Buy supplies for the guild, paying for them from the guild's gold.
+fn buy_supplies(
+    mut ev_buy_supplies: EventReader<BuySuppliesEvent>,
+    config: Res<ConsumptionConfig>,
+    mut guild: ResMut<Guild>,
+    mut supplies: ResMut<Supplies>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for BuySuppliesEvent { amount } in ev_buy_supplies.read() {
+        let cost = u64::from(*amount) * u64::from(config.gold_per_supply);
+        if guild.gold < cost {
+            ev_notify.write(NotificationEvent(format!(
+                "Not enough gold to buy {} supplies: costs {}, guild has {}",
+                amount, cost, guild.gold
+            )));
+            continue;
+        }
+        guild.gold -= cost;
+        supplies.0 = supplies.0.saturating_add(*amount);
+    }
+}
+
+#[test]
+fn consume_supplies_spares_morale_while_any_supplies_remain() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 100 });
+    app.insert_resource(Supplies(3));
+    app.insert_resource(ConsumptionConfig {
+        supplies_per_hero_per_turn: 2,
+        gold_per_supply: 5,
+    });
+    app.add_systems(Update, (buy_supplies, consume_supplies).chain());
+    let hero = spawn_hero(&mut app, HeroClass::Warrior, 1);
+
+    // The second turn is short of a full ration, but the heroes eat the last supply instead of going hungry
+    send_turns(&mut app, 2);
+    assert_eq!(app.world().resource::<Supplies>().0, 0);
+    assert_eq!(app.world().get::<Morale>(hero).unwrap().0, 50);
+}
+
+#[test]
+fn buy_supplies_saturates_supplies() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 100 });
+    app.insert_resource(Supplies(u32::MAX - 5));
+    app.insert_resource(ConsumptionConfig {
+        supplies_per_hero_per_turn: 2,
+        gold_per_supply: 5,
+    });
+    app.add_systems(Update, (buy_supplies, consume_supplies).chain());
+
+    app.world_mut()
+        .resource_mut::<Events<BuySuppliesEvent>>()
+        .send(BuySuppliesEvent { amount: 10 });
+    app.update();
+
+    assert_eq!(app.world().resource::<Guild>().gold, 50);
+    assert_eq!(app.world().resource::<Supplies>().0, u32::MAX);
+}
+
+#[test]
+fn buy_supplies_rejects_purchase_without_enough_gold() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 100 });
+    app.insert_resource(Supplies(0));
+    app.insert_resource(ConsumptionConfig {
+        supplies_per_hero_per_turn: 2,
+        gold_per_supply: 5,
+    });
+    app.add_systems(Update, (buy_supplies, consume_supplies).chain());
+
+    app.world_mut()
+        .resource_mut::<Events<BuySuppliesEvent>>()
+        .send(BuySuppliesEvent { amount: 21 });
+    app.update();
+
+    assert_eq!(app.world().resource::<Guild>().gold, 100);
+    assert_eq!(app.world().resource::<Supplies>().0, 0);
+    assert_eq!(
+        last_notification(&app),
+        "Not enough gold to buy 21 supplies: costs 105, guild has 100"
+    );
+}
+
+// Start constructing a building, paying its cost from the guild's gold. The building isn't usable until its TurnTimer completes.
+fn start_construction(
+    mut commands: Commands,
+    mut ev_build_building: EventReader<BuildBuildingEvent>,
+    config: Res<GuildBuildingConfig>,
+    mut guild: ResMut<Guild>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for BuildBuildingEvent(building_type) in ev_build_building.read() {
+        let Some(cost) = config.costs.get(building_type) else {
+            continue;
+        };
+        if guild.gold < cost.gold_cost {
+            ev_notify.write(NotificationEvent(format!(
+                "Not enough gold to build a {}: costs {}, guild has {}",
+                building_type, cost.gold_cost, guild.gold
+            )));
+            continue;
+        }
+        guild.gold = guild.gold.saturating_sub(cost.gold_cost);
+        commands.spawn((
+            GuildBuilding {
+                building_type: *building_type,
+            },
+            TurnTimer::new(cost.construction_turns),
+        ));
+        ev_notify.write(NotificationEvent(format!(
+            "Started building a {}, ready in {} turns",
+            building_type, cost.construction_turns
+        )));
+    }
+}
+
+// When a building's construction timer completes, it becomes operational.
+fn complete_construction(
+    mut commands: Commands,
+    mut ev_turn_timer_complete: EventReader<TurnTimerCompleteEvent>,
+    query: Query<&GuildBuilding, Without<Operational>>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for TurnTimerCompleteEvent(entity) in ev_turn_timer_complete.read() {
+        if let Ok(building) = query.get(*entity) {
+            commands
+                .entity(*entity)
+                .remove::<TurnTimer>()
+                .insert(Operational);
+            ev_notify.write(NotificationEvent(format!(
+                "The {} has finished construction",
+                building.building_type
+            )));
+        }
+    }
+}
+
+#[cfg(test)]
+fn make_construction_test_app(gold: u64) -> App {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold });
+    app.add_systems(
+        Update,
+        (
+            start_construction,
+            advance_turn_timer,
+            complete_construction,
+        )
+            .chain(),
+    );
+    app
+}
+
+#[cfg(test)]
+fn count_buildings(app: &mut App) -> usize {
+    app.world_mut()
+        .query::<&GuildBuilding>()
+        .iter(app.world())
+        .count()
+}
+
+#[test]
+fn start_construction_rejects_insufficient_gold() {
+    // A Tavern costs 200
+    let mut app = make_construction_test_app(199);
+    app.world_mut()
+        .resource_mut::<Events<BuildBuildingEvent>>()
+        .send(BuildBuildingEvent(BuildingType::Tavern));
+    app.update();
+
+    assert_eq!(app.world().resource::<Guild>().gold, 199);
+    assert_eq!(count_buildings(&mut app), 0);
+
+    assert_eq!(
+        last_notification(&app),
+        "Not enough gold to build a Tavern: costs 200, guild has 199"
+    );
+}
+
+#[test]
+fn start_construction_deducts_gold_and_spawns_building() {
+    let mut app = make_construction_test_app(250);
+    app.world_mut()
+        .resource_mut::<Events<BuildBuildingEvent>>()
+        .send(BuildBuildingEvent(BuildingType::Tavern));
+    app.update();
+
+    assert_eq!(app.world().resource::<Guild>().gold, 50);
+    let mut buildings_query = app
+        .world_mut()
+        .query::<(&GuildBuilding, &TurnTimer, Has<Operational>)>();
+    let buildings: Vec<(&GuildBuilding, &TurnTimer, bool)> =
+        buildings_query.iter(app.world()).collect();
+    assert_eq!(buildings.len(), 1);
+    let (building, timer, is_operational) = buildings[0];
+    assert_eq!(building.building_type, BuildingType::Tavern);
+    assert_eq!(timer.turns_remaining, 8);
+    assert!(!is_operational);
+}
+
+#[test]
+fn complete_construction_makes_building_operational_after_construction_turns() {
+    let mut app = make_construction_test_app(250);
+    app.world_mut()
+        .resource_mut::<Events<BuildBuildingEvent>>()
+        .send(BuildBuildingEvent(BuildingType::Tavern));
+    app.update();
+
+    let mut operational_query = app
+        .world_mut()
+        .query_filtered::<Entity, (With<GuildBuilding>, With<Operational>)>();
+
+    // A Tavern takes 8 turns to build
+    for _ in 0..7 {
+        send_turns(&mut app, 1);
+    }
+    assert_eq!(operational_query.iter(app.world()).count(), 0);
+
+    send_turns(&mut app, 1);
+    assert_eq!(operational_query.iter(app.world()).count(), 1);
+}
+
+// Work out which building synergies are active, whenever a building becomes operational or is destroyed.
+fn compute_building_synergies(
+    added_query: Query<(), (With<GuildBuilding>, Added<Operational>)>,
+    mut removed_operational: RemovedComponents<Operational>,
+    buildings_query: Query<&GuildBuilding, With<Operational>>,
+    config: Res<BuildingSynergyConfig>,
+    mut synergies: ResMut<ActiveSynergies>,
+) {
+    // Always drain the removals, so an old one isn't mistaken for a new one later
+    let any_removed = removed_operational.read().count() > 0;
+    if added_query.is_empty() && !any_removed {
+        return;
+    }
+    let operational: HashSet<BuildingType> = buildings_query
+        .iter()
+        .map(|building| building.building_type)
+        .collect();
+    synergies.0 = config
+        .synergies
+        .iter()
+        .filter(|(first, second, _)| operational.contains(first) && operational.contains(second))
+        .map(|(_, _, bonus)| *bonus)
+        .collect();
+}
+
+#[cfg(test)]
+fn spawn_operational_building(app: &mut App, building_type: BuildingType) -> Entity {
+    app.world_mut()
+        .spawn((GuildBuilding { building_type }, Operational))
+        .id()
+}
+
+#[test]
+fn compute_building_synergies_requires_both_buildings() {
+    let mut app = make_test_app();
+    app.add_systems(Update, compute_building_synergies);
+
+    spawn_operational_building(&mut app, BuildingType::Tavern);
+    // A Barracks still under construction doesn't count
+    let barracks = app
+        .world_mut()
+        .spawn(GuildBuilding {
+            building_type: BuildingType::Barracks,
+        })
+        .id();
+    app.update();
+    assert!(app.world().resource::<ActiveSynergies>().0.is_empty());
+
+    app.world_mut().entity_mut(barracks).insert(Operational);
+    app.update();
+    assert_eq!(
+        app.world().resource::<ActiveSynergies>().0,
+        vec![SynergyBonus::MoraleRecovery(Percent(50))]
+    );
+
+    spawn_operational_building(&mut app, BuildingType::Library);
+    spawn_operational_building(&mut app, BuildingType::TrainingGround);
+    app.update();
+    let synergies = app.world().resource::<ActiveSynergies>();
+    assert_eq!(synergies.morale_recovery_bonus(), Percent(50));
+    assert_eq!(synergies.exp_gain_bonus(), Percent(20));
+}
+
+#[test]
+fn compute_building_synergies_deactivates_when_building_is_destroyed() {
+    let mut app = make_test_app();
+    app.add_systems(Update, compute_building_synergies);
+    spawn_operational_building(&mut app, BuildingType::Library);
+    let training_ground = spawn_operational_building(&mut app, BuildingType::TrainingGround);
+    app.update();
+    assert_eq!(
+        app.world().resource::<ActiveSynergies>().0,
+        vec![SynergyBonus::ExpGain(Percent(20))]
+    );
+
+    app.world_mut().despawn(training_ground);
+    app.update();
+    assert!(app.world().resource::<ActiveSynergies>().0.is_empty());
+}
+
+#[test]
+fn active_synergies_boost_morale_recovery_and_exp() {
+    let mut app = make_test_app();
+    app.insert_resource(MoraleConfig {
+        recovery_rate: 2,
+        ..default()
+    });
+    app.insert_resource(ActiveSynergies(vec![
+        SynergyBonus::MoraleRecovery(Percent(50)),
+        SynergyBonus::ExpGain(Percent(20)),
+    ]));
+    app.add_systems(Update, (recover_hero_morale, complete_quest_assign_exp));
+    let hero = app
+        .world_mut()
+        .spawn((Hero, Morale(95), LevelState::default()))
+        .id();
+
+    app.world_mut()
+        .resource_mut::<Events<TurnDeltaEvent>>()
+        .send(TurnDeltaEvent(5));
+    send_quest_complete(&mut app, vec![hero], true);
+
+    // 2 morale a turn for 5 turns, half again as fast
+    assert_eq!(app.world().get::<Morale>(hero).unwrap().0, 80);
+    // The quest's 50 exp, plus a fifth
+    assert_eq!(app.world().get::<LevelState>(hero).unwrap().exp, 60);
+}
+
+// Start upgrading the guild hall to its next level, paying for it from the guild's gold.
+// Each level costs more gold and takes longer to build than the last.
+fn start_guild_hall_upgrade(
+    mut commands: Commands,
+    mut ev_upgrade_guild_hall: EventReader<UpgradeGuildHallEvent>,
+    upgrades_query: Query<(), With<GuildHallUpgrade>>,
+    guild_hall: Res<GuildHallLevel>,
+    mut guild: ResMut<Guild>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    let gold_per_level = 500; // Upgrade cost for each level the guild hall already has
+    let turns_per_level = 10; // Construction turns for each level the guild hall already has
+    // Only the first request counts, since only one upgrade can be underway at a time
+    let mut upgrade_started = !upgrades_query.is_empty();
+    for _ in ev_upgrade_guild_hall.read() {
+        if upgrade_started {
+            ev_notify.write(NotificationEvent(
+                "The guild hall is already being upgraded".to_string(),
+            ));
+            continue;
+        }
+        if guild_hall.0 >= GuildHallLevel::MAX_LEVEL {
+            ev_notify.write(NotificationEvent(
+                "The guild hall is already at its highest level".to_string(),
+            ));
+            continue;
+        }
+        let cost = gold_per_level * u64::from(guild_hall.0);
+        if guild.gold < cost {
+            ev_notify.write(NotificationEvent(format!(
+                "Not enough gold to upgrade the guild hall: costs {}, guild has {}",
+                cost, guild.gold
+            )));
+            continue;
+        }
+        guild.gold = guild.gold.saturating_sub(cost);
+        let construction_turns = turns_per_level * guild_hall.0;
+        commands.spawn((GuildHallUpgrade, TurnTimer::new(construction_turns)));
+        upgrade_started = true;
+        ev_notify.write(NotificationEvent(format!(
+            "Started upgrading the guild hall to level {}, ready in {} turns",
+            guild_hall.0 + 1,
+            construction_turns
+        )));
+    }
+}
+
+// When the guild hall's upgrade timer completes, the guild hall gains a level and its unlock takes effect.
+fn complete_guild_upgrade(
+    mut commands: Commands,
+    mut ev_turn_timer_complete: EventReader<TurnTimerCompleteEvent>,
+    upgrades_query: Query<(), With<GuildHallUpgrade>>,
+    mut guild_hall: ResMut<GuildHallLevel>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for TurnTimerCompleteEvent(entity) in ev_turn_timer_complete.read() {
+        if upgrades_query.contains(*entity) {
+            commands.entity(*entity).despawn();
+            guild_hall.0 += 1;
+            ev_notify.write(NotificationEvent(format!(
+                "The guild hall reached level {}: {}",
+                guild_hall.0,
+                guild_hall.unlock_description()
+            )));
+        }
+    }
+}
+
+#[test]
+fn start_guild_hall_upgrade_deducts_gold_and_starts_timer() {
+    let app_at_level = |level: u32| {
+        let mut app = make_test_app();
+        app.insert_resource(Guild { gold: 5000 });
+        app.insert_resource(GuildHallLevel(level));
+        app.add_systems(
+            Update,
+            (
+                start_guild_hall_upgrade,
+                advance_turn_timer,
+                complete_guild_upgrade,
+            )
+                .chain(),
+        );
+        app.world_mut()
+            .resource_mut::<Events<UpgradeGuildHallEvent>>()
+            .send(UpgradeGuildHallEvent);
+        app.update();
+        let mut timers_query = app
+            .world_mut()
+            .query_filtered::<&TurnTimer, With<GuildHallUpgrade>>();
+        let turns: Vec<u32> = timers_query
+            .iter(app.world())
+            .map(|timer| timer.turns_remaining)
+            .collect();
+        (app.world().resource::<Guild>().gold, turns)
+    };
+
+    assert_eq!(app_at_level(1), (4500, vec![10]));
+    assert_eq!(app_at_level(3), (3500, vec![30]));
+    // There's nothing left to unlock at the highest level
+    assert_eq!(app_at_level(5), (5000, vec![]));
+}
+
+#[test]
+fn start_guild_hall_upgrade_rejects_insufficient_gold() {
+    // Upgrading from level 2 costs 1000
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 999 });
+    app.insert_resource(GuildHallLevel(2));
+    app.add_systems(
+        Update,
+        (
+            start_guild_hall_upgrade,
+            advance_turn_timer,
+            complete_guild_upgrade,
+        )
+            .chain(),
+    );
+    app.world_mut()
+        .resource_mut::<Events<UpgradeGuildHallEvent>>()
+        .send(UpgradeGuildHallEvent);
+    app.update();
+
+    assert_eq!(app.world().resource::<Guild>().gold, 999);
+    assert!(
+        app.world_mut()
+            .query::<&GuildHallUpgrade>()
+            .iter(app.world())
+            .next()
+            .is_none()
+    );
+    assert_eq!(
+        last_notification(&app),
+        "Not enough gold to upgrade the guild hall: costs 1000, guild has 999"
+    );
+}
+
+#[test]
+fn start_guild_hall_upgrade_allows_one_upgrade_at_a_time() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 5000 });
+    app.insert_resource(GuildHallLevel(1));
+    app.add_systems(
+        Update,
+        (
+            start_guild_hall_upgrade,
+            advance_turn_timer,
+            complete_guild_upgrade,
+        )
+            .chain(),
+    );
+    app.world_mut()
+        .resource_mut::<Events<UpgradeGuildHallEvent>>()
+        .send(UpgradeGuildHallEvent);
+    app.update();
+    app.world_mut()
+        .resource_mut::<Events<UpgradeGuildHallEvent>>()
+        .send(UpgradeGuildHallEvent);
+    app.update();
+
+    assert_eq!(app.world().resource::<Guild>().gold, 4500);
+}
+
+#[test]
+fn complete_guild_upgrade_raises_level_after_construction_turns() {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 500 });
+    app.insert_resource(GuildHallLevel(1));
+    app.add_systems(
+        Update,
+        (
+            start_guild_hall_upgrade,
+            advance_turn_timer,
+            complete_guild_upgrade,
+        )
+            .chain(),
+    );
+    app.world_mut()
+        .resource_mut::<Events<UpgradeGuildHallEvent>>()
+        .send(UpgradeGuildHallEvent);
+    app.update();
+
+    // Upgrading from level 1 takes 10 turns
+    for _ in 0..9 {
+        send_turns(&mut app, 1);
+    }
+    assert_eq!(app.world().resource::<GuildHallLevel>().0, 1);
+
+    send_turns(&mut app, 1);
+    assert_eq!(app.world().resource::<GuildHallLevel>().0, 2);
+    assert!(
+        app.world_mut()
+            .query::<&GuildHallUpgrade>()
+            .iter(app.world())
+            .next()
+            .is_none()
+    );
+}
+
+#[test]
+fn guild_hall_level_unlocks_accumulate() {
+    let unlocks = |level: u32| {
+        let guild_hall = GuildHallLevel(level);
+        (
+            guild_hall.quest_board_bonus(),
+            guild_hall.gold_reward_multiplier(),
+            guild_hall.hero_capacity_bonus(),
+            guild_hall.boss_quests_enabled(),
+        )
+    };
+
+    assert_eq!(unlocks(1), (0, 1.0, 0, false));
+    assert_eq!(unlocks(2), (1, 1.0, 0, false));
+    assert_eq!(unlocks(3), (1, 1.1, 0, false));
+    assert_eq!(unlocks(4), (1, 1.1, 5, false));
+    assert_eq!(unlocks(5), (1, 1.1, 5, true));
+}
+
+// Everything needed to restore a game from a save file.
+// Quests in progress aren't saved, so the game can't be saved while any are under way. Recruits, biographies,
+// companions, couples, rivals, fatigue, buildings and the guild's stats aren't saved yet either, so loaded heroes
+// come back idle and without them.
+#[derive(Serialize, Deserialize)]
+struct SaveGame {
+    turn: Turn,
+    guild: Guild,
+    rank: GuildRank,
+    reputation: GuildReputation,
+    debt: GuildDebt,
+    supplies: Supplies,
+    heroes: Vec<SavedHero>,
+    quests: Vec<SavedQuest>, // Quests that were available to start
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedQuest {
+    description: QuestDescription,
+    turns_to_expiry_remaining: u32, // So a save and load doesn't put off a quest's expiry
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedHero {
+    entity_bits: u64, // The hero's entity when saved, so relationships between heroes can be restored
+    name: String,
+    level: LevelState,
+    class: HeroClass,
+    person: Person,
+    age: Age,
+    morale: Morale,
+    quest_count: QuestCount,
+    inventory: Inventory,
+    skills: Option<Skills>,
+    alignment: Option<Alignment>,
+    faction: Option<Faction>,
+    specialization: Option<Specialization>,
+    injury_turns_remaining: Option<u32>, // Turns until an injured hero recovers
+}
+
+// The components of a hero that are written to a save file.
+#[derive(QueryData)]
+struct SavableHero {
+    entity: Entity,
+    name: &'static HeroName,
+    level: &'static LevelState,
+    class: &'static HeroClass,
+    person: &'static Person,
+    age: &'static Age,
+    morale: &'static Morale,
+    quest_count: &'static QuestCount,
+    inventory: &'static Inventory,
+    skills: Option<&'static Skills>,
+    alignment: Option<&'static Alignment>,
+    faction: Option<&'static HeroFaction>,
+    specialization: Option<&'static Specialization>,
+    injury: Option<(&'static Injured, &'static TurnTimer)>,
+}
+
+// Write the guild, its heroes and its available quests to a JSON save file, unless any quests are in progress.
+fn save_game(
+    mut ev_save: EventReader<SaveEvent>,
+    turn: Res<Turn>,
+    guild: Res<Guild>,
+    rank: Res<GuildRank>,
+    reputation: Res<GuildReputation>,
+    debt: Res<GuildDebt>,
+    supplies: Res<Supplies>,
+    heroes_query: Query<SavableHero, (With<Hero>, Without<HeroStatusAvailable>)>,
+    quests_query: Query<(&QuestDescription, &TurnTimer), (With<Quest>, With<QuestStatusAvailable>)>,
+    in_progress_query: Query<(), (With<Quest>, With<QuestStatusInProgress>)>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for SaveEvent(path) in ev_save.read() {
+        if !in_progress_query.is_empty() {
+            ev_notify.write(NotificationEvent(
+                "Can't save the game while quests are in progress".to_string(),
+            ));
+            continue;
+        }
+        let save = SaveGame {
+            turn: Turn(turn.0),
+            guild: Guild { gold: guild.gold },
+            rank: GuildRank(rank.0),
+            reputation: GuildReputation(reputation.0),
+            debt: GuildDebt {
+                amount: debt.amount,
+                turns_in_debt: debt.turns_in_debt,
+            },
+            supplies: Supplies(supplies.0),
+            heroes: heroes_query
+                .iter()
+                .map(|hero| SavedHero {
+                    entity_bits: hero.entity.to_bits(),
+                    name: hero.name.0.clone(),
+                    level: hero.level.clone(),
+                    class: *hero.class,
+                    person: hero.person.clone(),
+                    age: hero.age.clone(),
+                    morale: hero.morale.clone(),
+                    quest_count: *hero.quest_count,
+                    inventory: hero.inventory.clone(),
+                    skills: hero.skills.cloned(),
+                    alignment: hero.alignment.copied(),
+                    faction: hero.faction.map(|faction| faction.0),
+                    specialization: hero.specialization.copied(),
+                    injury_turns_remaining: hero.injury.map(|(_, timer)| timer.turns_remaining),
+                })
+                .collect(),
+            quests: quests_query
+                .iter()
+                .map(|(description, expiry)| SavedQuest {
+                    description: description.clone(),
+                    turns_to_expiry_remaining: expiry.turns_remaining,
+                })
+                .collect(),
+        };
+        let result = serde_json::to_string_pretty(&save)
+            .map_err(|err| err.to_string())
+            .and_then(|json| std::fs::write(path, json).map_err(|err| err.to_string()));
+        match result {
+            Ok(()) => ev_notify.write(NotificationEvent(format!("Game saved to {:?}", path))),
+            Err(err) => ev_notify.write(NotificationEvent(format!(
+                "Failed to save the game to {:?}: {}",
+                path, err
+            ))),
+        };
+    }
+}
+
+// Replace the current guild, heroes and quests with those from a JSON save file.
+fn load_game(
+    mut commands: Commands,
+    mut ev_load: EventReader<LoadEvent>,
+    mut turn: ResMut<Turn>,
+    mut guild: ResMut<Guild>,
+    mut rank: ResMut<GuildRank>,
+    mut reputation: ResMut<GuildReputation>,
+    mut debt: ResMut<GuildDebt>,
+    mut supplies: ResMut<Supplies>,
+    existing_query: Query<Entity, Or<(With<Hero>, With<Quest>)>>,
+    mut ev_notify: EventWriter<NotificationEvent>,
+) {
+    for LoadEvent(path) in ev_load.read() {
+        let result = std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|json| {
+                serde_json::from_str::<SaveGame>(&json).map_err(|err| err.to_string())
+            });
+        let save = match result {
+            Ok(save) => save,
+            Err(err) => {
+                ev_notify.write(NotificationEvent(format!(
+                    "Failed to load the game from {:?}: {}",
+                    path, err
+                )));
+                continue;
+            }
+        };
+
+        // Heroes on a quest are despawned along with it, so they may already be gone
+        for entity in existing_query.iter() {
+            commands.entity(entity).try_despawn();
+        }
+        *turn = save.turn;
+        *guild = save.guild;
+        *rank = save.rank;
+        *reputation = save.reputation;
+        *debt = save.debt;
+        *supplies = save.supplies;
+
+        // Reserve every hero's new entity first, so relationships can point at heroes spawned later
+        let new_entities: HashMap<u64, Entity> = save
+            .heroes
+            .iter()
+            .map(|hero| (hero.entity_bits, commands.spawn_empty().id()))
+            .collect();
+        for hero in save.heroes {
+            let relationships = hero
+                .person
+                .relationships
+                .iter()
+                .filter_map(|(other, value)| {
+                    new_entities
+                        .get(&other.to_bits())
+                        .map(|new_other| (*new_other, *value))
+                })
+                .collect();
+            let mut entity = commands.entity(new_entities[&hero.entity_bits]);
+            entity.insert(HeroBundle {
+                marker: Hero,
+                name: HeroName(hero.name),
+                age: hero.age,
+                morale: hero.morale,
+                quest_count: hero.quest_count,
+                level: hero.level,
+                class: hero.class,
+                person: Person {
+                    personality: hero.person.personality,
+                    relationships,
+                },
+                inventory: hero.inventory,
+                biography: Biography::default(),
+                skills: hero.skills.unwrap_or_default(),
+                alignment: hero.alignment.unwrap_or_default(),
+                portrait: HeroPortrait::first_for(&hero.class),
+                status: HeroStatusIdle,
+            });
+            if let Some(faction) = hero.faction {
+                entity.insert(HeroFaction(faction));
+            }
+            if let Some(specialization) = hero.specialization {
+                entity.insert(specialization);
+            }
+            if let Some(turns_remaining) = hero.injury_turns_remaining {
+                entity.insert((Injured, TurnTimer::new(turns_remaining)));
+            }
+        }
+        for quest in save.quests {
+            let progress = TurnTimer {
+                turns_remaining: quest.turns_to_expiry_remaining,
+                ..TurnTimer::new(quest.description.turns_to_expiry)
+            };
+            commands.spawn(QuestBundle {
+                marker: Quest,
+                description: quest.description,
+                progress,
+                status: QuestStatusAvailable,
+            });
+        }
+        ev_notify.write(NotificationEvent(format!("Game loaded from {:?}", path)));
+    }
+}
+
+#[test]
+fn serde_round_trips_guild_resources() {
+    let json = serde_json::to_string(&Turn(42)).unwrap();
+    assert_eq!(serde_json::from_str::<Turn>(&json).unwrap().0, 42);
+    let json = serde_json::to_string(&Guild { gold: 1234 }).unwrap();
+    assert_eq!(serde_json::from_str::<Guild>(&json).unwrap().gold, 1234);
+    let json = serde_json::to_string(&GuildRank(3)).unwrap();
+    assert_eq!(serde_json::from_str::<GuildRank>(&json).unwrap().0, 3);
+    let json = serde_json::to_string(&GuildReputation(-15)).unwrap();
+    assert_eq!(
+        serde_json::from_str::<GuildReputation>(&json).unwrap().0,
+        -15
+    );
+}
+
+#[test]
+fn serde_round_trips_hero_components() {
+    let level = LevelState {
+        level: 4,
+        exp: 120,
+        exp_to_next: 400,
+        ..default()
+    };
+    let json = serde_json::to_string(&level).unwrap();
+    let loaded: LevelState = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        (loaded.level, loaded.exp, loaded.exp_to_next),
+        (4, 120, 400)
+    );
+
+    for class in ALL_CLASSES {
+        let json = serde_json::to_string(&class).unwrap();
+        assert_eq!(serde_json::from_str::<HeroClass>(&json).unwrap(), class);
+    }
+
+    let friend = Entity::from_raw(7);
+    let rival = Entity::from_raw(8);
+    let person = Person {
+        personality: Personality::Judgmental,
+        relationships: HashMap::from([(friend, 5), (rival, -3)]),
+    };
+    let json = serde_json::to_string(&person).unwrap();
+    let loaded: Person = serde_json::from_str(&json).unwrap();
+    assert!(matches!(loaded.personality, Personality::Judgmental));
+    assert_eq!(loaded.relationships, person.relationships);
+}
+
+#[test]
+fn serde_round_trips_quests_and_items() {
+    for rarity in [
+        ItemRarity::Common,
+        ItemRarity::Uncommon,
+        ItemRarity::Rare,
+        ItemRarity::Epic,
+        ItemRarity::Legendary,
+    ] {
+        let item = Item {
+            class: HeroClass::Support,
+            rarity,
+        };
+        let json = serde_json::to_string(&item).unwrap();
+        assert_eq!(serde_json::from_str::<Item>(&json).unwrap(), item);
+    }
+
+    let description = QuestDescription {
+        name: "The Dragon's Lair".to_string(),
+        difficulty_level: 3,
+        turns_to_complete: 5,
+        exp_reward: 150,
+        gold_reward: 300,
+        item_reward: Some(Item {
+            class: HeroClass::Tank,
+            rarity: ItemRarity::Epic,
+        }),
+        turns_to_expiry: 10,
+        quest_type: QuestType::Escort,
+        min_party_size: 1,
+        max_party_size: 3,
+        faction: Some(Faction::Arcane),
+        alignment_affinity: None,
+    };
+    let json = serde_json::to_string(&description).unwrap();
+    let loaded: QuestDescription = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.name, description.name);
+    assert_eq!(loaded.difficulty_level, 3);
+    assert_eq!(loaded.turns_to_complete, 5);
+    assert_eq!(loaded.exp_reward, 150);
+    assert_eq!(loaded.gold_reward, 300);
+    assert_eq!(loaded.item_reward, description.item_reward);
+    assert_eq!(loaded.turns_to_expiry, 10);
+    assert_eq!(loaded.quest_type, QuestType::Escort);
+    assert_eq!(loaded.faction, Some(Faction::Arcane));
+}
+
+#[cfg(test)]
+fn make_save_test_app() -> App {
+    let mut app = make_test_app();
+    app.add_systems(Update, (save_game, load_game).chain());
+    app
+}
+
+#[test]
+fn save_game_and_load_game_restore_heroes_and_relationships() {
+    let path = std::env::temp_dir().join("hero_guild_bevy_save_test.json");
+    let mut app = make_save_test_app();
+    app.insert_resource(Turn(30));
+    app.insert_resource(Guild { gold: 500 });
+    app.insert_resource(GuildRank(2));
+    app.insert_resource(GuildReputation(12));
+    let spawn_hero = |app: &mut App, name: &str, level: u32| {
+        app.world_mut()
+            .spawn(HeroBundle {
+                name: HeroName(name.to_string()),
+                level: LevelState { level, ..default() },
+                ..default()
+            })
+            .id()
+    };
+    let brenna = spawn_hero(&mut app, "Brenna the Bold", 3);
+    let aldric = spawn_hero(&mut app, "Aldric the Steadfast", 5);
+    app.world_mut()
+        .get_mut::<Person>(brenna)
+        .unwrap()
+        .relationships
+        .insert(aldric, 4);
+
+    app.world_mut()
+        .resource_mut::<Events<SaveEvent>>()
+        .send(SaveEvent(path.clone()));
+    app.update();
+
+    // Load into a fresh game
+    let mut app = make_save_test_app();
+    app.world_mut()
+        .resource_mut::<Events<LoadEvent>>()
+        .send(LoadEvent(path.clone()));
+    app.update();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(app.world().resource::<Turn>().0, 30);
+    assert_eq!(app.world().resource::<Guild>().gold, 500);
+    assert_eq!(app.world().resource::<GuildRank>().0, 2);
+    assert_eq!(app.world().resource::<GuildReputation>().0, 12);
+    let mut heroes_query = app
+        .world_mut()
+        .query::<(Entity, &HeroName, &LevelState, &Person)>();
+    let heroes: HashMap<String, (Entity, u32, HashMap<Entity, i32>)> = heroes_query
+        .iter(app.world())
+        .map(|(entity, name, level, person)| {
+            (
+                name.0.clone(),
+                (entity, level.level, person.relationships.clone()),
+            )
+        })
+        .collect();
+    assert_eq!(heroes.len(), 2);
+    let (new_aldric, aldric_level, _) = &heroes["Aldric the Steadfast"];
+    let (_, brenna_level, brenna_relationships) = &heroes["Brenna the Bold"];
+    assert_eq!(*aldric_level, 5);
+    assert_eq!(*brenna_level, 3);
+    assert_eq!(*brenna_relationships, HashMap::from([(*new_aldric, 4)]));
+}
+
+#[test]
+fn save_game_and_load_game_restore_hero_state_and_supplies() {
+    let path = std::env::temp_dir().join("hero_guild_bevy_save_state_test.json");
+    let mut app = make_save_test_app();
+    app.insert_resource(GuildDebt {
+        amount: 40,
+        turns_in_debt: 2,
+    });
+    app.insert_resource(Supplies(12));
+    let mut skills = Skills::default();
+    skills.0[0].level = 2;
+    let item = Item {
+        class: HeroClass::Tank,
+        rarity: ItemRarity::Rare,
+    };
+    app.world_mut().spawn((
+        HeroBundle {
+            class: HeroClass::Tank,
+            age: Age { turns_alive: 300 },
+            morale: Morale(35),
+            quest_count: QuestCount {
+                completed: 6,
+                failed: 1,
+            },
+            inventory: Inventory {
+                items: vec![item],
+                equipped: Some(0),
+            },
+            skills: skills.clone(),
+            alignment: Alignment::Chaos,
+            ..default()
+        },
+        HeroFaction(Faction::Nature),
+        Specialization::Sentinel,
+        Injured,
+        TurnTimer::new(4),
+    ));
+
+    app.world_mut()
+        .resource_mut::<Events<SaveEvent>>()
+        .send(SaveEvent(path.clone()));
+    app.update();
+    let mut app = make_save_test_app();
+    app.world_mut()
+        .resource_mut::<Events<LoadEvent>>()
+        .send(LoadEvent(path.clone()));
+    app.update();
+    std::fs::remove_file(&path).unwrap();
+
+    let debt = app.world().resource::<GuildDebt>();
+    assert_eq!((debt.amount, debt.turns_in_debt), (40, 2));
+    assert_eq!(app.world().resource::<Supplies>().0, 12);
+    let hero = app
+        .world_mut()
+        .query_filtered::<Entity, With<Hero>>()
+        .single(app.world())
+        .unwrap();
+    let hero = app.world().entity(hero);
+    assert_eq!(hero.get::<Age>().unwrap().turns_alive, 300);
+    assert_eq!(hero.get::<Morale>().unwrap().0, 35);
+    assert_eq!(hero.get::<QuestCount>().unwrap().completed, 6);
+    assert_eq!(
+        hero.get::<Inventory>().unwrap().equipped_item(),
+        Some(&item)
+    );
+    assert_eq!(hero.get::<Skills>().unwrap().0, skills.0);
+    assert_eq!(*hero.get::<Alignment>().unwrap(), Alignment::Chaos);
+    assert_eq!(hero.get::<HeroFaction>().unwrap().0, Faction::Nature);
+    assert_eq!(
+        *hero.get::<Specialization>().unwrap(),
+        Specialization::Sentinel
+    );
+    assert!(hero.contains::<Injured>());
+    assert_eq!(hero.get::<TurnTimer>().unwrap().turns_remaining, 4);
+}
+
+#[test]
+fn save_game_and_load_game_keep_quest_expiry() {
+    let path = std::env::temp_dir().join("hero_guild_bevy_save_quest_test.json");
+    let mut app = make_save_test_app();
+    let quest = spawn_quest(&mut app, 1, 5);
+    app.world_mut()
+        .get_mut::<TurnTimer>(quest)
+        .unwrap()
+        .turns_remaining = 3;
+
+    app.world_mut()
+        .resource_mut::<Events<SaveEvent>>()
+        .send(SaveEvent(path.clone()));
+    app.update();
+    let mut app = make_save_test_app();
+    app.world_mut()
+        .resource_mut::<Events<LoadEvent>>()
+        .send(LoadEvent(path.clone()));
+    app.update();
+    std::fs::remove_file(&path).unwrap();
+
+    let (description, expiry) = app
+        .world_mut()
+        .query_filtered::<(&QuestDescription, &TurnTimer), With<Quest>>()
+        .single(app.world())
+        .unwrap();
+    assert_eq!(description.name, "The Dragon's Lair");
+    assert_eq!(expiry.turns_remaining, 3);
+}
+
+#[test]
+fn save_game_refuses_while_quests_are_in_progress() {
+    let path = std::env::temp_dir().join("hero_guild_bevy_save_in_progress_test.json");
+    let mut app = make_save_test_app();
+    let quest = spawn_quest(&mut app, 1, 10);
+    app.world_mut()
+        .entity_mut(quest)
+        .remove::<QuestStatusAvailable>()
+        .insert(QuestStatusInProgress);
+
+    app.world_mut()
+        .resource_mut::<Events<SaveEvent>>()
+        .send(SaveEvent(path.clone()));
+    app.update();
+
+    assert!(!path.exists());
+    assert_eq!(
+        last_notification(&app),
+        "Can't save the game while quests are in progress"
+    );
+}
+
+// A player input event, with entities stored as bits so a replay can be saved alongside the game.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum SerializedEvent {
+    TurnDelta(u32),
+    StartQuest { quest: u64, heroes: Vec<u64> },
+    HireHero(u64),
+    BuildBuilding(BuildingType),
+    EquipItem { hero: u64, item_index: usize },
+    UnequipItem(u64),
+    BuyItem { hero: u64, listing_index: usize },
+    SellItem { hero: u64, item_index: usize },
+}
+
+// Every input event sent during one update, ending on this turn.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct ReplayFrame {
+    turn: u32,
+    events: Vec<SerializedEvent>,
+}
+
+#[derive(Resource, Default)]
+struct ReplayRecorder {
+    frames: Vec<ReplayFrame>,
+    recording: bool,
+}
+
+// Frames still to be played back, one per update. Playback only matches the recorded game if it
+// starts from the same state, with the RandomSource seeded the same way.
+#[derive(Resource, Default)]
+struct ReplayPlayback(VecDeque<ReplayFrame>);
+
+// At the end of each update, record the input events sent during it as a frame.
+fn record_frame(
+    turn: Res<Turn>,
+    mut recorder: ResMut<ReplayRecorder>,
+    mut ev_turn_delta: EventReader<TurnDeltaEvent>,
+    mut ev_start_quest: EventReader<StartQuestEvent>,
+    mut ev_hire_hero: EventReader<HireHeroEvent>,
+    mut ev_build_building: EventReader<BuildBuildingEvent>,
+    mut ev_equip_item: EventReader<EquipItemEvent>,
+    mut ev_unequip_item: EventReader<UnequipItemEvent>,
+    mut ev_buy_item: EventReader<BuyItemEvent>,
+    mut ev_sell_item: EventReader<SellItemEvent>,
+) {
+    // Read every event even while not recording, so a recording never starts with stale input
+    let mut events: Vec<SerializedEvent> = Vec::new();
+    events.extend(
+        ev_turn_delta
+            .read()
+            .map(|TurnDeltaEvent(delta)| SerializedEvent::TurnDelta(*delta)),
+    );
+    events.extend(
+        ev_start_quest
+            .read()
+            .map(
+                |StartQuestEvent { quest, heroes }| SerializedEvent::StartQuest {
+                    quest: quest.to_bits(),
+                    heroes: heroes.iter().map(|hero| hero.to_bits()).collect(),
+                },
+            ),
+    );
+    events.extend(
+        ev_hire_hero
+            .read()
+            .map(|HireHeroEvent(hero)| SerializedEvent::HireHero(hero.to_bits())),
+    );
+    events.extend(
+        ev_build_building
+            .read()
+            .map(|BuildBuildingEvent(building_type)| {
+                SerializedEvent::BuildBuilding(*building_type)
+            }),
+    );
+    events.extend(
+        ev_equip_item
+            .read()
+            .map(
+                |EquipItemEvent { hero, item_index }| SerializedEvent::EquipItem {
+                    hero: hero.to_bits(),
+                    item_index: *item_index,
+                },
+            ),
+    );
+    events.extend(
+        ev_unequip_item
+            .read()
+            .map(|UnequipItemEvent(hero)| SerializedEvent::UnequipItem(hero.to_bits())),
+    );
+    events.extend(ev_buy_item.read().map(
+        |BuyItemEvent {
+             hero,
+             listing_index,
+         }| SerializedEvent::BuyItem {
+            hero: hero.to_bits(),
+            listing_index: *listing_index,
+        },
+    ));
+    events.extend(
+        ev_sell_item.read().map(
+            |SellItemEvent { hero, item_index }| SerializedEvent::SellItem {
+                hero: hero.to_bits(),
+                item_index: *item_index,
+            },
+        ),
+    );
+    if recorder.recording {
+        recorder.frames.push(ReplayFrame {
+            turn: turn.0,
+            events,
+        });
+    }
+}
+
+// At the start of each update, send the events of the next recorded frame, as if the player had sent them again.
+fn play_replay(
+    mut playback: ResMut<ReplayPlayback>,
+    mut ev_turn_delta: EventWriter<TurnDeltaEvent>,
+    mut ev_start_quest: EventWriter<StartQuestEvent>,
+    mut ev_hire_hero: EventWriter<HireHeroEvent>,
+    mut ev_build_building: EventWriter<BuildBuildingEvent>,
+    mut ev_equip_item: EventWriter<EquipItemEvent>,
+    mut ev_unequip_item: EventWriter<UnequipItemEvent>,
+    mut ev_buy_item: EventWriter<BuyItemEvent>,
+    mut ev_sell_item: EventWriter<SellItemEvent>,
+) {
+    let Some(frame) = playback.0.pop_front() else {
+        return;
+    };
+    for event in frame.events {
+        match event {
+            SerializedEvent::TurnDelta(delta) => {
+                ev_turn_delta.write(TurnDeltaEvent(delta));
+            }
+            SerializedEvent::StartQuest { quest, heroes } => {
+                ev_start_quest.write(StartQuestEvent {
+                    quest: Entity::from_bits(quest),
+                    heroes: heroes.into_iter().map(Entity::from_bits).collect(),
+                });
+            }
+            SerializedEvent::HireHero(hero) => {
+                ev_hire_hero.write(HireHeroEvent(Entity::from_bits(hero)));
+            }
+            SerializedEvent::BuildBuilding(building_type) => {
+                ev_build_building.write(BuildBuildingEvent(building_type));
+            }
+            SerializedEvent::EquipItem { hero, item_index } => {
+                ev_equip_item.write(EquipItemEvent {
+                    hero: Entity::from_bits(hero),
+                    item_index,
+                });
+            }
+            SerializedEvent::UnequipItem(hero) => {
+                ev_unequip_item.write(UnequipItemEvent(Entity::from_bits(hero)));
+            }
+            SerializedEvent::BuyItem {
+                hero,
+                listing_index,
+            } => {
+                ev_buy_item.write(BuyItemEvent {
+                    hero: Entity::from_bits(hero),
+                    listing_index,
+                });
+            }
+            SerializedEvent::SellItem { hero, item_index } => {
+                ev_sell_item.write(SellItemEvent {
+                    hero: Entity::from_bits(hero),
+                    item_index,
+                });
+            }
+        }
+    }
+}
+
+// A small game of two heroes, with quests and recruits turning up. Systems that share the RandomSource
+// are chained, so they draw from it in the same order every time.
+#[cfg(test)]
+fn make_replay_test_app() -> App {
+    let mut app = make_test_app();
+    app.insert_resource(Guild { gold: 1000 });
+    app.add_systems(PreUpdate, play_replay);
+    app.add_systems(
+        Update,
+        (
+            advance_turn,
+            advance_turn_timer,
+            generate_quests,
+            generate_recruits,
+            hire_hero,
+            start_quest,
+            complete_quest,
+            complete_quest_updates_guild,
+            deduct_hero_salaries,
+        )
+            .chain(),
+    );
+    app.add_systems(PostUpdate, record_frame);
+    app.world_mut()
+        .spawn((QuestGenerator, TurnTimer::repeating(2)));
+    app.world_mut()
+        .spawn((RecruitGenerator, TurnTimer::repeating(3)));
+    spawn_hero(&mut app, HeroClass::Warrior, 2);
+    spawn_hero(&mut app, HeroClass::Tank, 2);
+    app
+}
+
+// The turn, the guild's gold, and how many heroes there are, including recruits.
+#[cfg(test)]
+fn replay_test_outcome(app: &mut App) -> (u32, u64, usize) {
+    let hero_count = app
+        .world_mut()
+        .query_filtered::<(), With<Hero>>()
+        .iter(app.world())
+        .count();
+    (
+        app.world().resource::<Turn>().0,
+        app.world().resource::<Guild>().gold,
+        hero_count,
+    )
+}
+
+#[test]
+fn play_replay_reproduces_recorded_session() {
+    let mut app = make_replay_test_app();
+    app.world_mut().resource_mut::<ReplayRecorder>().recording = true;
+    for turn in 1..=10 {
+        let world = app.world_mut();
+        if turn == 3 {
+            let quest = world
+                .query_filtered::<Entity, (With<Quest>, With<QuestStatusAvailable>)>()
+                .iter(world)
+                .next()
+                .unwrap();
+            let heroes: Vec<Entity> = world
+                .query_filtered::<Entity, (With<Hero>, Without<HeroStatusAvailable>)>()
+                .iter(world)
+                .collect();
+            world.send_event(StartQuestEvent { quest, heroes });
+        }
+        if turn == 4 {
+            let recruit = world
+                .query_filtered::<Entity, With<HeroStatusAvailable>>()
+                .iter(world)
+                .next()
+                .unwrap();
+            world.send_event(HireHeroEvent(recruit));
+        }
+        world.send_event(TurnDeltaEvent(1));
+        app.update();
+    }
+    let recorded_outcome = replay_test_outcome(&mut app);
+    assert_eq!(recorded_outcome.0, 10);
+    assert_ne!(recorded_outcome.1, 1000);
+    assert!(recorded_outcome.2 >= 3);
+
+    // The recording survives being saved
+    let frames = &app.world().resource::<ReplayRecorder>().frames;
+    assert_eq!(frames.len(), 10);
+    assert_eq!(frames[2].turn, 3);
+    assert_eq!(frames[2].events.len(), 2);
+    let json = serde_json::to_string(frames).unwrap();
+    let frames: Vec<ReplayFrame> = serde_json::from_str(&json).unwrap();
+
+    let mut replay = make_replay_test_app();
+    replay.insert_resource(ReplayPlayback(frames.into()));
+    for _ in 0..10 {
+        replay.update();
+    }
+    assert!(replay.world().resource::<ReplayPlayback>().0.is_empty());
+    assert_eq!(replay_test_outcome(&mut replay), recorded_outcome);
+}
+
+// Entry points for the fuzz targets in fuzz/.
+#[cfg(fuzzing)]
+pub mod fuzzing {
+    use super::*;
+
+    // However strong or weak a party of Warriors, working out their chance at a quest never panics,
+    // and gives a chance that can be rolled.
+    pub fn check_probability_of_quest_success(difficulty_level: u32, hero_levels: &[u32]) {
+        let heroes: Vec<QuestHeroFixture> = hero_levels
+            .iter()
+            .enumerate()
+            .map(|(index, level)| QuestHeroFixture {
+                entity: Entity::from_raw(index as u32),
+                ..QuestHeroFixture::new(HeroClass::Warrior, *level)
+            })
+            .collect();
+        let probability = probability_of_quest_success(
+            difficulty_level,
+            QuestType::Bounty,
+            None,
+            None,
+            &quest_hero_items(&heroes),
+            &HeroConfig::default(),
+            &GuildConfig::default(),
+        );
+        assert!((0..=100).contains(&probability.clamped().0));
+    }
+
+    // Any Percent, however far out of range, can be turned into a distribution.
+    pub fn check_percent_distribution(value: i32) {
+        Percent(value).distribution();
+    }
+}
+
+// Entry points for the stress test in tests/, which builds the library with the stress feature.
+#[cfg(feature = "stress")]
+pub mod stress {
+    use super::*;
+
+    pub struct StressOutcome {
+        pub turn: u32,
+        pub hero_levels: Vec<u32>, // Every hero still in the guild, or waiting to be hired
+        pub quests_completed: u32, // Whether they succeeded or failed
+    }
+
+    // The whole game, without a window, joined by a crowd of random heroes and quests. Heroes are
+    // assigned to quests automatically, and nothing ends the game early, even once the guild can't
+    // pay its heroes.
+    pub fn make_stress_test_app(hero_count: usize, quest_count: usize) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, GamePlugin));
+        app.insert_resource(GuildConfig {
+            bankruptcy_threshold: u32::MAX,
+            max_rank: u32::MAX,
+            ..default()
+        });
+        app.insert_resource(QuestAutoAssign {
+            enabled: true,
+            ..default()
+        });
+        app.update(); // Run setup, and start the game
+
+        let mut rng = ChaCha8Rng::seed_from_u64(GuildConfig::default().rng_seed);
+        let level_config = LevelConfig::default();
+        for _ in 0..hero_count {
+            let level = rng.random_range(1..=5);
+            let name = HeroName(generate_hero_name(&mut rng));
+            let class = *ALL_CLASSES.choose(&mut rng).unwrap();
+            app.world_mut().spawn(HeroBundle {
+                name,
+                level: LevelState {
+                    level,
+                    exp: 0,
+                    exp_to_next: exp_required_for_level(&level_config, level),
+                },
+                class,
+                portrait: HeroPortrait::first_for(&class),
+                ..default()
+            });
+        }
+        for _ in 0..quest_count {
+            let description = QuestDescriptionBuilder::new()
+                .name(generate_quest_name(&mut rng))
+                .difficulty(rng.random_range(1..=5))
+                .build();
+            app.world_mut().spawn(QuestBundle {
+                marker: Quest,
+                progress: TurnTimer::new(description.turns_to_expiry),
+                description,
+                status: QuestStatusAvailable,
+            });
+        }
+        app
+    }
+
+    pub fn advance_stress_test_turn(app: &mut App) {
+        app.world_mut()
+            .resource_mut::<Events<TurnDeltaEvent>>()
+            .send(TurnDeltaEvent(1));
+        app.update();
+    }
+
+    pub fn stress_test_outcome(app: &mut App) -> StressOutcome {
+        let hero_levels = app
+            .world_mut()
+            .query_filtered::<&LevelState, With<Hero>>()
+            .iter(app.world())
+            .map(|level| level.level)
+            .collect();
+        StressOutcome {
+            turn: app.world().resource::<Turn>().0,
+            hero_levels,
+            quests_completed: app.world().resource::<GuildStats>().total_quests_completed,
+        }
+    }
+}
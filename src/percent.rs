@@ -0,0 +1,84 @@
+use bevy::log::warn;
+use rand::distr::Bernoulli;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, Div, Mul, Sub};
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub(crate) struct Percent(pub(crate) i32); // Represents a percentage value, normally 0-100, but we allow for negative or >100 values while adding values together.
+impl Add for Percent {
+    type Output = Percent;
+
+    fn add(self, rhs: Percent) -> Percent {
+        Percent(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl Sub for Percent {
+    type Output = Percent;
+
+    fn sub(self, rhs: Percent) -> Percent {
+        Percent(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl Sum for Percent {
+    fn sum<I: Iterator<Item = Percent>>(iter: I) -> Percent {
+        iter.fold(Percent(0), |total, percent| total + percent)
+    }
+}
+
+impl<'a> Sum<&'a Percent> for Percent {
+    fn sum<I: Iterator<Item = &'a Percent>>(iter: I) -> Percent {
+        iter.copied().sum()
+    }
+}
+
+impl Mul<i32> for Percent {
+    type Output = Percent;
+
+    fn mul(self, rhs: i32) -> Percent {
+        Percent(self.0.saturating_mul(rhs))
+    }
+}
+
+impl Div<i32> for Percent {
+    type Output = Percent;
+
+    fn div(self, rhs: i32) -> Percent {
+        if rhs == 0 {
+            warn!("Tried to divide {} by zero", self);
+            return Percent(0);
+        }
+        Percent(self.0 / rhs)
+    }
+}
+
+impl Percent {
+    pub(crate) fn distribution(&self) -> Bernoulli {
+        Bernoulli::from_ratio(self.clamped().0 as u32, 100).unwrap()
+    }
+
+    // Scale by a fractional factor, rounding to the nearest whole percent.
+    pub(crate) fn scale(self, factor: f32) -> Percent {
+        Percent((self.0 as f32 * factor).round() as i32)
+    }
+
+    pub(crate) fn clamped(self) -> Percent {
+        Percent(self.0.clamp(0, 100))
+    }
+
+    pub(crate) fn as_f32(self) -> f32 {
+        self.0 as f32 / 100.0
+    }
+
+    pub(crate) fn from_f32(f: f32) -> Percent {
+        Percent((f * 100.0).round() as i32)
+    }
+}
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}%", self.0)
+    }
+}
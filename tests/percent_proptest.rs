@@ -0,0 +1,36 @@
+// Property tests for Percent, kept apart from the unit tests in lib.rs so CI can run them in parallel.
+// Percent is private to the game, so include the module directly.
+#[allow(dead_code)]
+#[path = "../src/percent.rs"]
+mod percent;
+
+use percent::Percent;
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn clamped_is_always_between_0_and_100(a in any::<i32>()) {
+        prop_assert!((0..=100).contains(&Percent(a).clamped().0));
+    }
+
+    #[test]
+    fn as_f32_is_never_below_minus_one(a in -100..=i32::MAX) {
+        prop_assert!(Percent(a).as_f32() >= -1.0);
+    }
+
+    #[test]
+    fn from_f32_round_trips_as_f32(a in 0..=100) {
+        let round_trip = Percent::from_f32(Percent(a).as_f32());
+        prop_assert!((round_trip.0 - a).abs() <= 1, "{} came back as {}", a, round_trip);
+    }
+
+    #[test]
+    fn subtracting_undoes_adding(a in -1_000_000..=1_000_000, b in -1_000_000..=1_000_000) {
+        prop_assert_eq!(Percent(a) + Percent(b) - Percent(b), Percent(a));
+    }
+
+    #[test]
+    fn distribution_never_panics(a in any::<i32>()) {
+        Percent(a).distribution();
+    }
+}
@@ -0,0 +1,19 @@
+// Runs the whole game for a long time with a crowd of heroes, to catch entity lifecycle bugs, system
+// ordering issues and edge cases that unit tests don't reach.
+use hero_guild_bevy::stress::{
+    advance_stress_test_turn, make_stress_test_app, stress_test_outcome,
+};
+
+#[test]
+fn game_survives_1000_turns_with_50_heroes_and_20_quests() {
+    let mut app = make_stress_test_app(50, 20);
+    for _ in 0..1000 {
+        advance_stress_test_turn(&mut app);
+    }
+
+    let outcome = stress_test_outcome(&mut app);
+    assert_eq!(outcome.turn, 1000);
+    assert!(!outcome.hero_levels.is_empty());
+    assert!(outcome.hero_levels.iter().all(|level| *level >= 1));
+    assert!(outcome.quests_completed > 0);
+}